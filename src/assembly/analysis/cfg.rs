@@ -0,0 +1,265 @@
+//!
+//! The control flow graph of the linked code.
+//!
+
+use crate::assembly::instruction::metadata::{ControlFlow, InstructionMetadata, OperandRef};
+use crate::assembly::instruction::Instruction;
+use crate::assembly::linking::label_at;
+use crate::assembly::operand::{FullOperand, GenericOperand, RegisterOperand};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use zkevm_opcode_defs::{Condition, ImmMemHandlerFlags};
+
+///
+/// The straight-line sequence of instructions.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicBlock {
+    /// The PC of the first instruction.
+    pub start: usize,
+    /// The PC after the last instruction.
+    pub end: usize,
+    /// The blocks of the same function the control can be passed to.
+    pub successors: Vec<usize>,
+    /// The entries of the functions called with `near_call`.
+    pub calls: Vec<usize>,
+    /// Whether the block ends with a jump to a computed address, so the successors are unknown.
+    pub has_indirect_jump: bool,
+}
+
+///
+/// The function, which is either the entry point or a `near_call` target.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function {
+    /// The label of the entry, if there is one.
+    pub name: Option<String>,
+    /// The PC of the entry block.
+    pub entry: usize,
+    /// The blocks reachable from the entry.
+    pub blocks: BTreeSet<usize>,
+    /// The edges closing the loops, found by the depth-first search from the entry.
+    pub back_edges: BTreeSet<(usize, usize)>,
+}
+
+impl Function {
+    ///
+    /// Whether the function contains loops, so its cost is not bounded statically.
+    ///
+    pub fn has_loops(&self) -> bool {
+        !self.back_edges.is_empty()
+    }
+}
+
+///
+/// The control flow graph.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControlFlowGraph {
+    /// The blocks by their first PC.
+    pub blocks: BTreeMap<usize, BasicBlock>,
+    /// The functions by the entry PC.
+    pub functions: Vec<Function>,
+}
+
+///
+/// The way the instruction passes the control.
+///
+struct Flow {
+    /// Whether the next instruction can be executed.
+    falls_through: bool,
+    /// The other successors in the same function.
+    targets: Vec<usize>,
+    /// The called function.
+    call: Option<usize>,
+    /// Whether the target is computed at runtime.
+    is_indirect: bool,
+    /// Whether the instruction ends the block.
+    is_terminator: bool,
+}
+
+impl Flow {
+    fn new(instruction: &Instruction) -> Self {
+        let is_conditional = !matches!(instruction.condition(), Condition::Always);
+        let mut flow = Self {
+            falls_through: true,
+            targets: vec![],
+            call: None,
+            is_indirect: false,
+            is_terminator: true,
+        };
+        // the labels are the full operands: the jump destination, the near call destination
+        // and the exception handlers
+        let labels: Vec<&FullOperand> = instruction
+            .sources()
+            .into_iter()
+            .filter_map(|operand| match operand {
+                OperandRef::Full(operand) => Some(operand),
+                _ => None,
+            })
+            .collect();
+        match instruction.control_flow() {
+            ControlFlow::Jump => {
+                flow.falls_through = is_conditional;
+                match linked_pc(labels[0]) {
+                    Some(pc) => flow.targets.push(pc),
+                    None => flow.is_indirect = true,
+                }
+            }
+            ControlFlow::Return(_) | ControlFlow::Invalid => {
+                flow.falls_through = is_conditional;
+            }
+            ControlFlow::NearCall => {
+                flow.call = linked_pc(labels[0]);
+                flow.targets.extend(linked_pc(labels[1]));
+            }
+            ControlFlow::FarCall => {
+                flow.targets.extend(linked_pc(labels[0]));
+            }
+            ControlFlow::Next => flow.is_terminator = false,
+        }
+
+        flow
+    }
+}
+
+///
+/// The PC the linked label operand points to.
+///
+pub(crate) fn linked_pc(operand: &FullOperand) -> Option<usize> {
+    match operand {
+        FullOperand::Full(GenericOperand {
+            r#type: ImmMemHandlerFlags::UseImm16Only,
+            register: RegisterOperand::Null,
+            immediate,
+        }) => Some(*immediate as usize),
+        _ => None,
+    }
+}
+
+impl ControlFlowGraph {
+    ///
+    /// Builds the graph of the linked instructions.
+    ///
+    pub fn new(instructions: &[Instruction], function_labels: &HashMap<String, usize>) -> Self {
+        let flows: Vec<Flow> = instructions.iter().map(Flow::new).collect();
+
+        let mut leaders = BTreeSet::new();
+        let mut entries = BTreeSet::new();
+        if !instructions.is_empty() {
+            leaders.insert(0);
+            entries.insert(0);
+        }
+        leaders.extend(function_labels.values().copied());
+        for (pc, flow) in flows.iter().enumerate() {
+            leaders.extend(flow.targets.iter().copied());
+            leaders.extend(flow.call);
+            entries.extend(flow.call);
+            if flow.is_terminator {
+                leaders.insert(pc + 1);
+            }
+        }
+        leaders.retain(|pc| *pc < instructions.len());
+        entries.retain(|pc| *pc < instructions.len());
+
+        let starts: Vec<usize> = leaders.into_iter().collect();
+        let mut blocks = BTreeMap::new();
+        for (index, start) in starts.iter().copied().enumerate() {
+            let end = starts.get(index + 1).copied().unwrap_or(instructions.len());
+            let last = &flows[end - 1];
+
+            let mut successors = last.targets.clone();
+            if last.falls_through && end < instructions.len() {
+                successors.push(end);
+            }
+            successors.retain(|pc| *pc < instructions.len());
+            successors.dedup();
+
+            blocks.insert(
+                start,
+                BasicBlock {
+                    start,
+                    end,
+                    successors,
+                    calls: flows[start..end]
+                        .iter()
+                        .filter_map(|flow| flow.call)
+                        .collect(),
+                    has_indirect_jump: last.is_indirect,
+                },
+            );
+        }
+
+        let functions = entries
+            .into_iter()
+            .map(|entry| {
+                let mut function = Function {
+                    name: label_at(function_labels, entry).map(|label| label.to_owned()),
+                    entry,
+                    blocks: BTreeSet::new(),
+                    back_edges: BTreeSet::new(),
+                };
+                Self::visit(&blocks, &mut function);
+                function
+            })
+            .collect();
+
+        Self { blocks, functions }
+    }
+
+    ///
+    /// The block containing the PC.
+    ///
+    pub fn block_at(&self, pc: usize) -> Option<&BasicBlock> {
+        self.blocks
+            .range(..=pc)
+            .next_back()
+            .map(|(_, block)| block)
+            .filter(|block| pc < block.end)
+    }
+
+    pub fn function(&self, name: &str) -> Option<&Function> {
+        self.functions
+            .iter()
+            .find(|function| function.name.as_deref() == Some(name))
+    }
+
+    ///
+    /// The blocks of the function passing the control to the block.
+    ///
+    pub fn predecessors(&self, function: &Function, block: usize) -> Vec<usize> {
+        function
+            .blocks
+            .iter()
+            .copied()
+            .filter(|pc| self.blocks[pc].successors.contains(&block))
+            .collect()
+    }
+
+    ///
+    /// Collects the blocks reachable from the entry and the back edges between them.
+    ///
+    fn visit(blocks: &BTreeMap<usize, BasicBlock>, function: &mut Function) {
+        let mut on_stack = BTreeSet::new();
+        let mut stack = vec![(function.entry, 0)];
+        function.blocks.insert(function.entry);
+        on_stack.insert(function.entry);
+
+        while let Some((block, index)) = stack.pop() {
+            let successor = match blocks[&block].successors.get(index) {
+                Some(successor) => *successor,
+                None => {
+                    on_stack.remove(&block);
+                    continue;
+                }
+            };
+            stack.push((block, index + 1));
+
+            if on_stack.contains(&successor) {
+                function.back_edges.insert((block, successor));
+            } else if function.blocks.insert(successor) {
+                on_stack.insert(successor);
+                stack.push((successor, 0));
+            }
+        }
+    }
+}
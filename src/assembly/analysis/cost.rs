@@ -0,0 +1,257 @@
+//!
+//! The static ergs cost estimation.
+//!
+
+use super::cfg::{ControlFlowGraph, Function};
+use crate::assembly::linking::LinkedAssembly;
+use std::collections::{BTreeMap, VecDeque};
+use zkevm_opcode_defs::decoding::VmEncodingMode;
+
+///
+/// The base cost of the instruction.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstructionCost {
+    pub pc: usize,
+    /// The instruction in the canonical assembly syntax.
+    pub text: String,
+    pub ergs: u32,
+}
+
+///
+/// The total base cost of the basic block.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockCost {
+    pub start: usize,
+    pub end: usize,
+    pub ergs: u64,
+}
+
+///
+/// The worst-case cost of the function body.
+///
+/// The called functions are not included, so every `near_call` costs as much as the instruction itself.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionCost {
+    pub name: Option<String>,
+    pub entry: usize,
+    /// The most expensive path through the function, with every loop taken at most once.
+    pub worst_case_ergs: u64,
+    /// Whether the function has loops or computed jumps, so the worst case is only the lower bound.
+    pub is_unbounded: bool,
+    /// The blocks of the function.
+    pub blocks: Vec<usize>,
+}
+
+///
+/// The static cost report.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CostReport {
+    /// The instructions by the PC.
+    pub instructions: Vec<InstructionCost>,
+    /// The blocks by the first PC.
+    pub blocks: BTreeMap<usize, BlockCost>,
+    /// The functions by the entry PC.
+    pub functions: Vec<FunctionCost>,
+}
+
+impl CostReport {
+    pub fn new<const N: usize, E: VmEncodingMode<N>>(assembly: &LinkedAssembly<N, E>) -> Self {
+        let cfg = ControlFlowGraph::new(assembly.instructions(), assembly.function_labels());
+
+        let instructions: Vec<InstructionCost> = assembly
+            .instructions()
+            .iter()
+            .zip(assembly.opcodes().iter())
+            .enumerate()
+            .map(|(pc, (instruction, opcode))| InstructionCost {
+                pc,
+                text: instruction.to_string(),
+                ergs: opcode.variant.ergs_price(),
+            })
+            .collect();
+
+        let blocks: BTreeMap<usize, BlockCost> = cfg
+            .blocks
+            .values()
+            .map(|block| {
+                let ergs = instructions[block.start..block.end]
+                    .iter()
+                    .map(|instruction| instruction.ergs as u64)
+                    .sum();
+                (
+                    block.start,
+                    BlockCost {
+                        start: block.start,
+                        end: block.end,
+                        ergs,
+                    },
+                )
+            })
+            .collect();
+
+        let functions = cfg
+            .functions
+            .iter()
+            .map(|function| FunctionCost {
+                name: function.name.clone(),
+                entry: function.entry,
+                worst_case_ergs: Self::worst_case(&cfg, function, &blocks),
+                is_unbounded: function.has_loops()
+                    || function
+                        .blocks
+                        .iter()
+                        .any(|block| cfg.blocks[block].has_indirect_jump),
+                blocks: function.blocks.iter().copied().collect(),
+            })
+            .collect();
+
+        Self {
+            instructions,
+            blocks,
+            functions,
+        }
+    }
+
+    ///
+    /// Finds the most expensive path from the entry, ignoring the back edges.
+    ///
+    fn worst_case(
+        cfg: &ControlFlowGraph,
+        function: &Function,
+        blocks: &BTreeMap<usize, BlockCost>,
+    ) -> u64 {
+        let forward_successors = |block: usize| {
+            cfg.blocks[&block]
+                .successors
+                .iter()
+                .copied()
+                .filter(move |successor| !function.back_edges.contains(&(block, *successor)))
+        };
+
+        let mut in_degree: BTreeMap<usize, usize> =
+            function.blocks.iter().map(|block| (*block, 0)).collect();
+        for block in function.blocks.iter().copied() {
+            for successor in forward_successors(block) {
+                *in_degree
+                    .get_mut(&successor)
+                    .expect("reachable from the entry") += 1;
+            }
+        }
+
+        let mut costs = BTreeMap::new();
+        costs.insert(function.entry, blocks[&function.entry].ergs);
+        let mut queue = VecDeque::from([function.entry]);
+        while let Some(block) = queue.pop_front() {
+            let cost = costs[&block];
+            for successor in forward_successors(block) {
+                let successor_cost = cost + blocks[&successor].ergs;
+                let entry = costs.entry(successor).or_insert(successor_cost);
+                *entry = (*entry).max(successor_cost);
+
+                let degree = in_degree
+                    .get_mut(&successor)
+                    .expect("reachable from the entry");
+                *degree -= 1;
+                if *degree == 0 {
+                    queue.push_back(successor);
+                }
+            }
+        }
+
+        costs.values().copied().max().unwrap_or_default()
+    }
+}
+
+impl std::fmt::Display for CostReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for function in self.functions.iter() {
+            let name = function.name.as_deref().unwrap_or("<unnamed>");
+            if function.is_unbounded {
+                writeln!(
+                    f,
+                    "function {} at {}: unbounded, at least {} ergs",
+                    name, function.entry, function.worst_case_ergs
+                )?;
+            } else {
+                writeln!(
+                    f,
+                    "function {} at {}: {} ergs",
+                    name, function.entry, function.worst_case_ergs
+                )?;
+            }
+
+            for block in function.blocks.iter() {
+                let block = &self.blocks[block];
+                writeln!(
+                    f,
+                    "    block {}..{}: {} ergs",
+                    block.start, block.end, block.ergs
+                )?;
+                for instruction in self.instructions[block.start..block.end].iter() {
+                    writeln!(
+                        f,
+                        "        {:>5} {:>6}  {}",
+                        instruction.pc, instruction.ergs, instruction.text
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::assembly::Assembly;
+    use std::convert::TryFrom;
+    use zkevm_opcode_defs::decoding::EncodingModeProduction;
+
+    #[test]
+    fn test_cost_report() {
+        let assembly = Assembly::try_from(
+            r#"
+    .text
+    .globl  __entry
+    __entry:
+        add 3, r0, r1
+        sub.s! 1, r1, r1
+        jump.eq @.BB0_1
+        mul r1, r1, r2, r3
+        mul r2, r2, r2, r3
+    .BB0_1:
+        near_call r0, @loop, @DEFAULT_UNWIND
+        ret.ok r0
+    loop:
+        sub.s! 1, r1, r1
+        jump.ne @loop
+        ret.ok r0
+    "#
+            .to_owned(),
+        )
+        .unwrap();
+        let linked = assembly.link::<8, EncodingModeProduction>().unwrap();
+        let report = CostReport::new(&linked);
+        let ergs = |pcs: std::ops::Range<usize>| -> u64 {
+            report.instructions[pcs]
+                .iter()
+                .map(|instruction| instruction.ergs as u64)
+                .sum()
+        };
+
+        let entry = &report.functions[0];
+        assert_eq!(entry.name.as_deref(), Some("__entry"));
+        assert!(!entry.is_unbounded);
+        assert_eq!(entry.worst_case_ergs, ergs(0..7));
+
+        let function = &report.functions[1];
+        assert_eq!(function.name.as_deref(), Some("loop"));
+        assert!(function.is_unbounded);
+        assert_eq!(function.worst_case_ergs, ergs(7..10));
+    }
+}
@@ -0,0 +1,468 @@
+//!
+//! The register liveness and the uses of the undefined registers.
+//!
+
+use super::cfg::{linked_pc, ControlFlowGraph, Function};
+use crate::assembly::instruction::registers::RegisterSet;
+use crate::assembly::instruction::Instruction;
+use crate::assembly::linking::LinkedAssembly;
+use crate::assembly::operand::RegisterOperand;
+use std::collections::{BTreeMap, BTreeSet};
+use zkevm_opcode_defs::decoding::VmEncodingMode;
+use zkevm_opcode_defs::Condition;
+
+///
+/// The registers defined on the entry of the program: the calldata pointer, the call flags and
+/// the extra ABI data in `r1` to `r12`.
+///
+pub const ENTRY_REGISTERS: (u8, u8) = (1, 12);
+
+///
+/// The registers live on the boundaries of the basic block.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockLiveness {
+    pub start: usize,
+    pub live_in: RegisterSet,
+    pub live_out: RegisterSet,
+}
+
+///
+/// The register interface of the function.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionLiveness {
+    pub name: Option<String>,
+    pub entry: usize,
+    /// The registers read by the function before they are written.
+    pub parameters: RegisterSet,
+    /// The registers the function or any function it calls may write.
+    pub clobbers: RegisterSet,
+}
+
+///
+/// The read of the register which is not written on some path from the function entry.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UndefinedUse {
+    pub pc: usize,
+    pub register: RegisterOperand,
+}
+
+///
+/// The register liveness report.
+///
+/// The `near_call` reads the parameters of the callee and is not assumed to overwrite anything,
+/// while the returns of a called function keep alive everything live after any of its call sites.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LivenessReport {
+    /// The blocks by the first PC.
+    pub blocks: BTreeMap<usize, BlockLiveness>,
+    /// The functions by the entry PC.
+    pub functions: Vec<FunctionLiveness>,
+    /// The uses of the undefined registers, ordered by the PC.
+    pub warnings: Vec<UndefinedUse>,
+}
+
+impl LivenessReport {
+    pub fn new<const N: usize, E: VmEncodingMode<N>>(assembly: &LinkedAssembly<N, E>) -> Self {
+        let instructions = assembly.instructions();
+        let cfg = ControlFlowGraph::new(instructions, assembly.function_labels());
+        let analysis = Analysis::new(&cfg, instructions);
+
+        let (parameters, _) = analysis.liveness(None);
+        let (live_in, live_out) = analysis.liveness(Some(&parameters));
+        let blocks = cfg
+            .blocks
+            .keys()
+            .map(|start| {
+                (
+                    *start,
+                    BlockLiveness {
+                        start: *start,
+                        live_in: live_in[start],
+                        live_out: live_out[start],
+                    },
+                )
+            })
+            .collect();
+
+        let clobbers = analysis.clobbers();
+        let functions = cfg
+            .functions
+            .iter()
+            .map(|function| FunctionLiveness {
+                name: function.name.clone(),
+                entry: function.entry,
+                parameters: parameters[&function.entry],
+                clobbers: clobbers[&function.entry],
+            })
+            .collect();
+
+        let mut warnings = BTreeSet::new();
+        for function in cfg.functions.iter() {
+            let defined = if function.entry == 0 {
+                RegisterSet::range(ENTRY_REGISTERS.0, ENTRY_REGISTERS.1)
+            } else {
+                parameters[&function.entry]
+            };
+            analysis.undefined_uses(function, defined, &parameters, &clobbers, &mut warnings);
+        }
+        let warnings = warnings
+            .into_iter()
+            .map(|(pc, index)| UndefinedUse {
+                pc,
+                register: RegisterOperand::Register(index),
+            })
+            .collect();
+
+        Self {
+            blocks,
+            functions,
+            warnings,
+        }
+    }
+
+    pub fn live_in(&self, block: usize) -> Option<RegisterSet> {
+        self.blocks.get(&block).map(|block| block.live_in)
+    }
+
+    pub fn live_out(&self, block: usize) -> Option<RegisterSet> {
+        self.blocks.get(&block).map(|block| block.live_out)
+    }
+}
+
+///
+/// The PCs and the indexes of the undefined registers read there.
+///
+type Warnings = BTreeSet<(usize, u8)>;
+
+///
+/// The shared state of the analyses.
+///
+struct Analysis<'a> {
+    cfg: &'a ControlFlowGraph,
+    instructions: &'a [Instruction],
+    /// The registers read and written by every instruction.
+    registers: Vec<(RegisterSet, RegisterSet)>,
+    /// The functions containing the block.
+    owners: BTreeMap<usize, Vec<usize>>,
+}
+
+impl<'a> Analysis<'a> {
+    fn new(cfg: &'a ControlFlowGraph, instructions: &'a [Instruction]) -> Self {
+        let mut owners: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for function in cfg.functions.iter() {
+            for block in function.blocks.iter() {
+                owners.entry(*block).or_default().push(function.entry);
+            }
+        }
+
+        Self {
+            cfg,
+            instructions,
+            registers: instructions
+                .iter()
+                .map(|instruction| instruction.registers())
+                .collect(),
+            owners,
+        }
+    }
+
+    ///
+    /// The backward fixpoint over all blocks, returning the live-in and live-out sets.
+    ///
+    /// Without the parameters of the functions, the returns read nothing, so the live-in set
+    /// of the function entry only contains the registers the function reads before writing,
+    /// which are then used as its parameters.
+    ///
+    fn liveness(
+        &self,
+        parameters: Option<&BTreeMap<usize, RegisterSet>>,
+    ) -> (BTreeMap<usize, RegisterSet>, BTreeMap<usize, RegisterSet>) {
+        let mut live_in: BTreeMap<usize, RegisterSet> = self
+            .cfg
+            .blocks
+            .keys()
+            .map(|start| (*start, RegisterSet::new()))
+            .collect();
+        let mut live_out = live_in.clone();
+        // the registers live after the calls of the function, which its returns keep alive
+        let mut live_after_calls: BTreeMap<usize, RegisterSet> = BTreeMap::new();
+
+        let mut is_changed = true;
+        while is_changed {
+            is_changed = false;
+            for (start, block) in self.cfg.blocks.iter().rev() {
+                let out = block
+                    .successors
+                    .iter()
+                    .fold(RegisterSet::new(), |set, successor| {
+                        set.union(&live_in[successor])
+                    });
+
+                let returns = match parameters {
+                    Some(_) => self.owners.get(start).map_or(RegisterSet::new(), |owners| {
+                        owners.iter().filter(|entry| **entry != 0).fold(
+                            RegisterSet::new(),
+                            |set, entry| {
+                                set.union(&live_after_calls.get(entry).copied().unwrap_or_default())
+                            },
+                        )
+                    }),
+                    None => RegisterSet::new(),
+                };
+
+                let mut live = out;
+                for pc in (block.start..block.end).rev() {
+                    let instruction = &self.instructions[pc];
+                    let (mut reads, writes) = self.registers[pc];
+                    match instruction {
+                        Instruction::Ret(_) => reads = reads.union(&returns),
+                        Instruction::NearCall(call) => {
+                            if let Some(callee) = linked_pc(&call.destination) {
+                                let after = live_after_calls.entry(callee).or_default();
+                                let union = after.union(&live);
+                                if union != *after {
+                                    *after = union;
+                                    is_changed = true;
+                                }
+                                let callee_parameters = match parameters {
+                                    Some(parameters) => parameters.get(&callee),
+                                    None => live_in.get(&callee),
+                                };
+                                if let Some(callee_parameters) = callee_parameters {
+                                    reads = reads.union(callee_parameters);
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                    if matches!(instruction.condition(), Condition::Always) {
+                        live = live.difference(&writes);
+                    }
+                    live = live.union(&reads);
+                }
+
+                live_out.insert(*start, out);
+                if live_in.insert(*start, live) != Some(live) {
+                    is_changed = true;
+                }
+            }
+        }
+
+        (live_in, live_out)
+    }
+
+    ///
+    /// The registers written by the functions, including the functions they call.
+    ///
+    fn clobbers(&self) -> BTreeMap<usize, RegisterSet> {
+        let mut own = BTreeMap::new();
+        let mut calls: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+        for function in self.cfg.functions.iter() {
+            let mut writes = RegisterSet::new();
+            for block in function.blocks.iter() {
+                let block = &self.cfg.blocks[block];
+                for pc in block.start..block.end {
+                    writes = writes.union(&self.registers[pc].1);
+                }
+                calls
+                    .entry(function.entry)
+                    .or_default()
+                    .extend(block.calls.iter().copied());
+            }
+            own.insert(function.entry, writes);
+        }
+
+        let mut clobbers = own;
+        let mut is_changed = true;
+        while is_changed {
+            is_changed = false;
+            for (entry, callees) in calls.iter() {
+                let mut writes = clobbers[entry];
+                for callee in callees.iter() {
+                    writes = writes.union(&clobbers.get(callee).copied().unwrap_or_default());
+                }
+                if clobbers.insert(*entry, writes) != Some(writes) {
+                    is_changed = true;
+                }
+            }
+        }
+
+        clobbers
+    }
+
+    ///
+    /// The forward must-defined analysis of the function, collecting the reads of the registers
+    /// not defined on every path from the entry.
+    ///
+    fn undefined_uses(
+        &self,
+        function: &Function,
+        defined: RegisterSet,
+        parameters: &BTreeMap<usize, RegisterSet>,
+        clobbers: &BTreeMap<usize, RegisterSet>,
+        warnings: &mut Warnings,
+    ) {
+        let all = RegisterSet::range(1, 15);
+        let entry = defined;
+        let mut defined_in: BTreeMap<usize, RegisterSet> =
+            function.blocks.iter().map(|block| (*block, all)).collect();
+
+        let mut is_changed = true;
+        while is_changed {
+            is_changed = false;
+            for start in function.blocks.iter() {
+                let initial = if *start == function.entry { entry } else { all };
+                let defined = self
+                    .cfg
+                    .predecessors(function, *start)
+                    .iter()
+                    .map(|predecessor| {
+                        self.transfer(*predecessor, defined_in[predecessor], clobbers, None)
+                    })
+                    .fold(initial, |set, out| set.intersection(&out));
+                if defined_in.insert(*start, defined) != Some(defined) {
+                    is_changed = true;
+                }
+            }
+        }
+
+        for start in function.blocks.iter() {
+            self.transfer(
+                *start,
+                defined_in[start],
+                clobbers,
+                Some((parameters, &mut *warnings)),
+            );
+        }
+    }
+
+    ///
+    /// Passes the defined registers through the block, reporting the undefined reads if requested.
+    ///
+    fn transfer(
+        &self,
+        start: usize,
+        mut defined: RegisterSet,
+        clobbers: &BTreeMap<usize, RegisterSet>,
+        mut report: Option<(&BTreeMap<usize, RegisterSet>, &mut Warnings)>,
+    ) -> RegisterSet {
+        let block = &self.cfg.blocks[&start];
+        for pc in block.start..block.end {
+            let instruction = &self.instructions[pc];
+            let (mut reads, writes) = self.registers[pc];
+            let callee = match instruction {
+                Instruction::NearCall(call) => linked_pc(&call.destination),
+                _ => None,
+            };
+
+            if let Some((parameters, warnings)) = report.as_mut() {
+                if let Some(parameters) = callee.and_then(|callee| parameters.get(&callee)) {
+                    reads = reads.union(parameters);
+                }
+                for register in reads.difference(&defined).iter() {
+                    if let RegisterOperand::Register(index) = register {
+                        warnings.insert((pc, index));
+                    }
+                }
+            }
+
+            if matches!(instruction.condition(), Condition::Always) {
+                defined = defined.union(&writes);
+                if let Some(callee) = callee {
+                    defined = defined.union(&clobbers.get(&callee).copied().unwrap_or_default());
+                }
+            }
+        }
+
+        defined
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::assembly::Assembly;
+    use std::convert::TryFrom;
+    use zkevm_opcode_defs::decoding::EncodingModeProduction;
+
+    fn report(asm_text: &str) -> LivenessReport {
+        let assembly = Assembly::try_from(asm_text.to_owned()).unwrap();
+        let linked = assembly.link::<8, EncodingModeProduction>().unwrap();
+        LivenessReport::new(&linked)
+    }
+
+    #[test]
+    fn test_liveness() {
+        let report = report(
+            r#"
+    .text
+    .globl  __entry
+    __entry:
+        add 5, r0, r13
+        near_call r0, @f, @DEFAULT_UNWIND
+        add r14, r1, r1
+        ret.ok r1
+    f:
+        add r13, r0, r14
+        ret.ok r0
+    "#,
+        );
+
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+        let f = &report.functions[1];
+        assert_eq!(f.name.as_deref(), Some("f"));
+        assert_eq!(f.parameters, RegisterSet::range(13, 13));
+        assert_eq!(f.clobbers, RegisterSet::range(14, 14));
+        // `r1` is read after the call, so it is kept alive through `f`
+        assert_eq!(
+            report.live_in(f.entry),
+            Some(
+                [RegisterOperand::Register(1), RegisterOperand::Register(13)]
+                    .into_iter()
+                    .collect()
+            )
+        );
+        assert!(report
+            .live_in(0)
+            .unwrap()
+            .contains(RegisterOperand::Register(1)));
+        assert!(!report
+            .live_in(0)
+            .unwrap()
+            .contains(RegisterOperand::Register(13)));
+    }
+
+    #[test]
+    fn test_undefined_use() {
+        let report = report(
+            r#"
+    .text
+    .globl  __entry
+    __entry:
+        jump.eq @.BB0_1
+        add 1, r0, r13
+    .BB0_1:
+        add r13, r14, r1
+        ret.ok r1
+    "#,
+        );
+
+        assert_eq!(
+            report.warnings,
+            vec![
+                UndefinedUse {
+                    pc: 2,
+                    register: RegisterOperand::Register(13)
+                },
+                UndefinedUse {
+                    pc: 2,
+                    register: RegisterOperand::Register(14)
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,8 @@
+//!
+//! The static analyses of the linked code.
+//!
+
+pub mod cfg;
+pub mod cost;
+pub mod liveness;
+pub mod stack;
@@ -0,0 +1,366 @@
+//!
+//! The stack depth verification.
+//!
+
+use super::cfg::{ControlFlowGraph, Function};
+use crate::assembly::instruction::metadata::{
+    ControlFlow, InstructionMetadata, OperandRef, OperandRole,
+};
+use crate::assembly::instruction::Instruction;
+use crate::assembly::linking::LinkedAssembly;
+use crate::assembly::operand::FullOperand;
+use std::collections::BTreeMap;
+use zkevm_opcode_defs::decoding::VmEncodingMode;
+use zkevm_opcode_defs::{Condition, ImmMemHandlerFlags, RetOpcode};
+
+///
+/// The stack misuse found in the function.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StackIssue {
+    /// The block is reached with different stack depths.
+    InconsistentDepth {
+        block: usize,
+        expected: i64,
+        found: i64,
+    },
+    /// The `ret.ok` leaves a different depth than the first `ret.ok` of the function.
+    UnbalancedReturn {
+        pc: usize,
+        depth: i64,
+        expected: i64,
+    },
+    /// The pop or the `stack-[k]` access reaches outside the frame of the function.
+    OutOfFrameAccess { pc: usize, offset: u64, depth: i64 },
+    /// The stack pointer is moved by a register or under a condition, so the depth after
+    /// the instruction is not known statically and the successors are not checked.
+    DynamicAdjustment { pc: usize },
+}
+
+///
+/// The stack usage of the function.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionStack {
+    pub name: Option<String>,
+    pub entry: usize,
+    /// The maximal number of words pushed by the function.
+    pub max_frame_size: u64,
+    /// The depth every `ret.ok` of the function leaves.
+    pub return_depth: Option<i64>,
+    pub issues: Vec<StackIssue>,
+}
+
+///
+/// The stack usage of the whole program.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackReport {
+    /// The number of the global variable words, which are not counted into the frame of the entry.
+    pub globals_size: usize,
+    /// The functions by the entry PC.
+    pub functions: Vec<FunctionStack>,
+}
+
+impl StackReport {
+    pub fn new<const N: usize, E: VmEncodingMode<N>>(assembly: &LinkedAssembly<N, E>) -> Self {
+        let cfg = ControlFlowGraph::new(assembly.instructions(), assembly.function_labels());
+        let functions = cfg
+            .functions
+            .iter()
+            .map(|function| {
+                analyze_function(
+                    &cfg,
+                    function,
+                    assembly.instructions(),
+                    assembly.globals_size(),
+                )
+            })
+            .collect();
+
+        Self {
+            globals_size: assembly.globals_size(),
+            functions,
+        }
+    }
+
+    ///
+    /// Whether no function has any stack issues.
+    ///
+    pub fn is_balanced(&self) -> bool {
+        self.functions
+            .iter()
+            .all(|function| function.issues.is_empty())
+    }
+}
+
+///
+/// The stack effect of the instruction.
+///
+struct StackEffect {
+    /// The number of words popped by the source operand.
+    pop: Option<u64>,
+    /// The number of words pushed by the destination operand.
+    push: Option<u64>,
+    /// The `stack-[k]` offsets read by the source and written by the destination.
+    source_offset: Option<u64>,
+    destination_offset: Option<u64>,
+    /// Whether the adjustment depends on a register or the condition.
+    is_dynamic: bool,
+}
+
+impl StackEffect {
+    fn new(instruction: &Instruction) -> Self {
+        let operand = |role| {
+            instruction
+                .operands()
+                .into_iter()
+                .filter(|operand| operand.role == role)
+                .find_map(|operand| match operand.operand {
+                    OperandRef::Full(FullOperand::Full(operand)) | OperandRef::Generic(operand) => {
+                        Some(*operand)
+                    }
+                    _ => None,
+                })
+        };
+        let source = operand(OperandRole::Source);
+        let destination = operand(OperandRole::Destination);
+
+        let mut effect = Self {
+            pop: None,
+            push: None,
+            source_offset: None,
+            destination_offset: None,
+            is_dynamic: false,
+        };
+        if let Some(source) = source {
+            match source.r#type {
+                ImmMemHandlerFlags::UseStackWithPushPop => {
+                    effect.pop = Some(source.immediate);
+                    effect.is_dynamic |= !source.register.is_void();
+                }
+                ImmMemHandlerFlags::UseStackWithOffset if source.register.is_void() => {
+                    effect.source_offset = Some(source.immediate);
+                }
+                _ => {}
+            }
+        }
+        if let Some(destination) = destination {
+            match destination.r#type {
+                ImmMemHandlerFlags::UseStackWithPushPop => {
+                    effect.push = Some(destination.immediate);
+                    effect.is_dynamic |= !destination.register.is_void();
+                }
+                ImmMemHandlerFlags::UseStackWithOffset if destination.register.is_void() => {
+                    effect.destination_offset = Some(destination.immediate);
+                }
+                _ => {}
+            }
+        }
+        if !matches!(instruction.condition(), Condition::Always)
+            && (effect.pop.is_some() || effect.push.is_some())
+        {
+            effect.is_dynamic = true;
+        }
+
+        effect
+    }
+}
+
+///
+/// Propagates the depth relative to the function entry through its blocks.
+///
+fn analyze_function(
+    cfg: &ControlFlowGraph,
+    function: &Function,
+    instructions: &[Instruction],
+    globals_size: usize,
+) -> FunctionStack {
+    let mut result = FunctionStack {
+        name: function.name.clone(),
+        entry: function.entry,
+        max_frame_size: 0,
+        return_depth: None,
+        issues: vec![],
+    };
+
+    let mut block_depths = BTreeMap::new();
+    block_depths.insert(function.entry, 0i64);
+    let mut worklist = vec![function.entry];
+    let mut returns = BTreeMap::new();
+
+    while let Some(start) = worklist.pop() {
+        let block = &cfg.blocks[&start];
+        let mut depth = block_depths[&start];
+        let mut is_known = true;
+
+        for (pc, instruction) in instructions
+            .iter()
+            .enumerate()
+            .take(block.end)
+            .skip(block.start)
+        {
+            // the globals prologue does not belong to any frame
+            if pc == 0 && globals_size > 0 {
+                continue;
+            }
+
+            let effect = StackEffect::new(instruction);
+            if effect.is_dynamic {
+                result.issues.push(StackIssue::DynamicAdjustment { pc });
+                is_known = false;
+                break;
+            }
+
+            if let Some(offset) = effect.source_offset {
+                check_offset(&mut result, pc, offset, depth);
+            }
+            if let Some(pop) = effect.pop {
+                if pop as i64 > depth {
+                    result.issues.push(StackIssue::OutOfFrameAccess {
+                        pc,
+                        offset: pop,
+                        depth,
+                    });
+                }
+                depth -= pop as i64;
+            }
+            if let Some(offset) = effect.destination_offset {
+                check_offset(&mut result, pc, offset, depth);
+            }
+            if let Some(push) = effect.push {
+                depth += push as i64;
+            }
+            result.max_frame_size = result.max_frame_size.max(depth.max(0) as u64);
+
+            // the frame is discarded on `ret.revert` and `ret.panic`, so only `ret.ok` must be balanced
+            if instruction.control_flow() == ControlFlow::Return(RetOpcode::Ok) {
+                returns.insert(pc, depth);
+            }
+        }
+
+        if !is_known {
+            continue;
+        }
+        for successor in block.successors.iter().copied() {
+            match block_depths.get(&successor).copied() {
+                Some(expected) if expected != depth => {
+                    result.issues.push(StackIssue::InconsistentDepth {
+                        block: successor,
+                        expected,
+                        found: depth,
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    block_depths.insert(successor, depth);
+                    worklist.push(successor);
+                }
+            }
+        }
+    }
+
+    let mut returns = returns.into_iter();
+    if let Some((_, expected)) = returns.next() {
+        result.return_depth = Some(expected);
+        for (pc, depth) in returns {
+            if depth != expected {
+                result.issues.push(StackIssue::UnbalancedReturn {
+                    pc,
+                    depth,
+                    expected,
+                });
+            }
+        }
+    }
+
+    result
+}
+
+///
+/// Checks that `stack-[offset]` points into the current frame.
+///
+fn check_offset(result: &mut FunctionStack, pc: usize, offset: u64, depth: i64) {
+    if offset == 0 || offset as i64 > depth {
+        result
+            .issues
+            .push(StackIssue::OutOfFrameAccess { pc, offset, depth });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::assembly::Assembly;
+    use std::convert::TryFrom;
+    use zkevm_opcode_defs::decoding::EncodingModeProduction;
+
+    fn report(asm_text: &str) -> StackReport {
+        let assembly = Assembly::try_from(asm_text.to_owned()).unwrap();
+        let linked = assembly.link::<8, EncodingModeProduction>().unwrap();
+        StackReport::new(&linked)
+    }
+
+    #[test]
+    fn test_balanced_stack() {
+        let report = report(
+            r#"
+    .data
+    .globl  val
+    val:
+        .cell 5
+    .text
+    .globl  __entry
+    __entry:
+        nop stack+=[2]
+        add stack[@val], r0, stack-[1]
+        jump.eq @.BB0_1
+        add r1, r0, stack+=[1]
+        add stack-=[1], r0, r2
+    .BB0_1:
+        near_call r0, @f, @DEFAULT_UNWIND
+        nop stack-=[2]
+        ret.ok r0
+    f:
+        add r1, r0, stack+=[3]
+        ret.ok r0
+    "#,
+        );
+
+        assert_eq!(report.globals_size, 1);
+        assert!(report.is_balanced(), "{:?}", report);
+        assert_eq!(report.functions[0].max_frame_size, 3);
+        assert_eq!(report.functions[0].return_depth, Some(0));
+        assert_eq!(report.functions[1].return_depth, Some(3));
+    }
+
+    #[test]
+    fn test_unbalanced_stack() {
+        let report = report(
+            r#"
+    .text
+    .globl  __entry
+    __entry:
+        nop stack+=[1]
+        add stack-[2], r0, r1
+        jump.eq @.BB0_1
+        nop stack-=[1]
+        ret.ok r0
+    .BB0_1:
+        ret.ok r0
+    "#,
+        );
+
+        let issues = &report.functions[0].issues;
+        assert!(issues.contains(&StackIssue::OutOfFrameAccess {
+            pc: 1,
+            offset: 2,
+            depth: 1
+        }));
+        assert!(issues.contains(&StackIssue::UnbalancedReturn {
+            pc: 5,
+            depth: 1,
+            expected: 0
+        }));
+    }
+}
@@ -0,0 +1,381 @@
+//!
+//! The batch assembler.
+//!
+
+use super::config::AssemblerConfig;
+use super::Assembly;
+use crate::error::Error;
+use sha3::Digest;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use zkevm_opcode_defs::decoding::encoding_mode_production::EncodingModeProduction;
+use zkevm_opcode_defs::decoding::VmEncodingMode;
+
+///
+/// The single source of the batch.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchSource {
+    /// The name used to identify the result, usually the file path.
+    pub name: String,
+    /// The assembly code.
+    pub code: String,
+}
+
+///
+/// The result of assembling the single source of the batch.
+///
+#[derive(Debug)]
+pub struct BatchOutput {
+    /// The name of the source.
+    pub name: String,
+    /// The bytecode or the error.
+    pub result: Result<Vec<[u8; 32]>, Error>,
+    /// Whether the bytecode was taken from the cache.
+    pub cached: bool,
+}
+
+///
+/// Assembles many sources in parallel for the encoding mode `E`.
+///
+/// The bytecode is cached by the hash of the source, so unchanged sources are not
+/// assembled again. The cache is kept in memory, and optionally in a directory, where
+/// every entry is a file with the hex encoded bytecode, so it survives across runs.
+/// The entries are written to a temporary file first and renamed into place, so the
+/// concurrent or interrupted runs never leave a partial entry behind.
+///
+#[derive(Debug)]
+pub struct BatchAssembler<const N: usize = 8, E: VmEncodingMode<N> = EncodingModeProduction> {
+    config: AssemblerConfig,
+    threads: usize,
+    cache_directory: Option<PathBuf>,
+    cache: Mutex<HashMap<[u8; 32], Vec<[u8; 32]>>>,
+    _marker: PhantomData<fn() -> E>,
+}
+
+impl<const N: usize, E: VmEncodingMode<N>> Default for BatchAssembler<N, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, E: VmEncodingMode<N>> BatchAssembler<N, E> {
+    /// The extension of the on-disk cache entries.
+    pub const CACHE_FILE_EXTENSION: &'static str = "hex";
+
+    ///
+    /// Creates the assembler using all the available cores and the in-memory cache only.
+    ///
+    pub fn new() -> Self {
+        let threads = std::thread::available_parallelism()
+            .map(|threads| threads.get())
+            .unwrap_or(1);
+
+        Self {
+            config: AssemblerConfig::default(),
+            threads,
+            cache_directory: None,
+            cache: Mutex::new(HashMap::new()),
+            _marker: PhantomData,
+        }
+    }
+
+    ///
+    /// Sets the configuration. Its encoding mode is ignored in favor of `E`.
+    ///
+    pub fn with_config(mut self, config: AssemblerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn with_cache_directory(mut self, directory: PathBuf) -> Self {
+        self.cache_directory = Some(directory);
+        self
+    }
+
+    ///
+    /// Assembles the sources. The outputs are in the same order as the sources.
+    ///
+    pub fn assemble(&self, sources: &[BatchSource]) -> Vec<BatchOutput> {
+        let next = AtomicUsize::new(0);
+        let threads = self.threads.min(sources.len()).max(1);
+
+        let mut outputs: Vec<(usize, BatchOutput)> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut outputs = vec![];
+                        loop {
+                            let index = next.fetch_add(1, Ordering::Relaxed);
+                            let source = match sources.get(index) {
+                                Some(source) => source,
+                                None => break,
+                            };
+                            outputs.push((index, self.assemble_source(source)));
+                        }
+                        outputs
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("batch worker must not panic"))
+                .collect()
+        });
+        outputs.sort_by_key(|(index, _)| *index);
+
+        outputs.into_iter().map(|(_, output)| output).collect()
+    }
+
+    ///
+    /// Reads and assembles the files. A file that cannot be read only fails its own output.
+    ///
+    pub fn assemble_files(&self, paths: &[PathBuf]) -> Vec<BatchOutput> {
+        let mut sources = Vec::with_capacity(paths.len());
+        let mut read_errors = HashMap::new();
+        for (index, path) in paths.iter().enumerate() {
+            let name = path.to_string_lossy().into_owned();
+            match read_file(path) {
+                Ok(code) => sources.push(BatchSource { name, code }),
+                Err(error) => {
+                    read_errors.insert(
+                        index,
+                        BatchOutput {
+                            name,
+                            result: Err(error),
+                            cached: false,
+                        },
+                    );
+                }
+            }
+        }
+
+        let mut assembled = self.assemble(&sources).into_iter();
+        (0..paths.len())
+            .map(|index| match read_errors.remove(&index) {
+                Some(output) => output,
+                None => assembled.next().expect("every readable file is assembled"),
+            })
+            .collect()
+    }
+
+    fn assemble_source(&self, source: &BatchSource) -> BatchOutput {
+        let key = self.cache_key(&source.code);
+        if let Some(bytecode) = self.cached(&key) {
+            return BatchOutput {
+                name: source.name.clone(),
+                result: Ok(bytecode),
+                cached: true,
+            };
+        }
+
+        let result = Assembly::from_string_with_config(source.code.clone(), self.config)
+            .map_err(Error::from)
+            .and_then(|assembly| {
+                assembly
+                    .compile_to_bytecode_for_mode::<N, E>()
+                    .map_err(Error::from)
+            });
+        if let Ok(bytecode) = result.as_ref() {
+            self.store(key, bytecode);
+        }
+
+        BatchOutput {
+            name: source.name.clone(),
+            result,
+            cached: false,
+        }
+    }
+
+    ///
+    /// The key depends on the assembler version, the encoding and the configuration as well,
+    /// since the same source produces different bytecode for them.
+    ///
+    fn cache_key(&self, code: &str) -> [u8; 32] {
+        let mut hasher = sha3::Keccak256::new();
+        hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+        hasher.update([0]);
+        hasher.update(std::any::type_name::<E>().as_bytes());
+        hasher.update([0]);
+        hasher.update((N as u64).to_be_bytes());
+        hasher.update(self.config.stable_bytes());
+        hasher.update(code.as_bytes());
+        hasher.finalize().into()
+    }
+
+    fn cache_path(&self, key: &[u8; 32]) -> Option<PathBuf> {
+        self.cache_directory.as_ref().map(|directory| {
+            directory
+                .join(hex::encode(key))
+                .with_extension(Self::CACHE_FILE_EXTENSION)
+        })
+    }
+
+    fn cached(&self, key: &[u8; 32]) -> Option<Vec<[u8; 32]>> {
+        if let Some(bytecode) = self.cache.lock().expect("cache lock").get(key) {
+            return Some(bytecode.clone());
+        }
+
+        let text = fs::read_to_string(self.cache_path(key)?).ok()?;
+        let bytes = hex::decode(text.trim()).ok()?;
+        if bytes.is_empty() || bytes.len() % 32 != 0 {
+            return None;
+        }
+        let bytecode: Vec<[u8; 32]> = bytes
+            .chunks_exact(32)
+            .map(|word| word.try_into().expect("chunk is 32 bytes long"))
+            .collect();
+        self.cache
+            .lock()
+            .expect("cache lock")
+            .insert(*key, bytecode.clone());
+
+        Some(bytecode)
+    }
+
+    fn store(&self, key: [u8; 32], bytecode: &[[u8; 32]]) {
+        if let Some(path) = self.cache_path(&key) {
+            let text: String = bytecode.iter().map(hex::encode).collect();
+            let temporary = path.with_extension(format!(
+                "{}.{}.{}",
+                Self::CACHE_FILE_EXTENSION,
+                std::process::id(),
+                TEMPORARY_FILES.fetch_add(1, Ordering::Relaxed)
+            ));
+            if let Err(error) =
+                fs::write(&temporary, text).and_then(|()| fs::rename(&temporary, &path))
+            {
+                let _ = fs::remove_file(&temporary);
+                log::warn!("cannot write the cache entry {:?}: {}", path, error);
+            }
+        }
+
+        self.cache
+            .lock()
+            .expect("cache lock")
+            .insert(key, bytecode.to_vec());
+    }
+}
+
+///
+/// The counter which makes the names of the temporary cache entries unique within the process.
+///
+static TEMPORARY_FILES: AtomicUsize = AtomicUsize::new(0);
+
+fn read_file(path: &Path) -> Result<String, Error> {
+    let mut file = fs::File::open(path).map_err(Error::FileOpening)?;
+    let mut text = String::new();
+    file.read_to_string(&mut text).map_err(Error::FileReading)?;
+    Ok(text)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SOURCE: &str = r#"
+    .text
+    .globl  __entry
+    __entry:
+        add 1, r0, r1
+        ret.ok r0
+    "#;
+
+    #[test]
+    fn test_batch_preserves_order_and_caches() {
+        let sources: Vec<_> = (0..8)
+            .map(|index| BatchSource {
+                name: format!("source_{}", index),
+                code: if index == 5 {
+                    "    .text\n    __entry:\n        bogus r1".to_owned()
+                } else {
+                    format!("{}    ; {}\n", SOURCE, index % 2)
+                },
+            })
+            .collect();
+
+        let assembler = BatchAssembler::<8, EncodingModeProduction>::new().with_threads(3);
+        let outputs = assembler.assemble(&sources);
+        assert_eq!(outputs.len(), sources.len());
+        for (index, output) in outputs.iter().enumerate() {
+            assert_eq!(output.name, format!("source_{}", index));
+            assert_eq!(output.result.is_err(), index == 5);
+        }
+
+        let outputs = assembler.assemble(&sources);
+        assert!(outputs
+            .iter()
+            .enumerate()
+            .all(|(index, output)| output.cached == (index != 5)));
+    }
+
+    #[test]
+    fn test_batch_disk_cache() {
+        let directory =
+            std::env::temp_dir().join(format!("zkevm_assembly_batch_cache_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let sources = vec![BatchSource {
+            name: "source".to_owned(),
+            code: SOURCE.to_owned(),
+        }];
+
+        let first = BatchAssembler::<8, EncodingModeProduction>::new()
+            .with_cache_directory(directory.clone())
+            .assemble(&sources);
+        let second = BatchAssembler::<8, EncodingModeProduction>::new()
+            .with_cache_directory(directory.clone())
+            .assemble(&sources);
+        let entries: Vec<_> = fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        fs::remove_dir_all(&directory).unwrap();
+
+        // only the renamed entry is left
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0].extension().unwrap(),
+            BatchAssembler::<8, EncodingModeProduction>::CACHE_FILE_EXTENSION
+        );
+        assert!(!first[0].cached);
+        assert!(second[0].cached);
+        assert_eq!(
+            first[0].result.as_ref().unwrap(),
+            second[0].result.as_ref().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_cache_key_depends_on_mode() {
+        use zkevm_opcode_defs::decoding::EncodingModeTesting;
+
+        let production = BatchAssembler::<8, EncodingModeProduction>::new();
+        let testing = BatchAssembler::<16, EncodingModeTesting>::new();
+        assert_eq!(production.cache_key(SOURCE), production.cache_key(SOURCE));
+        assert_ne!(production.cache_key(SOURCE), testing.cache_key(SOURCE));
+    }
+
+    #[test]
+    fn test_cache_key_depends_on_config() {
+        use crate::assembly::config::NullDestinationPolicy;
+
+        let batch = BatchAssembler::<8, EncodingModeProduction>::new();
+        let config = batch
+            .config
+            .with_null_destination_policy(NullDestinationPolicy::Allow);
+        let allowing = BatchAssembler::<8, EncodingModeProduction>::new().with_config(config);
+        assert_ne!(batch.cache_key(SOURCE), allowing.cache_key(SOURCE));
+        assert_eq!(config.stable_bytes(), [0, config.isa_version.0, 0, 1, 0]);
+    }
+}
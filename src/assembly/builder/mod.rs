@@ -0,0 +1,241 @@
+//!
+//! The programmatic construction of the assembly.
+//!
+
+use super::config::AssemblerConfig;
+use super::constants::ConstantValue;
+use super::instruction::Instruction;
+use super::section::*;
+use super::Assembly;
+use crate::error::AssemblyParseError;
+use num_bigint::BigUint;
+use std::collections::{HashMap, HashSet};
+
+///
+/// The element of the text section.
+///
+#[derive(Debug, Clone)]
+enum TextItem {
+    Label(String),
+    Instruction(Instruction),
+}
+
+///
+/// The builder of the assembly from the typed instructions, which are linked by the same
+/// `Linker` as the parsed ones.
+///
+/// The assembly code of the result is the canonical text of the added items, and the source
+/// lines of the instructions point into it.
+///
+#[derive(Debug, Clone)]
+pub struct AssemblyBuilder {
+    config: AssemblerConfig,
+    text: Vec<TextItem>,
+    constants: Vec<(String, Vec<[u8; 32]>)>,
+    globals: Vec<(String, Vec<[u8; 32]>)>,
+}
+
+impl AssemblyBuilder {
+    pub fn new(config: AssemblerConfig) -> Self {
+        Self {
+            config,
+            text: vec![],
+            constants: vec![],
+            globals: vec![],
+        }
+    }
+
+    ///
+    /// Starts the code following the label in the text section.
+    ///
+    pub fn label(&mut self, label: &str) -> &mut Self {
+        self.text.push(TextItem::Label(label.to_owned()));
+        self
+    }
+
+    pub fn instruction(&mut self, instruction: impl Into<Instruction>) -> &mut Self {
+        self.text.push(TextItem::Instruction(instruction.into()));
+        self
+    }
+
+    ///
+    /// Adds the constant of the big-endian words to the read-only data section.
+    ///
+    pub fn constant(&mut self, label: &str, words: Vec<[u8; 32]>) -> &mut Self {
+        self.constants.push((label.to_owned(), words));
+        self
+    }
+
+    ///
+    /// Adds the global variable with the big-endian initial words.
+    ///
+    pub fn global(&mut self, label: &str, words: Vec<[u8; 32]>) -> &mut Self {
+        self.globals.push((label.to_owned(), words));
+        self
+    }
+
+    pub fn build(&self) -> Result<Assembly, AssemblyParseError> {
+        let mut lines = vec![];
+        let mut sections = vec![];
+        let mut labels = HashSet::new();
+        let mut add_label = |label: &str| {
+            if labels.insert(label.to_owned()) {
+                Ok(())
+            } else {
+                Err(AssemblyParseError::DuplicateLabel(label.to_owned()))
+            }
+        };
+
+        if !self.globals.is_empty() {
+            lines.push("\t.data".to_owned());
+            let mut elements = Vec::with_capacity(self.globals.len());
+            for (label, words) in self.globals.iter() {
+                add_label(label)?;
+                elements.push(GlobalsSectionElement::Labeled(LabeledGlobal {
+                    label: label.to_owned(),
+                    source_line: lines.len(),
+                    content: cells(&mut lines, label, words),
+                }));
+            }
+            sections.push(ParsedSection::Globals(GlobalsSection { elements }));
+        }
+
+        if !self.text.is_empty() {
+            lines.push("\t.text".to_owned());
+            let mut elements = vec![];
+            let mut function: Option<LabeledFunction> = None;
+            for item in self.text.iter() {
+                match item {
+                    TextItem::Label(label) => {
+                        add_label(label)?;
+                        elements.extend(function.take().map(TextSectionElement::Labeled));
+                        function = Some(LabeledFunction {
+                            label: label.to_owned(),
+                            source_line: lines.len(),
+                            content: vec![],
+                        });
+                        lines.push(format!("{}:", label));
+                    }
+                    TextItem::Instruction(instruction) => {
+                        let code = CodeElement {
+                            source_line: lines.len(),
+                            instruction: instruction.clone(),
+                        };
+                        lines.push(format!("\t{}", instruction));
+                        match function.as_mut() {
+                            Some(function) => function.content.push(code),
+                            None => elements.push(TextSectionElement::Unlabeled(code)),
+                        }
+                    }
+                }
+            }
+            elements.extend(function.map(TextSectionElement::Labeled));
+            sections.push(ParsedSection::Text(TextSection { elements }));
+        }
+
+        if !self.constants.is_empty() {
+            lines.push("\t.rodata".to_owned());
+            let mut elements = Vec::with_capacity(self.constants.len());
+            for (label, words) in self.constants.iter() {
+                add_label(label)?;
+                elements.push(DataSectionElement::Labeled(LabeledConstant {
+                    label: label.to_owned(),
+                    source_line: lines.len(),
+                    content: cells(&mut lines, label, words),
+                }));
+            }
+            sections.push(ParsedSection::Data(DataSection { elements }));
+        }
+
+        let text = lines.join("\n");
+        super::validate_instructions(&sections, &text, &self.config)?;
+
+        Ok(Assembly {
+            metadata_hash: self.config.metadata_policy.metadata_hash(text.as_str()),
+            bytecode: vec![],
+            pc_line_mapping: HashMap::new(),
+            function_labels: HashMap::new(),
+            deduplicated_constants: 0,
+            config: self.config,
+            assembly_code: text,
+            global_variables: HashMap::new(),
+            parsed_sections: sections,
+            labels,
+            entry: None,
+        })
+    }
+}
+
+///
+/// Writes the labeled `.cell` lines, returning the words as constants.
+///
+fn cells(lines: &mut Vec<String>, label: &str, words: &[[u8; 32]]) -> Vec<ConstantValue> {
+    lines.push(format!("{}:", label));
+    words
+        .iter()
+        .map(|word| {
+            lines.push(format!("\t.cell {}", BigUint::from_bytes_be(word)));
+            ConstantValue::Cell(*word)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::assembly::instruction::add::Add;
+    use crate::assembly::instruction::jump::Jump;
+    use crate::assembly::instruction::near_call::NearCall;
+    use crate::assembly::instruction::ret::Ret;
+    use crate::assembly::operand::{FullOperand, GenericOperand, RegisterOperand};
+    use std::convert::TryFrom;
+    use zkevm_opcode_defs::decoding::EncodingModeProduction;
+    use zkevm_opcode_defs::{Condition, RetOpcode};
+
+    fn word(value: u8) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        word[31] = value;
+        word
+    }
+
+    #[test]
+    fn test_builder_matches_parser() {
+        let r = RegisterOperand::new;
+        let mut builder = AssemblyBuilder::new(AssemblerConfig::default());
+        builder
+            .global("val", vec![word(5)])
+            .constant("CPI0_0", vec![word(7)])
+            .label("__entry")
+            .instruction(Add::new(FullOperand::label("CPI0_0"), r(0), r(1)).with_set_flags(true))
+            .instruction(Add::new(
+                FullOperand::global("val", r(0), 0),
+                r(1),
+                GenericOperand::stack_push_pop(r(0), 1),
+            ))
+            .instruction(Jump::new(FullOperand::label(".BB0_1")).with_condition(Condition::Eq))
+            .instruction(NearCall::new(
+                r(0),
+                FullOperand::label("f"),
+                FullOperand::label("DEFAULT_UNWIND"),
+            ))
+            .label(".BB0_1")
+            .instruction(Ret::new(RetOpcode::Ok, r(0)))
+            .label("f")
+            .instruction(Ret::new(RetOpcode::Ok, r(1)));
+        let built = builder.build().unwrap();
+
+        let parsed = Assembly::try_from(built.assembly_code.clone()).unwrap();
+        let built = built.link::<8, EncodingModeProduction>().unwrap();
+        let parsed = parsed.link::<8, EncodingModeProduction>().unwrap();
+        assert_eq!(built.instructions(), parsed.instructions());
+        assert_eq!(built.bytecode(), parsed.bytecode());
+        assert_eq!(built.pc_line_mapping(), parsed.pc_line_mapping());
+        assert_eq!(built.function_labels(), parsed.function_labels());
+
+        builder.label("f");
+        assert_eq!(
+            builder.build().unwrap_err(),
+            AssemblyParseError::DuplicateLabel("f".to_owned())
+        );
+    }
+}
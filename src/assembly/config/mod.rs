@@ -0,0 +1,187 @@
+//!
+//! The assembler configuration.
+//!
+
+use crate::RunningVmEncodingMode;
+use zkevm_opcode_defs::ISAVersion;
+
+///
+/// The contract metadata hash to put at the end of the bytecode.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum MetadataPolicy {
+    /// The keccak256 hash of the assembly source.
+    SourceHash,
+    /// The provided hash.
+    Fixed(
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "crate::assembly::serialization::hash")
+        )]
+        [u8; 32],
+    ),
+    /// No metadata hash, only the padding to the odd number of words.
+    Omit,
+}
+
+impl MetadataPolicy {
+    ///
+    /// Resolves the policy to the hash to be appended for the `source`.
+    ///
+    pub fn metadata_hash(&self, source: &str) -> Option<[u8; 32]> {
+        use sha3::Digest;
+
+        match self {
+            Self::SourceHash => Some(sha3::Keccak256::digest(source.as_bytes()).into()),
+            Self::Fixed(hash) => Some(*hash),
+            Self::Omit => None,
+        }
+    }
+}
+
+impl From<Option<[u8; 32]>> for MetadataPolicy {
+    fn from(metadata_hash: Option<[u8; 32]>) -> Self {
+        match metadata_hash {
+            Some(hash) => Self::Fixed(hash),
+            None => Self::Omit,
+        }
+    }
+}
+
+///
+/// The handling of the default `DEFAULT_UNWIND`, `DEFAULT_FAR_RETURN` and `DEFAULT_FAR_REVERT` landing pads.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LandingPadPolicy {
+    /// Append the default landing pads, unless the code defines the labels itself.
+    Default,
+    /// Append only the default landing pads the code refers to, unless it defines them itself.
+    Referenced,
+    /// Never append the landing pads, so the code must define every label it uses.
+    Omit,
+}
+
+///
+/// The handling of the instructions without side effects, whose only destination is `r0`, so
+/// their result is discarded.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum NullDestinationPolicy {
+    /// Reject such instructions, as they are most likely typos.
+    Reject,
+    /// Accept such instructions.
+    Allow,
+}
+
+///
+/// The assembler configuration.
+///
+/// It is passed explicitly, so assembling for different modes or ISA versions at the same
+/// time does not race on the process-global defaults.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AssemblerConfig {
+    /// The encoding mode used by `Assembly::compile_to_bytecode`.
+    pub encoding_mode: RunningVmEncodingMode,
+    /// The ISA version to encode the instructions for.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::assembly::serialization::isa_version")
+    )]
+    pub isa_version: ISAVersion,
+    /// The contract metadata hash.
+    pub metadata_policy: MetadataPolicy,
+    /// The default landing pads. `Default` also means that the policy is not set, so it is
+    /// overridden by the `.landing_pads` directive of the file even if configured explicitly,
+    /// while the other policies conflict with a different directive.
+    pub landing_pad_policy: LandingPadPolicy,
+    /// The instructions that discard their result.
+    pub null_destination_policy: NullDestinationPolicy,
+}
+
+impl AssemblerConfig {
+    pub fn new(encoding_mode: RunningVmEncodingMode, isa_version: ISAVersion) -> Self {
+        Self {
+            encoding_mode,
+            isa_version,
+            metadata_policy: MetadataPolicy::SourceHash,
+            landing_pad_policy: LandingPadPolicy::Default,
+            null_destination_policy: NullDestinationPolicy::Reject,
+        }
+    }
+
+    ///
+    /// The platform-independent bytes of every field, e.g. for the cache keys.
+    ///
+    pub(crate) fn stable_bytes(&self) -> Vec<u8> {
+        // the fields are destructured, so a new one cannot be left out of the bytes
+        let Self {
+            encoding_mode,
+            isa_version,
+            metadata_policy,
+            landing_pad_policy,
+            null_destination_policy,
+        } = self;
+
+        let mut bytes = vec![
+            match encoding_mode {
+                RunningVmEncodingMode::Production => 0,
+                RunningVmEncodingMode::Testing => 1,
+            },
+            isa_version.0,
+            match landing_pad_policy {
+                LandingPadPolicy::Default => 0,
+                LandingPadPolicy::Referenced => 1,
+                LandingPadPolicy::Omit => 2,
+            },
+            match null_destination_policy {
+                NullDestinationPolicy::Reject => 0,
+                NullDestinationPolicy::Allow => 1,
+            },
+        ];
+        match metadata_policy {
+            MetadataPolicy::SourceHash => bytes.push(0),
+            MetadataPolicy::Fixed(hash) => {
+                bytes.push(1);
+                bytes.extend_from_slice(hash);
+            }
+            MetadataPolicy::Omit => bytes.push(2),
+        }
+        bytes
+    }
+
+    pub fn with_metadata_policy(mut self, metadata_policy: MetadataPolicy) -> Self {
+        self.metadata_policy = metadata_policy;
+        self
+    }
+
+    pub fn with_landing_pad_policy(mut self, landing_pad_policy: LandingPadPolicy) -> Self {
+        self.landing_pad_policy = landing_pad_policy;
+        self
+    }
+
+    pub fn with_null_destination_policy(
+        mut self,
+        null_destination_policy: NullDestinationPolicy,
+    ) -> Self {
+        self.null_destination_policy = null_destination_policy;
+        self
+    }
+}
+
+impl Default for AssemblerConfig {
+    ///
+    /// Takes the encoding mode and the ISA version from the deprecated process-global defaults.
+    ///
+    #[allow(deprecated)]
+    fn default() -> Self {
+        Self::new(crate::get_encoding_mode(), crate::get_isa_version())
+    }
+}
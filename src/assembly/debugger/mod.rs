@@ -0,0 +1,316 @@
+//!
+//! The step debugger on top of the reference simulator.
+//!
+
+use super::instruction::Instruction;
+use super::linking::{label_at, LinkedAssembly};
+use super::simulator::host::Host;
+use super::simulator::{ExecutionOutcome, ExecutionStatus, Simulator};
+use crate::error::SimulationError;
+use std::collections::{BTreeSet, HashMap};
+use zkevm_opcode_defs::decoding::VmEncodingMode;
+
+///
+/// The reason the debugger has returned the control.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopReason {
+    /// The requested step is done.
+    Step,
+    /// The execution has reached the breakpoint at the PC.
+    Breakpoint(usize),
+    /// The program has finished.
+    Finished(ExecutionOutcome),
+}
+
+///
+/// The entry of the backtrace.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BacktraceFrame {
+    /// The current PC of the innermost frame, or the PC of the `near_call` of the outer ones.
+    pub pc: usize,
+    /// The label of the function the frame has been entered at.
+    pub function: Option<String>,
+    /// The source line of the PC.
+    pub line: Option<usize>,
+}
+
+///
+/// The step debugger.
+///
+/// The source lines are numbered as in `pc_line_mapping` of the linked assembly.
+///
+#[derive(Debug, Clone)]
+pub struct Debugger<H: Host> {
+    simulator: Simulator<H>,
+    function_labels: HashMap<String, usize>,
+    pc_line_mapping: HashMap<usize, usize>,
+    breakpoints: BTreeSet<usize>,
+}
+
+impl<H: Host> Debugger<H> {
+    pub fn new<const N: usize, E: VmEncodingMode<N>>(
+        assembly: &LinkedAssembly<N, E>,
+        host: H,
+    ) -> Self {
+        Self {
+            simulator: Simulator::new(assembly, host),
+            function_labels: assembly.function_labels().clone(),
+            pc_line_mapping: assembly.pc_line_mapping().clone(),
+            breakpoints: BTreeSet::new(),
+        }
+    }
+
+    ///
+    /// See `Simulator::with_calldata`.
+    ///
+    pub fn with_calldata(mut self, calldata: Vec<u8>) -> Self {
+        self.simulator = self.simulator.with_calldata(calldata);
+        self
+    }
+
+    pub fn add_breakpoint(&mut self, pc: usize) {
+        self.breakpoints.insert(pc);
+    }
+
+    ///
+    /// Sets the breakpoint at the label, returning its PC.
+    ///
+    pub fn add_breakpoint_at_label(&mut self, label: &str) -> Result<usize, SimulationError> {
+        let pc = self
+            .function_labels
+            .get(label)
+            .copied()
+            .ok_or_else(|| SimulationError::UnknownLabel(label.to_owned()))?;
+        self.breakpoints.insert(pc);
+
+        Ok(pc)
+    }
+
+    ///
+    /// Sets the breakpoint at the first instruction of the source line, returning its PC.
+    ///
+    pub fn add_breakpoint_at_line(&mut self, line: usize) -> Result<usize, SimulationError> {
+        let pc = self
+            .pc_line_mapping
+            .iter()
+            .filter(|(_, mapped_line)| **mapped_line == line)
+            .map(|(pc, _)| *pc)
+            .min()
+            .ok_or(SimulationError::NoCodeAtLine(line))?;
+        self.breakpoints.insert(pc);
+
+        Ok(pc)
+    }
+
+    ///
+    /// Removes the breakpoint, returning whether it has been set.
+    ///
+    pub fn remove_breakpoint(&mut self, pc: usize) -> bool {
+        self.breakpoints.remove(&pc)
+    }
+
+    pub fn breakpoints(&self) -> &BTreeSet<usize> {
+        &self.breakpoints
+    }
+
+    ///
+    /// Executes a single instruction.
+    ///
+    pub fn step(&mut self) -> Result<StopReason, SimulationError> {
+        match self.simulator.step()? {
+            ExecutionStatus::Running => Ok(StopReason::Step),
+            ExecutionStatus::Finished(outcome) => Ok(StopReason::Finished(outcome)),
+        }
+    }
+
+    ///
+    /// Executes a single instruction, running the whole function if it is a taken `near_call`.
+    ///
+    /// Stops early at the breakpoints inside the called function.
+    ///
+    pub fn step_over(&mut self, max_steps: usize) -> Result<StopReason, SimulationError> {
+        let is_call = match self.current_instruction() {
+            Some(instruction @ Instruction::NearCall(_)) => {
+                self.simulator.flags().satisfy(instruction.condition())
+            }
+            _ => false,
+        };
+        let depth = self.simulator.frames().len();
+
+        let mut reason = self.step()?;
+        if !is_call {
+            return Ok(reason);
+        }
+
+        let mut steps = 1;
+        while matches!(reason, StopReason::Step) && self.simulator.frames().len() > depth {
+            if self.breakpoints.contains(&self.simulator.pc()) {
+                return Ok(StopReason::Breakpoint(self.simulator.pc()));
+            }
+            if steps == max_steps {
+                return Err(SimulationError::StepLimitExceeded(max_steps));
+            }
+            reason = self.step()?;
+            steps += 1;
+        }
+
+        Ok(reason)
+    }
+
+    ///
+    /// Runs the program until it reaches a breakpoint or finishes.
+    ///
+    /// The breakpoint at the current PC is stepped over, so resuming from it makes progress.
+    ///
+    pub fn resume(&mut self, max_steps: usize) -> Result<StopReason, SimulationError> {
+        for _ in 0..max_steps {
+            let reason = self.step()?;
+            if !matches!(reason, StopReason::Step) {
+                return Ok(reason);
+            }
+            if self.breakpoints.contains(&self.simulator.pc()) {
+                return Ok(StopReason::Breakpoint(self.simulator.pc()));
+            }
+        }
+
+        Err(SimulationError::StepLimitExceeded(max_steps))
+    }
+
+    pub fn current_instruction(&self) -> Option<&Instruction> {
+        self.simulator.instructions().get(self.simulator.pc())
+    }
+
+    ///
+    /// The current instruction in the canonical assembly syntax.
+    ///
+    pub fn current_instruction_text(&self) -> Option<String> {
+        self.current_instruction()
+            .map(|instruction| instruction.to_string())
+    }
+
+    pub fn current_line(&self) -> Option<usize> {
+        self.pc_line_mapping.get(&self.simulator.pc()).copied()
+    }
+
+    ///
+    /// See `LinkedAssembly::label_at`.
+    ///
+    pub fn label_at(&self, pc: usize) -> Option<&str> {
+        label_at(&self.function_labels, pc)
+    }
+
+    ///
+    /// The near call frames, from the innermost to the outermost.
+    ///
+    pub fn backtrace(&self) -> Vec<BacktraceFrame> {
+        let frames = self.simulator.frames();
+        let mut pc = self.simulator.pc();
+        let mut backtrace = Vec::with_capacity(frames.len() + 1);
+        for frame in frames.iter().rev() {
+            backtrace.push(self.backtrace_frame(pc, frame.entry_pc));
+            pc = frame.return_pc - 1;
+        }
+        backtrace.push(self.backtrace_frame(pc, 0));
+
+        backtrace
+    }
+
+    pub fn simulator(&self) -> &Simulator<H> {
+        &self.simulator
+    }
+
+    pub fn simulator_mut(&mut self) -> &mut Simulator<H> {
+        &mut self.simulator
+    }
+
+    fn backtrace_frame(&self, pc: usize, entry_pc: usize) -> BacktraceFrame {
+        BacktraceFrame {
+            pc,
+            function: self.label_at(entry_pc).map(|label| label.to_owned()),
+            line: self.pc_line_mapping.get(&pc).copied(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::assembly::simulator::host::MockHost;
+    use crate::assembly::simulator::OutcomeKind;
+    use crate::assembly::Assembly;
+    use num_bigint::BigUint;
+    use std::convert::TryFrom;
+    use zkevm_opcode_defs::decoding::EncodingModeProduction;
+
+    const PROGRAM: &str = r#"
+    .text
+    .globl  __entry
+    __entry:
+        add 5, r0, r1
+        near_call r0, @double, @handler
+        add r1, r0, r2
+        ret.ok r0
+    double:
+        add r1, r1, r1
+        ret.ok r0
+    handler:
+        ret.revert r0
+    "#;
+
+    fn debugger() -> Debugger<MockHost> {
+        let assembly = Assembly::try_from(PROGRAM.to_owned()).unwrap();
+        let linked = assembly.link::<8, EncodingModeProduction>().unwrap();
+        Debugger::new(&linked, MockHost::default())
+    }
+
+    #[test]
+    fn test_breakpoints_and_backtrace() {
+        let mut debugger = debugger();
+        let pc = debugger.add_breakpoint_at_label("double").unwrap();
+        assert_eq!(debugger.resume(100).unwrap(), StopReason::Breakpoint(pc));
+        assert_eq!(
+            debugger.current_instruction_text().unwrap(),
+            "add r1, r1, r1"
+        );
+        assert_eq!(debugger.current_line(), Some(8));
+
+        let backtrace = debugger.backtrace();
+        assert_eq!(backtrace.len(), 2);
+        assert_eq!(backtrace[0].function.as_deref(), Some("double"));
+        assert_eq!(backtrace[1].function.as_deref(), Some("__entry"));
+        assert_eq!(backtrace[1].line, Some(4));
+
+        let pc = debugger.add_breakpoint_at_line(5).unwrap();
+        assert_eq!(debugger.resume(100).unwrap(), StopReason::Breakpoint(pc));
+        assert_eq!(debugger.simulator().register(1), BigUint::from(10u32));
+        assert!(matches!(
+            debugger.resume(100).unwrap(),
+            StopReason::Finished(outcome) if outcome.kind == OutcomeKind::Ok
+        ));
+
+        assert_eq!(
+            debugger.add_breakpoint_at_label("missing"),
+            Err(SimulationError::UnknownLabel("missing".to_owned()))
+        );
+        assert_eq!(
+            debugger.add_breakpoint_at_line(2),
+            Err(SimulationError::NoCodeAtLine(2))
+        );
+    }
+
+    #[test]
+    fn test_step_over() {
+        let mut debugger = debugger();
+        assert_eq!(debugger.step().unwrap(), StopReason::Step);
+        assert_eq!(
+            debugger.current_instruction_text().unwrap(),
+            "near_call r0, 4, 6"
+        );
+        assert_eq!(debugger.step_over(100).unwrap(), StopReason::Step);
+        assert_eq!(debugger.simulator().pc(), 2);
+        assert!(debugger.simulator().frames().is_empty());
+        assert_eq!(debugger.simulator().register(1), BigUint::from(10u32));
+    }
+}
@@ -3,7 +3,8 @@
 //!
 
 use super::*;
-use crate::assembly::operand::{FullOperand, RegisterOperand};
+use crate::assembly::mnemonic::LOAD_IMMEDIATE_LABEL_PREFIX;
+use crate::assembly::operand::{ConstantOperand, FullOperand, RegisterOperand};
 use crate::error::InstructionReadError;
 use std::collections::HashMap;
 use std::convert::TryFrom;
@@ -12,6 +13,7 @@ use std::convert::TryFrom;
 /// The arithmetic addition instruction.
 ///
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Add {
     /// Condition for execution
     pub condition: ConditionCase,
@@ -29,6 +31,30 @@ impl Add {
     // Total number of arguments in canonical form
     pub const NUM_ARGUMENTS: usize = 3;
 
+    pub fn new(
+        source_1: impl Into<FullOperand>,
+        source_2: RegisterOperand,
+        destination: impl Into<FullOperand>,
+    ) -> Self {
+        Self {
+            condition: ConditionCase(Condition::Always),
+            set_flags_option: SetFlags(false),
+            source_1: source_1.into(),
+            source_2,
+            destination: destination.into(),
+        }
+    }
+
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = ConditionCase(condition);
+        self
+    }
+
+    pub fn with_set_flags(mut self, set_flags: bool) -> Self {
+        self.set_flags_option = SetFlags(set_flags);
+        self
+    }
+
     #[track_caller]
     pub fn build_from_parts(
         mut modifiers: HashSet<&str>,
@@ -99,6 +125,90 @@ impl Add {
     }
 }
 
+impl Add {
+    ///
+    /// The value loaded by the unlinked `li` pseudo-instruction, which reads its internal constant
+    /// label.
+    ///
+    fn load_immediate_value(&self) -> Option<&str> {
+        match &self.source_1 {
+            FullOperand::Constant(ConstantOperand {
+                label,
+                register: RegisterOperand::Null,
+                immediate: 0,
+            }) if self.condition.condition() == Condition::Always
+                && !self.set_flags_option.0
+                && self.source_2 == RegisterOperand::Null =>
+            {
+                label.strip_prefix(LOAD_IMMEDIATE_LABEL_PREFIX)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Add {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // the internal label of `li` is printed in the source form
+        if let Some(value) = self.load_immediate_value() {
+            return write!(
+                f,
+                "li 0x{}, {}",
+                value,
+                self.destination.to_canonical_string(true)
+            );
+        }
+
+        let mut modifiers = vec![];
+        if self.set_flags_option.0 {
+            modifiers.push(SetFlags::ALL_CANONICAL_MODIFIERS[0]);
+        }
+        write_canonical_form(
+            f,
+            "add",
+            modifiers,
+            self.condition,
+            &[
+                self.source_1.to_canonical_string(false),
+                self.source_2.to_string(),
+                self.destination.to_canonical_string(true),
+            ],
+        )
+    }
+}
+
+impl InstructionMetadata for Add {
+    fn condition(&self) -> Condition {
+        self.condition.0
+    }
+
+    fn opcode(&self) -> Opcode {
+        Opcode::Add(AddOpcode::Add)
+    }
+
+    fn operands(&self) -> Vec<OperandInfo<'_>> {
+        vec![
+            OperandInfo::source(OperandRef::Full(&self.source_1)),
+            OperandInfo::source(OperandRef::Register(&self.source_2)),
+            OperandInfo::destination(OperandRef::Full(&self.destination)),
+        ]
+    }
+
+    fn sets_flags(&self) -> bool {
+        self.set_flags_option.0
+    }
+
+    fn memory_effects(&self) -> Vec<MemoryEffect> {
+        operand_memory_effect(&self.source_1, OperandRole::Source)
+            .into_iter()
+            .chain(operand_memory_effect(
+                &self.destination,
+                OperandRole::Destination,
+            ))
+            .collect()
+    }
+}
+
 impl<const N: usize, E: VmEncodingMode<N>> TryFrom<Add> for DecodedOpcode<N, E> {
     type Error = InstructionReadError;
 
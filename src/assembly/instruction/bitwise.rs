@@ -1,6 +1,7 @@
 use super::*;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bitwise {
     /// Condition for execution
     pub condition: ConditionCase,
@@ -13,12 +14,42 @@ pub struct Bitwise {
     /// The destination register.
     pub destination: FullOperand,
     /// And, Or or Xor
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::assembly::serialization::binop_opcode")
+    )]
     pub op_type: BinopOpcode,
 }
 
 impl Bitwise {
     pub const ALL_CANONICAL_MODIFIERS: [&'static str; 3] = ["xor", "and", "or"];
 
+    pub fn new(
+        op_type: BinopOpcode,
+        source_1: impl Into<FullOperand>,
+        source_2: RegisterOperand,
+        destination: impl Into<FullOperand>,
+    ) -> Self {
+        Self {
+            condition: ConditionCase(Condition::Always),
+            set_flags_option: SetFlags(false),
+            source_1: source_1.into(),
+            source_2,
+            destination: destination.into(),
+            op_type,
+        }
+    }
+
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = ConditionCase(condition);
+        self
+    }
+
+    pub fn with_set_flags(mut self, set_flags: bool) -> Self {
+        self.set_flags_option = SetFlags(set_flags);
+        self
+    }
+
     #[track_caller]
     pub fn build_from_parts(
         mut modifiers: HashSet<&str>,
@@ -117,6 +148,62 @@ impl Bitwise {
     }
 }
 
+impl std::fmt::Display for Bitwise {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut modifiers = vec![match self.op_type {
+            BinopOpcode::Xor => Self::ALL_CANONICAL_MODIFIERS[0],
+            BinopOpcode::And => Self::ALL_CANONICAL_MODIFIERS[1],
+            BinopOpcode::Or => Self::ALL_CANONICAL_MODIFIERS[2],
+        }];
+        if self.set_flags_option.0 {
+            modifiers.push(SetFlags::ALL_CANONICAL_MODIFIERS[0]);
+        }
+        write_canonical_form(
+            f,
+            "binop",
+            modifiers,
+            self.condition,
+            &[
+                self.source_1.to_canonical_string(false),
+                self.source_2.to_string(),
+                self.destination.to_canonical_string(true),
+            ],
+        )
+    }
+}
+
+impl InstructionMetadata for Bitwise {
+    fn condition(&self) -> Condition {
+        self.condition.0
+    }
+
+    fn opcode(&self) -> Opcode {
+        Opcode::Binop(self.op_type)
+    }
+
+    fn operands(&self) -> Vec<OperandInfo<'_>> {
+        vec![
+            OperandInfo::source(OperandRef::Full(&self.source_1)),
+            OperandInfo::source(OperandRef::Register(&self.source_2)),
+            OperandInfo::destination(OperandRef::Full(&self.destination)),
+        ]
+    }
+
+    fn sets_flags(&self) -> bool {
+        self.set_flags_option.0
+    }
+
+    fn memory_effects(&self) -> Vec<MemoryEffect> {
+        operand_memory_effect(&self.source_1, OperandRole::Source)
+            .into_iter()
+            .chain(operand_memory_effect(
+                &self.destination,
+                OperandRole::Destination,
+            ))
+            .collect()
+    }
+}
+
 impl<const N: usize, E: VmEncodingMode<N>> TryFrom<Bitwise> for DecodedOpcode<N, E> {
     type Error = InstructionReadError;
 
@@ -18,20 +18,104 @@ impl std::default::Default for ConditionCase {
 }
 
 impl ConditionCase {
-    pub const ALL_CANONICAL_MODIFIERS: [&'static str; 8] =
-        ["gt", "lt", "eq", "ge", "le", "ne", "gtlt", "of"];
+    ///
+    /// The modifiers the conditions are printed with, in the order of `Condition`.
+    ///
+    /// It used to end with `of`, which is now one of the aliases in `ALL_MODIFIERS`.
+    ///
+    pub const ALL_CANONICAL_MODIFIERS: [&'static str; 7] =
+        ["gt", "lt", "eq", "ge", "le", "ne", "gtlt"];
+
+    ///
+    /// The condition table shared by parsing and printing: every canonical modifier, followed by
+    /// the aliases.
+    ///
+    /// The VM keeps the overflow and the less-than results in the same flag, so `of` is the alias
+    /// of `lt`. The `z` and `nz` aliases test the zero result, that is the equality flag.
+    ///
+    pub const ALL_MODIFIERS: [(&'static str, Condition); 10] = [
+        (Self::ALL_CANONICAL_MODIFIERS[0], Condition::Gt),
+        (Self::ALL_CANONICAL_MODIFIERS[1], Condition::Lt),
+        (Self::ALL_CANONICAL_MODIFIERS[2], Condition::Eq),
+        (Self::ALL_CANONICAL_MODIFIERS[3], Condition::Ge),
+        (Self::ALL_CANONICAL_MODIFIERS[4], Condition::Le),
+        (Self::ALL_CANONICAL_MODIFIERS[5], Condition::Ne),
+        (Self::ALL_CANONICAL_MODIFIERS[6], Condition::GtOrLt),
+        ("of", Condition::Lt),
+        ("z", Condition::Eq),
+        ("nz", Condition::Ne),
+    ];
 
     pub fn from_modifier(modifier: &str) -> Result<Self, InstructionReadError> {
-        match modifier {
-            m if m == Self::ALL_CANONICAL_MODIFIERS[0] => Ok(ConditionCase(Condition::Gt)),
-            m if m == Self::ALL_CANONICAL_MODIFIERS[1] => Ok(ConditionCase(Condition::Lt)),
-            m if m == Self::ALL_CANONICAL_MODIFIERS[2] => Ok(ConditionCase(Condition::Eq)),
-            m if m == Self::ALL_CANONICAL_MODIFIERS[3] => Ok(ConditionCase(Condition::Ge)),
-            m if m == Self::ALL_CANONICAL_MODIFIERS[4] => Ok(ConditionCase(Condition::Le)),
-            m if m == Self::ALL_CANONICAL_MODIFIERS[5] => Ok(ConditionCase(Condition::Ne)),
-            m if m == Self::ALL_CANONICAL_MODIFIERS[6] => Ok(ConditionCase(Condition::GtOrLt)),
-            m if m == Self::ALL_CANONICAL_MODIFIERS[7] => Ok(ConditionCase(Condition::Lt)),
-            _ => Err(InstructionReadError::UnknownArgument(modifier.to_owned())),
+        Self::ALL_MODIFIERS
+            .iter()
+            .find(|(name, _)| *name == modifier)
+            .map(|(_, condition)| ConditionCase(*condition))
+            .ok_or_else(|| InstructionReadError::UnknownArgument(modifier.to_owned()))
+    }
+
+    ///
+    /// The modifier the condition is written with, or `None` for the unconditional execution.
+    ///
+    pub fn canonical_modifier(&self) -> Option<&'static str> {
+        Self::ALL_MODIFIERS
+            .iter()
+            .find(|(_, condition)| *condition == self.0)
+            .map(|(name, _)| *name)
+    }
+
+    pub fn condition(&self) -> Condition {
+        self.0
+    }
+}
+
+impl From<Condition> for ConditionCase {
+    fn from(condition: Condition) -> Self {
+        ConditionCase(condition)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ALL_CONDITIONS: [(Condition, Option<&str>); 8] = [
+        (Condition::Always, None),
+        (Condition::Gt, Some("gt")),
+        (Condition::Lt, Some("lt")),
+        (Condition::Eq, Some("eq")),
+        (Condition::Ge, Some("ge")),
+        (Condition::Le, Some("le")),
+        (Condition::Ne, Some("ne")),
+        (Condition::GtOrLt, Some("gtlt")),
+    ];
+
+    #[test]
+    fn test_condition_table() {
+        for (condition, expected) in ALL_CONDITIONS {
+            let case = ConditionCase::from(condition);
+            assert_eq!(case.canonical_modifier(), expected);
+            if let Some(modifier) = expected {
+                assert!(ConditionCase::ALL_CANONICAL_MODIFIERS.contains(&modifier));
+                assert_eq!(ConditionCase::from_modifier(modifier).unwrap(), case);
+            }
+        }
+        assert_eq!(
+            ConditionCase::ALL_CANONICAL_MODIFIERS.len(),
+            ALL_CONDITIONS.len() - 1
+        );
+        for (modifier, condition) in ConditionCase::ALL_MODIFIERS {
+            let case = ConditionCase::from_modifier(modifier).unwrap();
+            assert_eq!(case.condition(), condition);
+            assert_eq!(
+                ConditionCase::from_modifier(case.canonical_modifier().unwrap()).unwrap(),
+                case
+            );
         }
+        assert_eq!(
+            ConditionCase::from_modifier("of").unwrap().condition(),
+            Condition::Lt
+        );
+        assert!(ConditionCase::from_modifier("always").is_err());
     }
 }
@@ -12,6 +12,7 @@ use std::convert::TryFrom;
 /// Read value from execution context.
 ///
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Context {
     /// Condition for execution
     pub condition: ConditionCase,
@@ -20,6 +21,10 @@ pub struct Context {
     /// The formal destination location
     pub destination_location: RegisterOperand,
     /// Information to get
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::assembly::serialization::context_opcode")
+    )]
     pub field: ContextOpcode,
 }
 
@@ -38,6 +43,32 @@ impl Context {
         "inc_tx_num",
     ];
 
+    ///
+    /// The register is the source of the setters, the destination of the getters, and is
+    /// ignored by `inc_tx_num`.
+    ///
+    pub fn new(field: ContextOpcode, register: RegisterOperand) -> Self {
+        let (source_location, destination_location) = match field {
+            ContextOpcode::SetContextU128 | ContextOpcode::SetErgsPerPubdataByte => {
+                (register, RegisterOperand::Null)
+            }
+            ContextOpcode::IncrementTxNumber => (RegisterOperand::Null, RegisterOperand::Null),
+            _ => (RegisterOperand::Null, register),
+        };
+
+        Self {
+            condition: ConditionCase(Condition::Always),
+            source_location,
+            destination_location,
+            field,
+        }
+    }
+
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = ConditionCase(condition);
+        self
+    }
+
     #[track_caller]
     pub fn build_from_parts(
         mut modifiers: HashSet<&str>,
@@ -156,6 +187,49 @@ impl Context {
     }
 }
 
+impl std::fmt::Display for Context {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let operands = match self.field {
+            ContextOpcode::SetContextU128 | ContextOpcode::SetErgsPerPubdataByte => {
+                vec![self.source_location.to_string()]
+            }
+            ContextOpcode::IncrementTxNumber => vec![],
+            _ => vec![self.destination_location.to_string()],
+        };
+        write_canonical_form(
+            f,
+            "context",
+            vec![Self::ALL_CANONICAL_MODIFIERS[self.field.variant_index()]],
+            self.condition,
+            &operands,
+        )
+    }
+}
+
+impl InstructionMetadata for Context {
+    fn condition(&self) -> Condition {
+        self.condition.0
+    }
+
+    fn opcode(&self) -> Opcode {
+        Opcode::Context(self.field)
+    }
+
+    fn operands(&self) -> Vec<OperandInfo<'_>> {
+        match self.field {
+            ContextOpcode::SetContextU128 | ContextOpcode::SetErgsPerPubdataByte => {
+                vec![OperandInfo::source(OperandRef::Register(
+                    &self.source_location,
+                ))]
+            }
+            ContextOpcode::IncrementTxNumber => vec![],
+            _ => vec![OperandInfo::destination(OperandRef::Register(
+                &self.destination_location,
+            ))],
+        }
+    }
+}
+
 impl<const N: usize, E: VmEncodingMode<N>> TryFrom<Context> for DecodedOpcode<N, E> {
     type Error = InstructionReadError;
     fn try_from(value: Context) -> Result<Self, Self::Error> {
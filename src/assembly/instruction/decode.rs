@@ -0,0 +1,280 @@
+//!
+//! The decoding of the encoded opcodes back into the instructions.
+//!
+//! It is the inverse of `Instruction::encode`, so the decoded instructions have their operands
+//! linked, e.g. the jump destinations are the PC immediates rather than the labels.
+//!
+
+use super::*;
+use crate::assembly::instruction::condition::ConditionCase;
+
+impl Instruction {
+    ///
+    /// Decodes the encoded opcode.
+    ///
+    pub fn decode<const N: usize, E: VmEncodingMode<N>>(
+        opcode: &DecodedOpcode<N, E>,
+    ) -> Result<Self, InstructionReadError> {
+        let condition = ConditionCase(opcode.condition);
+        let flags = opcode.variant.flags;
+        let set_flags_option = SetFlags(flags[SET_FLAGS_FLAG_IDX]);
+        let src_1 = register(opcode.src1_reg_idx);
+        let dst_1 = register(opcode.dst1_reg_idx);
+
+        let instruction = match opcode.variant.opcode {
+            Opcode::Invalid(_) => Instruction::Invalid(Invalid { condition }),
+            Opcode::Nop(_) => Instruction::Nop(Nop {
+                condition,
+                source_1: generic_source(opcode)?,
+                source_2: src_1,
+                dest_1: generic_destination(opcode)?,
+                dest_2: dst_1,
+            }),
+            Opcode::Add(_) => Instruction::Add(Add {
+                condition,
+                set_flags_option,
+                source_1: full_source(opcode)?,
+                source_2: src_1,
+                destination: full_destination(opcode)?,
+            }),
+            Opcode::Sub(_) => Instruction::Sub(Sub {
+                condition,
+                set_flags_option,
+                source_1: full_source(opcode)?,
+                source_2: src_1,
+                destination: full_destination(opcode)?,
+                swap_operands: flags[SWAP_OPERANDS_FLAG_IDX_FOR_ARITH_OPCODES],
+            }),
+            Opcode::Mul(_) => Instruction::Mul(Mul {
+                condition,
+                set_flags_option,
+                source_1: full_source(opcode)?,
+                source_2: src_1,
+                destination_1: full_destination(opcode)?,
+                destination_2: dst_1,
+            }),
+            Opcode::Div(_) => Instruction::Div(Div {
+                condition,
+                set_flags_option,
+                source_1: full_source(opcode)?,
+                source_2: src_1,
+                quotient_destination: full_destination(opcode)?,
+                remainder_destination: dst_1,
+                swap_operands: flags[SWAP_OPERANDS_FLAG_IDX_FOR_ARITH_OPCODES],
+            }),
+            Opcode::Jump(_) => Instruction::Jump(Jump {
+                condition,
+                destination_true: full_source(opcode)?,
+            }),
+            Opcode::Context(field) => Instruction::Context(Context {
+                condition,
+                source_location: register_source(opcode)?,
+                destination_location: register_destination(opcode)?,
+                field,
+            }),
+            Opcode::Shift(variant) => Instruction::Shift(Shift {
+                condition,
+                set_flags_option,
+                source_1: full_source(opcode)?,
+                source_2: src_1,
+                destination: full_destination(opcode)?,
+                swap_operands: flags[SWAP_OPERANDS_FLAG_IDX_FOR_ARITH_OPCODES],
+                variant,
+            }),
+            Opcode::Binop(op_type) => Instruction::Bitwise(Bitwise {
+                condition,
+                set_flags_option,
+                source_1: full_source(opcode)?,
+                source_2: src_1,
+                destination: full_destination(opcode)?,
+                op_type,
+            }),
+            Opcode::Ptr(op_type) => Instruction::Ptr(Ptr {
+                condition,
+                source_1: full_source(opcode)?,
+                source_2: src_1,
+                destination: full_destination(opcode)?,
+                op_type,
+                swap_operands: flags[SWAP_OPERANDS_FLAG_IDX_FOR_PTR_OPCODE],
+            }),
+            Opcode::NearCall(_) => Instruction::NearCall(NearCall {
+                condition,
+                source_for_passed_ergs: register_source(opcode)?,
+                destination: pc(opcode.imm_0.as_u64()),
+                exception_handler: pc(opcode.imm_1.as_u64()),
+            }),
+            Opcode::Log(log_type) => Instruction::Log(Log {
+                condition,
+                key: register_source(opcode)?,
+                value_source: src_1,
+                value_destination: register_destination(opcode)?,
+                log_type,
+                is_initial: flags[0],
+            }),
+            Opcode::FarCall(variant) => Instruction::FarCall(FarCall {
+                condition,
+                source_for_address_to_call: register_source(opcode)?,
+                source_for_meta_args: src_1,
+                exception_handler: pc(opcode.imm_0.as_u64()),
+                variant,
+                is_static: flags[FAR_CALL_STATIC_FLAG_IDX],
+                is_call_shard: flags[FAR_CALL_SHARD_FLAG_IDX],
+            }),
+            Opcode::Ret(variant) => Instruction::Ret(Ret {
+                condition,
+                source_for_meta_args: register_source(opcode)?,
+                variant,
+                is_to_label: flags[0],
+                label_for_return: flags[0].then(|| pc(opcode.imm_0.as_u64())),
+            }),
+            Opcode::UMA(uma_type) => Instruction::UMA(UMA {
+                condition,
+                src_0: match opcode.variant.src0_operand_type {
+                    Operand::RegOnly => NonMemoryOperand {
+                        r#type: RegOrImmFlags::UseRegOnly,
+                        immediate: 0,
+                        register: register(opcode.src0_reg_idx),
+                    },
+                    Operand::RegOrImm(r#type) => NonMemoryOperand {
+                        r#type,
+                        immediate: opcode.imm_0.as_u64(),
+                        register: register(opcode.src0_reg_idx),
+                    },
+                    operand => return Err(unexpected_operand(opcode, operand)),
+                },
+                src_1,
+                dst_0: register_destination(opcode)?,
+                dst_1,
+                uma_type,
+                increment_offset: flags[UMA_INCREMENT_FLAG_IDX],
+            }),
+        };
+
+        Ok(instruction)
+    }
+}
+
+fn register(index: u8) -> RegisterOperand {
+    match index {
+        0 => RegisterOperand::Null,
+        index => RegisterOperand::Register(index),
+    }
+}
+
+fn pc(pc: u64) -> FullOperand {
+    FullOperand::Full(GenericOperand {
+        r#type: ImmMemHandlerFlags::UseImm16Only,
+        immediate: pc,
+        register: RegisterOperand::Null,
+    })
+}
+
+fn unexpected_operand<const N: usize, E: VmEncodingMode<N>>(
+    opcode: &DecodedOpcode<N, E>,
+    operand: Operand,
+) -> InstructionReadError {
+    InstructionReadError::UndecodableOpcode(format!(
+        "{:?} with the operand {:?}",
+        opcode.variant.opcode, operand
+    ))
+}
+
+fn generic_source<const N: usize, E: VmEncodingMode<N>>(
+    opcode: &DecodedOpcode<N, E>,
+) -> Result<GenericOperand, InstructionReadError> {
+    match opcode.variant.src0_operand_type {
+        Operand::Full(r#type) => Ok(GenericOperand {
+            r#type,
+            immediate: opcode.imm_0.as_u64(),
+            register: register(opcode.src0_reg_idx),
+        }),
+        operand => Err(unexpected_operand(opcode, operand)),
+    }
+}
+
+fn generic_destination<const N: usize, E: VmEncodingMode<N>>(
+    opcode: &DecodedOpcode<N, E>,
+) -> Result<GenericOperand, InstructionReadError> {
+    match opcode.variant.dst0_operand_type {
+        Operand::Full(r#type) => Ok(GenericOperand {
+            r#type,
+            immediate: opcode.imm_1.as_u64(),
+            register: register(opcode.dst0_reg_idx),
+        }),
+        operand => Err(unexpected_operand(opcode, operand)),
+    }
+}
+
+fn full_source<const N: usize, E: VmEncodingMode<N>>(
+    opcode: &DecodedOpcode<N, E>,
+) -> Result<FullOperand, InstructionReadError> {
+    generic_source(opcode).map(FullOperand::Full)
+}
+
+fn full_destination<const N: usize, E: VmEncodingMode<N>>(
+    opcode: &DecodedOpcode<N, E>,
+) -> Result<FullOperand, InstructionReadError> {
+    generic_destination(opcode).map(FullOperand::Full)
+}
+
+fn register_source<const N: usize, E: VmEncodingMode<N>>(
+    opcode: &DecodedOpcode<N, E>,
+) -> Result<RegisterOperand, InstructionReadError> {
+    match opcode.variant.src0_operand_type {
+        Operand::RegOnly => Ok(register(opcode.src0_reg_idx)),
+        operand => Err(unexpected_operand(opcode, operand)),
+    }
+}
+
+fn register_destination<const N: usize, E: VmEncodingMode<N>>(
+    opcode: &DecodedOpcode<N, E>,
+) -> Result<RegisterOperand, InstructionReadError> {
+    match opcode.variant.dst0_operand_type {
+        Operand::RegOnly => Ok(register(opcode.dst0_reg_idx)),
+        operand => Err(unexpected_operand(opcode, operand)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::assembly::Assembly;
+    use std::convert::TryFrom;
+    use zkevm_opcode_defs::decoding::EncodingModeProduction;
+
+    #[test]
+    fn test_decode_linked_instructions() {
+        let assembly = Assembly::try_from(
+            r#"
+    .text
+__entry:
+    add 1, r0, r1
+    sub.s! stack[r1 + 2], r2, stack-=[1]
+    mul r1, r2, r3, r4
+    div.s r1, r2, r3, r4
+    shr! r1, r2, r3
+    xor @CPI0_0[0], r2, r3
+    ptr.add.s r1, r2, r3
+    context.this r1
+    sstore r1, r2
+    near_call r1, @f, @DEFAULT_UNWIND
+    far_call.static r1, r2, @DEFAULT_FAR_REVERT
+    ld.1.inc r1, r2, r3
+    nop stack+=[2]
+    jump.lt @f
+f:
+    ret.ok.to_label r1, @DEFAULT_FAR_RETURN
+    .rodata
+CPI0_0:
+    .cell 7
+"#
+            .to_owned(),
+        )
+        .unwrap();
+
+        let linked = assembly.link::<8, EncodingModeProduction>().unwrap();
+        for (instruction, opcode) in linked.instructions().iter().zip(linked.opcodes()) {
+            assert_eq!(&Instruction::decode(opcode).unwrap(), instruction);
+        }
+    }
+}
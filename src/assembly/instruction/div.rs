@@ -11,6 +11,7 @@ use std::convert::TryFrom;
 /// The arithmetic division or remainder instruction.
 ///
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Div {
     /// Condition for execution
     pub condition: ConditionCase,
@@ -32,6 +33,38 @@ impl Div {
     // Total number of arguments in canonical form
     pub const NUM_ARGUMENTS: usize = 4;
 
+    pub fn new(
+        source_1: impl Into<FullOperand>,
+        source_2: RegisterOperand,
+        quotient_destination: impl Into<FullOperand>,
+        remainder_destination: RegisterOperand,
+    ) -> Self {
+        Self {
+            condition: ConditionCase(Condition::Always),
+            set_flags_option: SetFlags(false),
+            source_1: source_1.into(),
+            source_2,
+            quotient_destination: quotient_destination.into(),
+            remainder_destination,
+            swap_operands: false,
+        }
+    }
+
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = ConditionCase(condition);
+        self
+    }
+
+    pub fn with_set_flags(mut self, set_flags: bool) -> Self {
+        self.set_flags_option = SetFlags(set_flags);
+        self
+    }
+
+    pub fn with_swapped_operands(mut self, swap_operands: bool) -> Self {
+        self.swap_operands = swap_operands;
+        self
+    }
+
     pub fn build_from_parts(
         mut modifiers: HashSet<&str>,
         operands: Vec<&str>,
@@ -51,7 +84,7 @@ impl Div {
         let set_flags_option = pick_setting_flags(&mut modifiers)?;
 
         let mut swap_operands = false;
-        if modifiers.remove("s") {
+        if modifiers.remove(SWAP_OPERANDS_MODIFIER) {
             swap_operands = true;
         }
 
@@ -100,6 +133,67 @@ impl Div {
     }
 }
 
+impl std::fmt::Display for Div {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut modifiers = vec![];
+        if self.swap_operands {
+            modifiers.push(SWAP_OPERANDS_MODIFIER);
+        }
+        if self.set_flags_option.0 {
+            modifiers.push(SetFlags::ALL_CANONICAL_MODIFIERS[0]);
+        }
+        write_canonical_form(
+            f,
+            "div",
+            modifiers,
+            self.condition,
+            &[
+                self.source_1.to_canonical_string(false),
+                self.source_2.to_string(),
+                self.quotient_destination.to_canonical_string(true),
+                self.remainder_destination.to_string(),
+            ],
+        )
+    }
+}
+
+impl InstructionMetadata for Div {
+    fn condition(&self) -> Condition {
+        self.condition.0
+    }
+
+    fn opcode(&self) -> Opcode {
+        Opcode::Div(DivOpcode)
+    }
+
+    fn operands(&self) -> Vec<OperandInfo<'_>> {
+        vec![
+            OperandInfo::source(OperandRef::Full(&self.source_1)),
+            OperandInfo::source(OperandRef::Register(&self.source_2)),
+            OperandInfo::destination(OperandRef::Full(&self.quotient_destination)),
+            OperandInfo::destination(OperandRef::Register(&self.remainder_destination)),
+        ]
+    }
+
+    fn sets_flags(&self) -> bool {
+        self.set_flags_option.0
+    }
+
+    fn swaps_operands(&self) -> bool {
+        self.swap_operands
+    }
+
+    fn memory_effects(&self) -> Vec<MemoryEffect> {
+        operand_memory_effect(&self.source_1, OperandRole::Source)
+            .into_iter()
+            .chain(operand_memory_effect(
+                &self.quotient_destination,
+                OperandRole::Destination,
+            ))
+            .collect()
+    }
+}
+
 impl<const N: usize, E: VmEncodingMode<N>> TryFrom<Div> for DecodedOpcode<N, E> {
     type Error = InstructionReadError;
     fn try_from(value: Div) -> Result<Self, Self::Error> {
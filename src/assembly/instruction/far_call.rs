@@ -11,6 +11,7 @@ use std::convert::TryFrom;
 /// The control flow jump instruction.
 ///
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FarCall {
     /// Condition for execution
     pub condition: ConditionCase,
@@ -21,6 +22,10 @@ pub struct FarCall {
     /// Exception handler
     pub exception_handler: FullOperand,
     /// Call variant
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::assembly::serialization::far_call_opcode")
+    )]
     pub variant: FarCallOpcode,
     /// Perform a call under static context restrictions
     pub is_static: bool,
@@ -31,9 +36,45 @@ pub struct FarCall {
 impl FarCall {
     pub const ALL_CANONICAL_MODIFIERS: [&'static str; 2] = ["delegate", "mimic"];
 
+    pub const STATIC_MODIFIER: &'static str = "static";
+
+    pub const SHARD_MODIFIER: &'static str = "shard";
+
     // Total number of arguments in canonical form
     pub const NUM_ARGUMENTS: usize = 3;
 
+    pub fn new(
+        variant: FarCallOpcode,
+        source_for_address_to_call: RegisterOperand,
+        source_for_meta_args: RegisterOperand,
+        exception_handler: impl Into<FullOperand>,
+    ) -> Self {
+        Self {
+            condition: ConditionCase(Condition::Always),
+            source_for_address_to_call,
+            source_for_meta_args,
+            exception_handler: exception_handler.into(),
+            variant,
+            is_static: false,
+            is_call_shard: false,
+        }
+    }
+
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = ConditionCase(condition);
+        self
+    }
+
+    pub fn with_static(mut self, is_static: bool) -> Self {
+        self.is_static = is_static;
+        self
+    }
+
+    pub fn with_shard(mut self, is_call_shard: bool) -> Self {
+        self.is_call_shard = is_call_shard;
+        self
+    }
+
     #[track_caller]
     pub fn build_from_parts(
         mut modifiers: HashSet<&str>,
@@ -52,9 +93,9 @@ impl FarCall {
         let src0 = operands[0].clone().as_register_operand(0)?;
         let src1 = operands[1].clone().as_register_operand(1)?;
 
-        let is_static = modifiers.remove("static");
+        let is_static = modifiers.remove(Self::STATIC_MODIFIER);
 
-        let is_call_shard = modifiers.remove("shard");
+        let is_call_shard = modifiers.remove(Self::SHARD_MODIFIER);
 
         let condition = pick_condition(&mut modifiers)?;
 
@@ -121,6 +162,56 @@ impl FarCall {
     }
 }
 
+impl std::fmt::Display for FarCall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut modifiers = vec![];
+        match self.variant {
+            FarCallOpcode::Normal => {}
+            FarCallOpcode::Delegate => modifiers.push(Self::ALL_CANONICAL_MODIFIERS[0]),
+            FarCallOpcode::Mimic => modifiers.push(Self::ALL_CANONICAL_MODIFIERS[1]),
+        }
+        if self.is_static {
+            modifiers.push(Self::STATIC_MODIFIER);
+        }
+        if self.is_call_shard {
+            modifiers.push(Self::SHARD_MODIFIER);
+        }
+        write_canonical_form(
+            f,
+            "far_call",
+            modifiers,
+            self.condition,
+            &[
+                self.source_for_address_to_call.to_string(),
+                self.source_for_meta_args.to_string(),
+                self.exception_handler.to_canonical_string(false),
+            ],
+        )
+    }
+}
+
+impl InstructionMetadata for FarCall {
+    fn condition(&self) -> Condition {
+        self.condition.0
+    }
+
+    fn opcode(&self) -> Opcode {
+        Opcode::FarCall(self.variant)
+    }
+
+    fn operands(&self) -> Vec<OperandInfo<'_>> {
+        vec![
+            OperandInfo::source(OperandRef::Register(&self.source_for_address_to_call)),
+            OperandInfo::source(OperandRef::Register(&self.source_for_meta_args)),
+            OperandInfo::source(OperandRef::Full(&self.exception_handler)),
+        ]
+    }
+
+    fn control_flow(&self) -> ControlFlow {
+        ControlFlow::FarCall
+    }
+}
+
 impl<const N: usize, E: VmEncodingMode<N>> TryFrom<FarCall> for DecodedOpcode<N, E> {
     type Error = InstructionReadError;
     fn try_from(value: FarCall) -> Result<Self, Self::Error> {
@@ -10,12 +10,24 @@ use super::*;
 /// similar
 ///
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Invalid {
     /// Condition for execution
     pub condition: ConditionCase,
 }
 
 impl Invalid {
+    pub fn new() -> Self {
+        Self {
+            condition: ConditionCase(Condition::Always),
+        }
+    }
+
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = ConditionCase(condition);
+        self
+    }
+
     #[track_caller]
     pub fn build_from_parts(
         mut modifiers: HashSet<&str>,
@@ -48,6 +60,36 @@ impl Invalid {
     }
 }
 
+impl Default for Invalid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for Invalid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_canonical_form(f, "invalid", vec![], self.condition, &[])
+    }
+}
+
+impl InstructionMetadata for Invalid {
+    fn condition(&self) -> Condition {
+        self.condition.0
+    }
+
+    fn opcode(&self) -> Opcode {
+        Opcode::Invalid(InvalidOpcode)
+    }
+
+    fn operands(&self) -> Vec<OperandInfo<'_>> {
+        vec![]
+    }
+
+    fn control_flow(&self) -> ControlFlow {
+        ControlFlow::Invalid
+    }
+}
+
 impl<const N: usize, E: VmEncodingMode<N>> TryFrom<Invalid> for DecodedOpcode<N, E> {
     type Error = InstructionReadError;
     fn try_from(value: Invalid) -> Result<Self, Self::Error> {
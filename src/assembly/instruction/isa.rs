@@ -0,0 +1,138 @@
+//!
+//! The ISA versions the instructions and the modifiers are available in.
+//!
+//! The versions are taken from `zkevm_opcode_defs`, which only tells them apart in
+//! `Opcode::input_operands`, where the UMA immediate source is introduced. The other
+//! instructions and modifiers are not gated, as the dependency does not give any evidence of
+//! their versions.
+//!
+
+use super::*;
+
+///
+/// The instruction or the modifier that is not available in every ISA version.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsaFeature {
+    /// The immediate first source of `uma`, which is available once
+    /// `Opcode::UMA(..).input_operands(version)` takes `RegOrImm`.
+    UmaImmediateSource,
+}
+
+/// All the features that are not available in every ISA version.
+pub const ALL_ISA_FEATURES: [IsaFeature; 1] = [IsaFeature::UmaImmediateSource];
+
+impl IsaFeature {
+    ///
+    /// The feature as it is written in the assembly.
+    ///
+    pub fn name(&self) -> &'static str {
+        match self {
+            IsaFeature::UmaImmediateSource => "uma with an immediate source",
+        }
+    }
+
+    pub fn is_available_in(&self, version: ISAVersion) -> bool {
+        match self {
+            IsaFeature::UmaImmediateSource => matches!(
+                Opcode::UMA(UMAOpcode::HeapRead)
+                    .input_operands(version)
+                    .first(),
+                Some(Operand::RegOrImm(_))
+            ),
+        }
+    }
+
+    ///
+    /// The first ISA version the feature is available in, up to the default one.
+    ///
+    pub fn since(&self) -> Option<ISAVersion> {
+        (0..=zkevm_opcode_defs::DEFAULT_ISA_VERSION.0)
+            .map(ISAVersion)
+            .find(|version| self.is_available_in(*version))
+    }
+}
+
+impl Instruction {
+    ///
+    /// The features of the instruction that are not available in every ISA version.
+    ///
+    pub fn isa_features(&self) -> Vec<IsaFeature> {
+        match self {
+            Instruction::UMA(instr) if !matches!(instr.src_0.r#type, RegOrImmFlags::UseRegOnly) => {
+                vec![IsaFeature::UmaImmediateSource]
+            }
+            _ => vec![],
+        }
+    }
+
+    ///
+    /// Checks that the instruction can be encoded for the ISA version `version`.
+    ///
+    pub fn check_isa_version(&self, version: ISAVersion) -> Result<(), InstructionReadError> {
+        match self
+            .isa_features()
+            .into_iter()
+            .find(|feature| !feature.is_available_in(version))
+        {
+            Some(feature) => Err(InstructionReadError::NotAvailableInIsaVersion(
+                feature.name(),
+                version.0,
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_isa_features() {
+        use crate::assembly::parse::code_element::parse_code_element;
+
+        let instruction = parse_code_element("uma.heap_read 32, r0, r2, r0").unwrap();
+        assert_eq!(
+            instruction.isa_features(),
+            vec![IsaFeature::UmaImmediateSource]
+        );
+        assert_eq!(
+            instruction.check_isa_version(ISAVersion(0)),
+            Err(InstructionReadError::NotAvailableInIsaVersion(
+                "uma with an immediate source",
+                0
+            ))
+        );
+        assert!(instruction.check_isa_version(ISAVersion(1)).is_ok());
+
+        // the instructions without the version evidence are available in every version
+        for line in [
+            "uma.heap_read r1, r0, r2, r0",
+            "ptr.pack r1, r2, r3",
+            "context.inc_tx_num",
+            "uma.aux_heap_write r1, r2, r0, r0",
+            "far_call.shard r1, r2, @handler",
+        ] {
+            let instruction = parse_code_element(line).unwrap();
+            assert!(instruction.isa_features().is_empty(), "{}", line);
+            assert!(
+                instruction.check_isa_version(ISAVersion(0)).is_ok(),
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn test_feature_versions_follow_the_opcode_definitions() {
+        for feature in ALL_ISA_FEATURES {
+            let since = feature.since().expect("available in the default version");
+            for version in 0..since.0 {
+                assert!(!feature.is_available_in(ISAVersion(version)));
+            }
+            assert!(feature.is_available_in(since));
+        }
+        assert_eq!(IsaFeature::UmaImmediateSource.since(), Some(ISAVersion(1)));
+    }
+}
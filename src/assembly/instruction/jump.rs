@@ -12,6 +12,7 @@ use std::convert::TryFrom;
 /// The control flow jump instruction.
 ///
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Jump {
     /// Condition for execution
     pub condition: ConditionCase,
@@ -24,6 +25,18 @@ impl Jump {
     // Total number of arguments in canonical form
     pub const NUM_ARGUMENTS: usize = 1;
 
+    pub fn new(destination: impl Into<FullOperand>) -> Self {
+        Self {
+            condition: ConditionCase(Condition::Always),
+            destination_true: destination.into(),
+        }
+    }
+
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = ConditionCase(condition);
+        self
+    }
+
     #[track_caller]
     pub fn build_from_parts(
         mut modifiers: HashSet<&str>,
@@ -78,6 +91,44 @@ impl Jump {
     }
 }
 
+impl std::fmt::Display for Jump {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_canonical_form(
+            f,
+            "jump",
+            vec![],
+            self.condition,
+            &[self.destination_true.to_canonical_string(false)],
+        )
+    }
+}
+
+impl InstructionMetadata for Jump {
+    fn condition(&self) -> Condition {
+        self.condition.0
+    }
+
+    fn opcode(&self) -> Opcode {
+        Opcode::Jump(JumpOpcode)
+    }
+
+    fn operands(&self) -> Vec<OperandInfo<'_>> {
+        vec![OperandInfo::source(OperandRef::Full(
+            &self.destination_true,
+        ))]
+    }
+
+    fn memory_effects(&self) -> Vec<MemoryEffect> {
+        operand_memory_effect(&self.destination_true, OperandRole::Source)
+            .into_iter()
+            .collect()
+    }
+
+    fn control_flow(&self) -> ControlFlow {
+        ControlFlow::Jump
+    }
+}
+
 impl<const N: usize, E: VmEncodingMode<N>> TryFrom<Jump> for DecodedOpcode<N, E> {
     type Error = InstructionReadError;
     fn try_from(value: Jump) -> Result<Self, Self::Error> {
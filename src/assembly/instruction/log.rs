@@ -12,6 +12,7 @@ use std::convert::TryFrom;
 /// Read value from execution context.
 ///
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Log {
     /// Condition for execution
     pub condition: ConditionCase,
@@ -22,6 +23,10 @@ pub struct Log {
     /// The destination register for value on read
     pub value_destination: RegisterOperand,
     /// Type of log
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::assembly::serialization::log_opcode")
+    )]
     pub log_type: LogOpcode,
     /// Indicator of the initial message
     pub is_initial: bool,
@@ -31,6 +36,34 @@ impl Log {
     pub const ALL_CANONICAL_MODIFIERS: [&'static str; 5] =
         ["sread", "swrite", "event", "to_l1", "precompile"];
 
+    pub const INITIAL_MODIFIER: &'static str = "first";
+
+    pub fn new(
+        log_type: LogOpcode,
+        key: RegisterOperand,
+        value_source: RegisterOperand,
+        value_destination: RegisterOperand,
+    ) -> Self {
+        Self {
+            condition: ConditionCase(Condition::Always),
+            key,
+            value_source,
+            value_destination,
+            log_type,
+            is_initial: false,
+        }
+    }
+
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = ConditionCase(condition);
+        self
+    }
+
+    pub fn with_initial(mut self, is_initial: bool) -> Self {
+        self.is_initial = is_initial;
+        self
+    }
+
     #[track_caller]
     pub fn build_from_parts(
         mut modifiers: HashSet<&str>,
@@ -42,7 +75,7 @@ impl Log {
             &[marker_register_operand()],
         )?;
 
-        let is_initial = modifiers.remove("first");
+        let is_initial = modifiers.remove(Self::INITIAL_MODIFIER);
 
         if modifiers.is_empty() {
             return Err(InstructionReadError::InvalidArgument {
@@ -114,6 +147,60 @@ impl Log {
     }
 }
 
+impl std::fmt::Display for Log {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut modifiers = vec![match self.log_type {
+            LogOpcode::StorageRead => Self::ALL_CANONICAL_MODIFIERS[0],
+            LogOpcode::StorageWrite => Self::ALL_CANONICAL_MODIFIERS[1],
+            LogOpcode::Event => Self::ALL_CANONICAL_MODIFIERS[2],
+            LogOpcode::ToL1Message => Self::ALL_CANONICAL_MODIFIERS[3],
+            LogOpcode::PrecompileCall => Self::ALL_CANONICAL_MODIFIERS[4],
+        }];
+        if self.is_initial {
+            modifiers.push(Self::INITIAL_MODIFIER);
+        }
+        write_canonical_form(
+            f,
+            "log",
+            modifiers,
+            self.condition,
+            &[
+                self.key.to_string(),
+                self.value_source.to_string(),
+                self.value_destination.to_string(),
+            ],
+        )
+    }
+}
+
+impl InstructionMetadata for Log {
+    fn condition(&self) -> Condition {
+        self.condition.0
+    }
+
+    fn opcode(&self) -> Opcode {
+        Opcode::Log(self.log_type)
+    }
+
+    fn operands(&self) -> Vec<OperandInfo<'_>> {
+        vec![
+            OperandInfo::source(OperandRef::Register(&self.key)),
+            OperandInfo::source(OperandRef::Register(&self.value_source)),
+            OperandInfo::destination(OperandRef::Register(&self.value_destination)),
+        ]
+    }
+
+    fn memory_effects(&self) -> Vec<MemoryEffect> {
+        vec![match self.log_type {
+            LogOpcode::StorageRead => MemoryEffect::StorageRead,
+            LogOpcode::StorageWrite => MemoryEffect::StorageWrite,
+            LogOpcode::Event => MemoryEffect::Event,
+            LogOpcode::ToL1Message => MemoryEffect::L1Message,
+            LogOpcode::PrecompileCall => MemoryEffect::PrecompileCall,
+        }]
+    }
+}
+
 impl<const N: usize, E: VmEncodingMode<N>> TryFrom<Log> for DecodedOpcode<N, E> {
     type Error = InstructionReadError;
     fn try_from(value: Log) -> Result<Self, Self::Error> {
@@ -0,0 +1,298 @@
+//!
+//! The structured description of the instructions.
+//!
+
+use super::*;
+
+///
+/// The way the instruction uses the operand.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperandRole {
+    /// The value of the operand is read.
+    Source,
+    /// The result is written to the operand, while its address register is still read.
+    Destination,
+}
+
+///
+/// The operand of any kind.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OperandRef<'a> {
+    Full(&'a FullOperand),
+    Generic(&'a GenericOperand),
+    NonMemory(&'a NonMemoryOperand),
+    Register(&'a RegisterOperand),
+}
+
+///
+/// The operand in the order of the canonical assembly syntax.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OperandInfo<'a> {
+    pub role: OperandRole,
+    pub operand: OperandRef<'a>,
+}
+
+impl<'a> OperandInfo<'a> {
+    pub fn source(operand: OperandRef<'a>) -> Self {
+        Self {
+            role: OperandRole::Source,
+            operand,
+        }
+    }
+
+    pub fn destination(operand: OperandRef<'a>) -> Self {
+        Self {
+            role: OperandRole::Destination,
+            operand,
+        }
+    }
+}
+
+///
+/// The access to the memory or the state outside the registers.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoryEffect {
+    /// The read of the stack, including the pop.
+    StackRead,
+    /// The write to the stack, including the push.
+    StackWrite,
+    CodeRead,
+    HeapRead,
+    HeapWrite,
+    AuxHeapRead,
+    AuxHeapWrite,
+    FatPointerRead,
+    StorageRead,
+    StorageWrite,
+    Event,
+    L1Message,
+    PrecompileCall,
+}
+
+impl MemoryEffect {
+    pub fn is_write(&self) -> bool {
+        !matches!(
+            self,
+            MemoryEffect::StackRead
+                | MemoryEffect::CodeRead
+                | MemoryEffect::HeapRead
+                | MemoryEffect::AuxHeapRead
+                | MemoryEffect::FatPointerRead
+                | MemoryEffect::StorageRead
+        )
+    }
+}
+
+///
+/// The way the instruction passes the control.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlFlow {
+    /// The next instruction is executed.
+    Next,
+    Jump,
+    NearCall,
+    FarCall,
+    Return(RetOpcode),
+    /// The `invalid` instruction, which panics.
+    Invalid,
+}
+
+///
+/// The facts about the instruction, implemented by every instruction struct and dispatched by
+/// `Instruction`.
+///
+pub trait InstructionMetadata {
+    fn condition(&self) -> Condition;
+
+    fn opcode(&self) -> Opcode;
+
+    ///
+    /// The operands in the order of the canonical assembly syntax.
+    ///
+    fn operands(&self) -> Vec<OperandInfo<'_>>;
+
+    fn sets_flags(&self) -> bool {
+        false
+    }
+
+    fn swaps_operands(&self) -> bool {
+        false
+    }
+
+    fn memory_effects(&self) -> Vec<MemoryEffect> {
+        vec![]
+    }
+
+    ///
+    /// Whether the results are written to the destinations, which `nop` does not do, as it only
+    /// moves the stack pointer.
+    ///
+    fn writes_destinations(&self) -> bool {
+        true
+    }
+
+    fn control_flow(&self) -> ControlFlow {
+        ControlFlow::Next
+    }
+
+    ///
+    /// Whether the next instruction is never executed after this one.
+    ///
+    fn is_terminator(&self) -> bool {
+        matches!(
+            self.control_flow(),
+            ControlFlow::Jump | ControlFlow::Return(_) | ControlFlow::Invalid
+        ) && matches!(self.condition(), Condition::Always)
+    }
+
+    fn sources(&self) -> Vec<OperandRef<'_>> {
+        self.operands()
+            .into_iter()
+            .filter(|operand| operand.role == OperandRole::Source)
+            .map(|operand| operand.operand)
+            .collect()
+    }
+
+    fn destinations(&self) -> Vec<OperandRef<'_>> {
+        self.operands()
+            .into_iter()
+            .filter(|operand| operand.role == OperandRole::Destination)
+            .map(|operand| operand.operand)
+            .collect()
+    }
+}
+
+///
+/// The memory accessed by the operand in the role.
+///
+pub(crate) fn operand_memory_effect(
+    operand: &FullOperand,
+    role: OperandRole,
+) -> Option<MemoryEffect> {
+    let r#type = match operand {
+        FullOperand::Full(operand) => operand.r#type,
+        FullOperand::Register(_) => ImmMemHandlerFlags::UseRegOnly,
+        FullOperand::Constant(_) => ImmMemHandlerFlags::UseCodePage,
+        FullOperand::GlobalVariable(_) => ImmMemHandlerFlags::UseAbsoluteOnStack,
+    };
+
+    match (r#type, role) {
+        (ImmMemHandlerFlags::UseRegOnly | ImmMemHandlerFlags::UseImm16Only, _) => None,
+        (ImmMemHandlerFlags::UseCodePage, OperandRole::Source) => Some(MemoryEffect::CodeRead),
+        (_, OperandRole::Source) => Some(MemoryEffect::StackRead),
+        (_, OperandRole::Destination) => Some(MemoryEffect::StackWrite),
+    }
+}
+
+impl Instruction {
+    pub(crate) fn metadata(&self) -> &dyn InstructionMetadata {
+        match self {
+            Instruction::Invalid(instr) => instr,
+            Instruction::Nop(instr) => instr,
+            Instruction::Add(instr) => instr,
+            Instruction::Sub(instr) => instr,
+            Instruction::Mul(instr) => instr,
+            Instruction::Div(instr) => instr,
+            Instruction::Jump(instr) => instr,
+            Instruction::Context(instr) => instr,
+            Instruction::Shift(instr) => instr,
+            Instruction::Bitwise(instr) => instr,
+            Instruction::Ptr(instr) => instr,
+            Instruction::Log(instr) => instr,
+            Instruction::NearCall(instr) => instr,
+            Instruction::FarCall(instr) => instr,
+            Instruction::Ret(instr) => instr,
+            Instruction::UMA(instr) => instr,
+        }
+    }
+}
+
+impl InstructionMetadata for Instruction {
+    fn condition(&self) -> Condition {
+        self.metadata().condition()
+    }
+
+    fn opcode(&self) -> Opcode {
+        self.metadata().opcode()
+    }
+
+    fn operands(&self) -> Vec<OperandInfo<'_>> {
+        self.metadata().operands()
+    }
+
+    fn sets_flags(&self) -> bool {
+        self.metadata().sets_flags()
+    }
+
+    fn swaps_operands(&self) -> bool {
+        self.metadata().swaps_operands()
+    }
+
+    fn memory_effects(&self) -> Vec<MemoryEffect> {
+        self.metadata().memory_effects()
+    }
+
+    fn writes_destinations(&self) -> bool {
+        self.metadata().writes_destinations()
+    }
+
+    fn control_flow(&self) -> ControlFlow {
+        self.metadata().control_flow()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_metadata() {
+        let instruction = parse_code_element("sub.s.set_flags stack-=[2], r2, stack[@g]").unwrap();
+        assert_eq!(instruction.opcode(), Opcode::Sub(SubOpcode::Sub));
+        assert!(instruction.sets_flags());
+        assert!(instruction.swaps_operands());
+        assert_eq!(
+            instruction.memory_effects(),
+            vec![MemoryEffect::StackRead, MemoryEffect::StackWrite]
+        );
+        assert_eq!(
+            instruction.sources(),
+            vec![
+                OperandRef::Full(&FullOperand::Full(GenericOperand {
+                    r#type: ImmMemHandlerFlags::UseStackWithPushPop,
+                    immediate: 2,
+                    register: RegisterOperand::Null,
+                })),
+                OperandRef::Register(&RegisterOperand::Register(2)),
+            ]
+        );
+        assert_eq!(instruction.destinations().len(), 1);
+        assert!(!instruction.is_terminator());
+
+        let instruction = parse_code_element("uma.heap_write.inc r1, r2, r3, r0").unwrap();
+        assert_eq!(instruction.memory_effects(), vec![MemoryEffect::HeapWrite]);
+        assert_eq!(instruction.destinations().len(), 2);
+
+        let instruction = parse_code_element("ret.ok r1").unwrap();
+        assert_eq!(
+            instruction.control_flow(),
+            ControlFlow::Return(RetOpcode::Ok)
+        );
+        assert!(instruction.is_terminator());
+        assert!(!parse_code_element("ret.ok.eq r1").unwrap().is_terminator());
+
+        // the structs expose the condition and the terminators without `Instruction`
+        match parse_code_element("jump.lt @.BB0_1").unwrap() {
+            Instruction::Jump(jump) => {
+                assert_eq!(jump.condition(), Condition::Lt);
+                assert!(!jump.is_terminator());
+            }
+            instruction => panic!("unexpected instruction {:?}", instruction),
+        }
+    }
+}
@@ -18,6 +18,7 @@ use self::far_call::FarCall;
 use self::invalid::Invalid;
 use self::jump::Jump;
 use self::log::Log;
+use self::metadata::*;
 use self::mul::Mul;
 use self::near_call::NearCall;
 use self::nop::Nop;
@@ -37,15 +38,19 @@ pub mod add;
 pub mod bitwise;
 pub mod condition;
 pub mod context;
+pub mod decode;
 pub mod div;
 pub mod far_call;
 pub mod invalid;
+pub mod isa;
 pub mod jump;
 pub mod log;
+pub mod metadata;
 pub mod mul;
 pub mod near_call;
 pub mod nop;
 pub mod ptr;
+pub mod registers;
 pub mod ret;
 pub mod set_flags;
 pub mod shift;
@@ -53,6 +58,14 @@ pub mod sub;
 pub mod uma;
 pub mod utils;
 
+///
+/// The modifier swapping the source operands of `sub`, `div`, `shift` and `ptr`.
+///
+pub(crate) const SWAP_OPERANDS_MODIFIER: &str = "s";
+
+///
+/// The canonical opcodes, which accept the modifiers.
+///
 pub(crate) const ALL_CANONICAL_OPCODES: [&str; 16] = [
     "invalid",
     "nop",
@@ -61,7 +74,7 @@ pub(crate) const ALL_CANONICAL_OPCODES: [&str; 16] = [
     "mul",
     "div",
     "jump",
-    "ctx",
+    "context",
     "shift",
     "binop",
     "ptr",
@@ -76,6 +89,8 @@ pub(crate) const ALL_CANONICAL_OPCODES: [&str; 16] = [
 /// The instruction.
 ///
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Instruction {
     /// The `invalid` instruction.
     Invalid(Invalid),
@@ -96,6 +111,7 @@ pub enum Instruction {
     /// Bitwise shift operation (shl, shr, rol, ror).
     Shift(Shift),
     /// Bitwise operation: AND, OR or XOR.
+    #[cfg_attr(feature = "serde", serde(rename = "binop"))]
     Bitwise(Bitwise),
     /// Pointer arithmetics
     Ptr(Ptr),
@@ -108,6 +124,7 @@ pub enum Instruction {
     /// Return
     Ret(Ret),
     /// Unaligned memory access
+    #[cfg_attr(feature = "serde", serde(rename = "uma"))]
     UMA(UMA),
 }
 
@@ -264,6 +281,13 @@ impl Instruction {
     }
 }
 
+///
+/// Replaces the label of the operand with its PC, code page offset or stack offset.
+///
+/// The out-of-range labels are reported with `CodeIsTooLong` rather than relaxed. The PC, the
+/// code page address and the stack address are all `E::PcOrImm` wide, so neither loading a far
+/// jump target from a constant cell nor adding a register base reaches past `E::PcOrImm::max()`.
+///
 pub(crate) fn link_operand<const N: usize, E: VmEncodingMode<N>>(
     operand: &mut FullOperand,
     function_labels_to_pc: &HashMap<String, usize>,
@@ -285,6 +309,8 @@ pub(crate) fn link_operand<const N: usize, E: VmEncodingMode<N>>(
                     register.is_void(),
                     "jumps can not have registers in labels addressing"
                 );
+                // there is nothing to relax here: the PC itself is `E::PcOrImm`, so the code
+                // with such a label can not be executed even if the jump went through a register
                 if pc > (E::PcOrImm::max()).as_u64() as usize {
                     return Err(AssemblyParseError::CodeIsTooLong(
                         pc,
@@ -299,6 +325,8 @@ pub(crate) fn link_operand<const N: usize, E: VmEncodingMode<N>>(
                     immediate: pc as u64,
                 });
             } else if let Some(offset) = constant_labels_to_offset.get(&*label).copied() {
+                // neither is there for the constants, as the code page is addressed with
+                // `E::PcOrImm` too, so a register base can not reach past it either
                 if offset > (E::PcOrImm::max()).as_u64() as usize {
                     return Err(AssemblyParseError::CodeIsTooLong(
                         offset,
@@ -326,6 +354,8 @@ pub(crate) fn link_operand<const N: usize, E: VmEncodingMode<N>>(
             immediate,
         }) => {
             if let Some(offset) = globals_to_offsets.get(&*label).copied() {
+                // the globals are the offsets from the stack bottom, and the stack is addressed
+                // with `E::PcOrImm` as well
                 if offset > (E::PcOrImm::max()).as_u64() as usize {
                     return Err(AssemblyParseError::CodeIsTooLong(
                         offset,
@@ -353,9 +383,162 @@ pub(crate) fn link_operand<const N: usize, E: VmEncodingMode<N>>(
     Ok(())
 }
 
+impl Instruction {
+    ///
+    /// The condition the instruction is executed under.
+    ///
+    pub fn condition(&self) -> Condition {
+        self.metadata().condition()
+    }
+
+    ///
+    /// Encodes the instruction for the ISA version `isa_version`.
+    ///
+    pub fn encode<const N: usize, E: VmEncodingMode<N>>(
+        self,
+        isa_version: ISAVersion,
+    ) -> Result<DecodedOpcode<N, E>, InstructionReadError> {
+        self.check_isa_version(isa_version)?;
+
+        match self {
+            Instruction::UMA(instr) => instr.encode(isa_version),
+            instr => DecodedOpcode::try_from(instr),
+        }
+    }
+}
+
+impl From<Invalid> for Instruction {
+    fn from(instr: Invalid) -> Self {
+        Instruction::Invalid(instr)
+    }
+}
+
+impl From<Nop> for Instruction {
+    fn from(instr: Nop) -> Self {
+        Instruction::Nop(instr)
+    }
+}
+
+impl From<Add> for Instruction {
+    fn from(instr: Add) -> Self {
+        Instruction::Add(instr)
+    }
+}
+
+impl From<Sub> for Instruction {
+    fn from(instr: Sub) -> Self {
+        Instruction::Sub(instr)
+    }
+}
+
+impl From<Mul> for Instruction {
+    fn from(instr: Mul) -> Self {
+        Instruction::Mul(instr)
+    }
+}
+
+impl From<Div> for Instruction {
+    fn from(instr: Div) -> Self {
+        Instruction::Div(instr)
+    }
+}
+
+impl From<Jump> for Instruction {
+    fn from(instr: Jump) -> Self {
+        Instruction::Jump(instr)
+    }
+}
+
+impl From<Context> for Instruction {
+    fn from(instr: Context) -> Self {
+        Instruction::Context(instr)
+    }
+}
+
+impl From<Shift> for Instruction {
+    fn from(instr: Shift) -> Self {
+        Instruction::Shift(instr)
+    }
+}
+
+impl From<Bitwise> for Instruction {
+    fn from(instr: Bitwise) -> Self {
+        Instruction::Bitwise(instr)
+    }
+}
+
+impl From<Ptr> for Instruction {
+    fn from(instr: Ptr) -> Self {
+        Instruction::Ptr(instr)
+    }
+}
+
+impl From<Log> for Instruction {
+    fn from(instr: Log) -> Self {
+        Instruction::Log(instr)
+    }
+}
+
+impl From<NearCall> for Instruction {
+    fn from(instr: NearCall) -> Self {
+        Instruction::NearCall(instr)
+    }
+}
+
+impl From<FarCall> for Instruction {
+    fn from(instr: FarCall) -> Self {
+        Instruction::FarCall(instr)
+    }
+}
+
+impl From<Ret> for Instruction {
+    fn from(instr: Ret) -> Self {
+        Instruction::Ret(instr)
+    }
+}
+
+impl From<UMA> for Instruction {
+    fn from(instr: UMA) -> Self {
+        Instruction::UMA(instr)
+    }
+}
+
+impl std::fmt::Display for Instruction {
+    ///
+    /// Formats the instruction in the canonical assembly syntax, which the parser accepts back
+    /// unless the operands have been linked.
+    ///
+    /// The constants without an offset are printed as the bare `@label`, which only `add` and
+    /// the label operands of the control flow instructions accept back.
+    ///
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instruction::Invalid(instr) => std::fmt::Display::fmt(instr, f),
+            Instruction::Nop(instr) => std::fmt::Display::fmt(instr, f),
+            Instruction::Add(instr) => std::fmt::Display::fmt(instr, f),
+            Instruction::Sub(instr) => std::fmt::Display::fmt(instr, f),
+            Instruction::Mul(instr) => std::fmt::Display::fmt(instr, f),
+            Instruction::Div(instr) => std::fmt::Display::fmt(instr, f),
+            Instruction::Jump(instr) => std::fmt::Display::fmt(instr, f),
+            Instruction::Context(instr) => std::fmt::Display::fmt(instr, f),
+            Instruction::Shift(instr) => std::fmt::Display::fmt(instr, f),
+            Instruction::Bitwise(instr) => std::fmt::Display::fmt(instr, f),
+            Instruction::Ptr(instr) => std::fmt::Display::fmt(instr, f),
+            Instruction::Log(instr) => std::fmt::Display::fmt(instr, f),
+            Instruction::NearCall(instr) => std::fmt::Display::fmt(instr, f),
+            Instruction::FarCall(instr) => std::fmt::Display::fmt(instr, f),
+            Instruction::Ret(instr) => std::fmt::Display::fmt(instr, f),
+            Instruction::UMA(instr) => std::fmt::Display::fmt(instr, f),
+        }
+    }
+}
+
 impl<const N: usize, E: VmEncodingMode<N>> TryFrom<Instruction> for DecodedOpcode<N, E> {
     type Error = InstructionReadError;
     fn try_from(value: Instruction) -> Result<Self, Self::Error> {
+        #[allow(deprecated)]
+        value.check_isa_version(crate::get_isa_version())?;
+
         match value {
             Instruction::Invalid(instr) => DecodedOpcode::try_from(instr),
             Instruction::Nop(instr) => DecodedOpcode::try_from(instr),
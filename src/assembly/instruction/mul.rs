@@ -12,6 +12,7 @@ use std::convert::TryFrom;
 /// The arithmetic multiplication instruction.
 ///
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mul {
     /// Condition for execution
     pub condition: ConditionCase,
@@ -31,6 +32,32 @@ impl Mul {
     // Total number of arguments in canonical form
     pub const NUM_ARGUMENTS: usize = 4;
 
+    pub fn new(
+        source_1: impl Into<FullOperand>,
+        source_2: RegisterOperand,
+        destination_1: impl Into<FullOperand>,
+        destination_2: RegisterOperand,
+    ) -> Self {
+        Self {
+            condition: ConditionCase(Condition::Always),
+            set_flags_option: SetFlags(false),
+            source_1: source_1.into(),
+            source_2,
+            destination_1: destination_1.into(),
+            destination_2,
+        }
+    }
+
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = ConditionCase(condition);
+        self
+    }
+
+    pub fn with_set_flags(mut self, set_flags: bool) -> Self {
+        self.set_flags_option = SetFlags(set_flags);
+        self
+    }
+
     #[track_caller]
     pub fn build_from_parts(
         mut modifiers: HashSet<&str>,
@@ -94,6 +121,60 @@ impl Mul {
     }
 }
 
+impl std::fmt::Display for Mul {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut modifiers = vec![];
+        if self.set_flags_option.0 {
+            modifiers.push(SetFlags::ALL_CANONICAL_MODIFIERS[0]);
+        }
+        write_canonical_form(
+            f,
+            "mul",
+            modifiers,
+            self.condition,
+            &[
+                self.source_1.to_canonical_string(false),
+                self.source_2.to_string(),
+                self.destination_1.to_canonical_string(true),
+                self.destination_2.to_string(),
+            ],
+        )
+    }
+}
+
+impl InstructionMetadata for Mul {
+    fn condition(&self) -> Condition {
+        self.condition.0
+    }
+
+    fn opcode(&self) -> Opcode {
+        Opcode::Mul(MulOpcode)
+    }
+
+    fn operands(&self) -> Vec<OperandInfo<'_>> {
+        vec![
+            OperandInfo::source(OperandRef::Full(&self.source_1)),
+            OperandInfo::source(OperandRef::Register(&self.source_2)),
+            OperandInfo::destination(OperandRef::Full(&self.destination_1)),
+            OperandInfo::destination(OperandRef::Register(&self.destination_2)),
+        ]
+    }
+
+    fn sets_flags(&self) -> bool {
+        self.set_flags_option.0
+    }
+
+    fn memory_effects(&self) -> Vec<MemoryEffect> {
+        operand_memory_effect(&self.source_1, OperandRole::Source)
+            .into_iter()
+            .chain(operand_memory_effect(
+                &self.destination_1,
+                OperandRole::Destination,
+            ))
+            .collect()
+    }
+}
+
 impl<const N: usize, E: VmEncodingMode<N>> TryFrom<Mul> for DecodedOpcode<N, E> {
     type Error = InstructionReadError;
     fn try_from(value: Mul) -> Result<Self, Self::Error> {
@@ -11,6 +11,7 @@ use std::convert::TryFrom;
 /// The control flow jump instruction.
 ///
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NearCall {
     /// Condition for execution
     pub condition: ConditionCase,
@@ -26,6 +27,24 @@ impl NearCall {
     // Total number of arguments in canonical form
     pub const NUM_ARGUMENTS: usize = 1;
 
+    pub fn new(
+        source_for_passed_ergs: RegisterOperand,
+        destination: impl Into<FullOperand>,
+        exception_handler: impl Into<FullOperand>,
+    ) -> Self {
+        Self {
+            condition: ConditionCase(Condition::Always),
+            source_for_passed_ergs,
+            destination: destination.into(),
+            exception_handler: exception_handler.into(),
+        }
+    }
+
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = ConditionCase(condition);
+        self
+    }
+
     #[track_caller]
     pub fn build_from_parts(
         mut modifiers: HashSet<&str>,
@@ -93,6 +112,44 @@ impl NearCall {
     }
 }
 
+impl std::fmt::Display for NearCall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_canonical_form(
+            f,
+            "near_call",
+            vec![],
+            self.condition,
+            &[
+                self.source_for_passed_ergs.to_string(),
+                self.destination.to_canonical_string(false),
+                self.exception_handler.to_canonical_string(false),
+            ],
+        )
+    }
+}
+
+impl InstructionMetadata for NearCall {
+    fn condition(&self) -> Condition {
+        self.condition.0
+    }
+
+    fn opcode(&self) -> Opcode {
+        Opcode::NearCall(NearCallOpcode)
+    }
+
+    fn operands(&self) -> Vec<OperandInfo<'_>> {
+        vec![
+            OperandInfo::source(OperandRef::Register(&self.source_for_passed_ergs)),
+            OperandInfo::source(OperandRef::Full(&self.destination)),
+            OperandInfo::source(OperandRef::Full(&self.exception_handler)),
+        ]
+    }
+
+    fn control_flow(&self) -> ControlFlow {
+        ControlFlow::NearCall
+    }
+}
+
 impl<const N: usize, E: VmEncodingMode<N>> TryFrom<NearCall> for DecodedOpcode<N, E> {
     type Error = InstructionReadError;
     fn try_from(value: NearCall) -> Result<Self, Self::Error> {
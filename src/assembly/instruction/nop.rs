@@ -8,6 +8,7 @@ use super::*;
 /// The NOP instruction. Can also be used for stack adjustment
 ///
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Nop {
     /// Condition for execution
     pub condition: ConditionCase,
@@ -25,6 +26,24 @@ impl Nop {
     // Total number of arguments in canonical form
     pub const NUM_ARGUMENTS: usize = 4;
 
+    ///
+    /// The `nop` adjusting the stack pointer with the push and pop operands, or doing nothing.
+    ///
+    pub fn new(source_1: GenericOperand, dest_1: GenericOperand) -> Self {
+        Self {
+            condition: ConditionCase(Condition::Always),
+            source_1,
+            source_2: RegisterOperand::Null,
+            dest_1,
+            dest_2: RegisterOperand::Null,
+        }
+    }
+
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = ConditionCase(condition);
+        self
+    }
+
     #[track_caller]
     pub fn build_from_parts(
         mut modifiers: HashSet<&str>,
@@ -91,6 +110,49 @@ impl Nop {
     }
 }
 
+impl std::fmt::Display for Nop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_canonical_form(
+            f,
+            "nop",
+            vec![],
+            self.condition,
+            &[
+                self.source_1.to_canonical_string(false),
+                self.source_2.to_string(),
+                self.dest_1.to_canonical_string(true),
+                self.dest_2.to_string(),
+            ],
+        )
+    }
+}
+
+impl InstructionMetadata for Nop {
+    fn condition(&self) -> Condition {
+        self.condition.0
+    }
+
+    fn opcode(&self) -> Opcode {
+        Opcode::Nop(NopOpcode)
+    }
+
+    ///
+    /// The operands only adjust the stack pointer, so `nop` has no memory effects.
+    ///
+    fn operands(&self) -> Vec<OperandInfo<'_>> {
+        vec![
+            OperandInfo::source(OperandRef::Generic(&self.source_1)),
+            OperandInfo::source(OperandRef::Register(&self.source_2)),
+            OperandInfo::destination(OperandRef::Generic(&self.dest_1)),
+            OperandInfo::destination(OperandRef::Register(&self.dest_2)),
+        ]
+    }
+
+    fn writes_destinations(&self) -> bool {
+        false
+    }
+}
+
 impl<const N: usize, E: VmEncodingMode<N>> TryFrom<Nop> for DecodedOpcode<N, E> {
     type Error = InstructionReadError;
     fn try_from(value: Nop) -> Result<Self, Self::Error> {
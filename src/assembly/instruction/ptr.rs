@@ -1,6 +1,7 @@
 use super::*;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ptr {
     /// Condition for execution
     pub condition: ConditionCase,
@@ -11,6 +12,10 @@ pub struct Ptr {
     /// The destination register.
     pub destination: FullOperand,
     /// And, Or or Xor
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::assembly::serialization::ptr_opcode")
+    )]
     pub op_type: PtrOpcode,
     /// if it is set then source operands have to be swapped.
     pub swap_operands: bool,
@@ -19,6 +24,32 @@ pub struct Ptr {
 impl Ptr {
     pub const ALL_CANONICAL_MODIFIERS: [&'static str; 4] = ["add", "sub", "pack", "shrink"];
 
+    pub fn new(
+        op_type: PtrOpcode,
+        source_1: impl Into<FullOperand>,
+        source_2: RegisterOperand,
+        destination: impl Into<FullOperand>,
+    ) -> Self {
+        Self {
+            condition: ConditionCase(Condition::Always),
+            source_1: source_1.into(),
+            source_2,
+            destination: destination.into(),
+            op_type,
+            swap_operands: false,
+        }
+    }
+
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = ConditionCase(condition);
+        self
+    }
+
+    pub fn with_swapped_operands(mut self, swap_operands: bool) -> Self {
+        self.swap_operands = swap_operands;
+        self
+    }
+
     #[track_caller]
     pub fn build_from_parts(
         mut modifiers: HashSet<&str>,
@@ -43,7 +74,7 @@ impl Ptr {
         }
 
         let mut swap_operands = false;
-        if modifiers.remove("s") {
+        if modifiers.remove(SWAP_OPERANDS_MODIFIER) {
             swap_operands = true;
         }
 
@@ -122,6 +153,63 @@ impl Ptr {
     }
 }
 
+impl std::fmt::Display for Ptr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut modifiers = vec![match self.op_type {
+            PtrOpcode::Add => Self::ALL_CANONICAL_MODIFIERS[0],
+            PtrOpcode::Sub => Self::ALL_CANONICAL_MODIFIERS[1],
+            PtrOpcode::Pack => Self::ALL_CANONICAL_MODIFIERS[2],
+            PtrOpcode::Shrink => Self::ALL_CANONICAL_MODIFIERS[3],
+        }];
+        if self.swap_operands {
+            modifiers.push(SWAP_OPERANDS_MODIFIER);
+        }
+        write_canonical_form(
+            f,
+            "ptr",
+            modifiers,
+            self.condition,
+            &[
+                self.source_1.to_canonical_string(false),
+                self.source_2.to_string(),
+                self.destination.to_canonical_string(true),
+            ],
+        )
+    }
+}
+
+impl InstructionMetadata for Ptr {
+    fn condition(&self) -> Condition {
+        self.condition.0
+    }
+
+    fn opcode(&self) -> Opcode {
+        Opcode::Ptr(self.op_type)
+    }
+
+    fn operands(&self) -> Vec<OperandInfo<'_>> {
+        vec![
+            OperandInfo::source(OperandRef::Full(&self.source_1)),
+            OperandInfo::source(OperandRef::Register(&self.source_2)),
+            OperandInfo::destination(OperandRef::Full(&self.destination)),
+        ]
+    }
+
+    fn swaps_operands(&self) -> bool {
+        self.swap_operands
+    }
+
+    fn memory_effects(&self) -> Vec<MemoryEffect> {
+        operand_memory_effect(&self.source_1, OperandRole::Source)
+            .into_iter()
+            .chain(operand_memory_effect(
+                &self.destination,
+                OperandRole::Destination,
+            ))
+            .collect()
+    }
+}
+
 impl<const N: usize, E: VmEncodingMode<N>> TryFrom<Ptr> for DecodedOpcode<N, E> {
     type Error = InstructionReadError;
 
@@ -0,0 +1,292 @@
+//!
+//! The registers read and written by the instructions.
+//!
+
+use super::*;
+
+///
+/// The set of the general purpose registers. The null register `r0` is never included.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RegisterSet(u16);
+
+impl RegisterSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// The set of `r{first}` to `r{last}` inclusive.
+    ///
+    pub fn range(first: u8, last: u8) -> Self {
+        (first..=last).map(RegisterOperand::Register).collect()
+    }
+
+    ///
+    /// Registers beyond the register file are ignored, as `RegisterOperand::new` rejects them.
+    ///
+    pub fn insert(&mut self, register: RegisterOperand) {
+        if let RegisterOperand::Register(index) = register {
+            self.0 |= Self::bit(index);
+        }
+    }
+
+    ///
+    /// Registers beyond the register file are ignored.
+    ///
+    pub fn remove(&mut self, register: RegisterOperand) {
+        if let RegisterOperand::Register(index) = register {
+            self.0 &= !Self::bit(index);
+        }
+    }
+
+    ///
+    /// Registers beyond the register file are never contained.
+    ///
+    pub fn contains(&self, register: RegisterOperand) -> bool {
+        match register {
+            RegisterOperand::Null => false,
+            RegisterOperand::Register(index) => self.0 & Self::bit(index) != 0,
+        }
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = RegisterOperand> + '_ {
+        (1..=zkevm_opcode_defs::REGISTERS_COUNT as u8)
+            .map(RegisterOperand::Register)
+            .filter(|register| self.contains(*register))
+    }
+
+    ///
+    /// The bit of the register, or none for `r16` and above, which would overflow the set.
+    ///
+    fn bit(index: u8) -> u16 {
+        if (index as usize) <= zkevm_opcode_defs::REGISTERS_COUNT {
+            1 << index
+        } else {
+            0
+        }
+    }
+}
+
+impl FromIterator<RegisterOperand> for RegisterSet {
+    fn from_iter<T: IntoIterator<Item = RegisterOperand>>(iter: T) -> Self {
+        let mut set = Self::new();
+        for register in iter {
+            set.insert(register);
+        }
+        set
+    }
+}
+
+impl std::fmt::Display for RegisterSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let registers: Vec<String> = self.iter().map(|register| register.to_string()).collect();
+        write!(f, "{{{}}}", registers.join(", "))
+    }
+}
+
+impl GenericOperand {
+    ///
+    /// The registers read to evaluate the operand in the source position.
+    ///
+    fn source_registers(&self, registers: &mut RegisterSet) {
+        match self.r#type {
+            ImmMemHandlerFlags::UseImm16Only => {}
+            _ => registers.insert(self.register),
+        }
+    }
+
+    ///
+    /// The register written through the operand in the destination position, while the address
+    /// register of the memory operands is read.
+    ///
+    fn destination_registers(&self, reads: &mut RegisterSet, writes: &mut RegisterSet) {
+        match self.r#type {
+            ImmMemHandlerFlags::UseRegOnly => writes.insert(self.register),
+            _ => reads.insert(self.register),
+        }
+    }
+}
+
+impl FullOperand {
+    pub(crate) fn source_registers(&self, registers: &mut RegisterSet) {
+        match self {
+            FullOperand::Full(operand) => operand.source_registers(registers),
+            FullOperand::Register(register) => registers.insert(*register),
+            FullOperand::Constant(ConstantOperand { register, .. })
+            | FullOperand::GlobalVariable(GlobalVariable { register, .. }) => {
+                registers.insert(*register)
+            }
+        }
+    }
+
+    fn destination_registers(&self, reads: &mut RegisterSet, writes: &mut RegisterSet) {
+        match self {
+            FullOperand::Full(operand) => operand.destination_registers(reads, writes),
+            FullOperand::Register(register) => writes.insert(*register),
+            FullOperand::Constant(ConstantOperand { register, .. })
+            | FullOperand::GlobalVariable(GlobalVariable { register, .. }) => {
+                reads.insert(*register)
+            }
+        }
+    }
+}
+
+impl OperandRef<'_> {
+    fn source_registers(&self, registers: &mut RegisterSet) {
+        match *self {
+            OperandRef::Full(operand) => operand.source_registers(registers),
+            OperandRef::Generic(operand) => operand.source_registers(registers),
+            OperandRef::NonMemory(operand) => {
+                if let RegOrImmFlags::UseRegOnly = operand.r#type {
+                    registers.insert(operand.register);
+                }
+            }
+            OperandRef::Register(register) => registers.insert(*register),
+        }
+    }
+
+    fn destination_registers(&self, reads: &mut RegisterSet, writes: &mut RegisterSet) {
+        match *self {
+            OperandRef::Full(operand) => operand.destination_registers(reads, writes),
+            OperandRef::Generic(operand) => operand.destination_registers(reads, writes),
+            OperandRef::NonMemory(operand) => writes.insert(operand.register),
+            OperandRef::Register(register) => writes.insert(*register),
+        }
+    }
+}
+
+impl Instruction {
+    ///
+    /// The registers read and written by the instruction, including the address registers of
+    /// the memory operands, which are read in both the source and the destination positions.
+    ///
+    /// `far_call` writes `r1` with the pointer to the returned data. `nop` does not write its
+    /// destinations, it only moves the stack pointer.
+    ///
+    pub fn registers(&self) -> (RegisterSet, RegisterSet) {
+        let mut reads = RegisterSet::new();
+        let mut writes = RegisterSet::new();
+        for operand in self.operands() {
+            match operand.role {
+                OperandRole::Source => operand.operand.source_registers(&mut reads),
+                OperandRole::Destination if self.writes_destinations() => operand
+                    .operand
+                    .destination_registers(&mut reads, &mut writes),
+                OperandRole::Destination => operand
+                    .operand
+                    .destination_registers(&mut reads, &mut RegisterSet::new()),
+            }
+        }
+        if self.control_flow() == ControlFlow::FarCall {
+            writes.insert(RegisterOperand::Register(1));
+        }
+
+        (reads, writes)
+    }
+
+    pub fn registers_read(&self) -> RegisterSet {
+        self.registers().0
+    }
+
+    pub fn registers_written(&self) -> RegisterSet {
+        self.registers().1
+    }
+
+    ///
+    /// Whether the instruction only computes the result, and every destination of it is `r0`,
+    /// so it has no effect. The comparisons, which set the flags, are not such.
+    ///
+    pub fn discards_result(&self) -> bool {
+        let destinations = self.destinations();
+        !matches!(self, Instruction::Nop(_))
+            && !destinations.is_empty()
+            && destinations.iter().all(is_null_destination)
+            && !self.sets_flags()
+            && self.memory_effects().is_empty()
+            && self.control_flow() == ControlFlow::Next
+    }
+}
+
+fn is_null_destination(operand: &OperandRef<'_>) -> bool {
+    match *operand {
+        OperandRef::Register(register) => register.is_void(),
+        OperandRef::Full(FullOperand::Register(register)) => register.is_void(),
+        OperandRef::Full(FullOperand::Full(operand)) | OperandRef::Generic(operand) => {
+            operand.r#type == ImmMemHandlerFlags::UseRegOnly && operand.register.is_void()
+        }
+        OperandRef::NonMemory(operand) => {
+            operand.r#type == RegOrImmFlags::UseRegOnly && operand.register.is_void()
+        }
+        OperandRef::Full(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_registers() {
+        let instruction = parse_code_element("add stack[r3 + 1], r2, stack-[r4]").unwrap();
+        assert_eq!(
+            instruction.registers(),
+            (RegisterSet::range(2, 4), RegisterSet::new())
+        );
+
+        let instruction = parse_code_element("uma.heap_read.inc r1, r0, r2, r1").unwrap();
+        assert_eq!(
+            instruction.registers(),
+            (
+                [RegisterOperand::Register(1)].into_iter().collect(),
+                RegisterSet::range(1, 2)
+            )
+        );
+    }
+
+    #[test]
+    fn test_register_beyond_the_file() {
+        let mut set = RegisterSet::range(1, 2);
+        set.insert(RegisterOperand::Register(16));
+        set.remove(RegisterOperand::Register(200));
+        assert_eq!(set, RegisterSet::range(1, 2));
+        assert!(!set.contains(RegisterOperand::Register(16)));
+    }
+
+    #[test]
+    fn test_discards_result() {
+        for discarding in ["add r1, r2, r0", "mul r1, r2, r0, r0", "context.this r0"] {
+            assert!(parse_code_element(discarding).unwrap().discards_result());
+        }
+        for effective in [
+            "sub! r1, r2, r0",
+            "div r1, r2, r0, r3",
+            "add stack-[1], r0, r0",
+            "log.sread r1, r0, r0",
+            "context.set_context_u128 r1",
+            "nop",
+        ] {
+            assert!(!parse_code_element(effective).unwrap().discards_result());
+        }
+    }
+}
@@ -11,12 +11,17 @@ use std::convert::TryFrom;
 /// The control flow jump instruction.
 ///
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ret {
     /// Condition for execution
     pub condition: ConditionCase,
     /// Source register for return parameters
     pub source_for_meta_args: RegisterOperand,
     /// Type of return
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::assembly::serialization::ret_opcode")
+    )]
     pub variant: RetOpcode,
     /// Return to specific label
     pub is_to_label: bool,
@@ -27,9 +32,35 @@ pub struct Ret {
 impl Ret {
     pub const ALL_CANONICAL_MODIFIERS: [&'static str; 3] = ["ok", "revert", "panic"];
 
+    pub const TO_LABEL_MODIFIER: &'static str = "to_label";
+
     // Total number of arguments in canonical form
     pub const NUM_ARGUMENTS: usize = 1;
 
+    pub fn new(variant: RetOpcode, source_for_meta_args: RegisterOperand) -> Self {
+        Self {
+            condition: ConditionCase(Condition::Always),
+            source_for_meta_args,
+            variant,
+            is_to_label: false,
+            label_for_return: None,
+        }
+    }
+
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = ConditionCase(condition);
+        self
+    }
+
+    ///
+    /// Makes the instruction the `ret.*.to_label` one.
+    ///
+    pub fn with_label(mut self, label: impl Into<FullOperand>) -> Self {
+        self.is_to_label = true;
+        self.label_for_return = Some(label.into());
+        self
+    }
+
     #[track_caller]
     pub fn build_from_parts(
         mut modifiers: HashSet<&str>,
@@ -64,7 +95,7 @@ impl Ret {
             result = Some(RetOpcode::Ok);
         }
 
-        let is_to_label = modifiers.remove("to_label");
+        let is_to_label = modifiers.remove(Self::TO_LABEL_MODIFIER);
 
         let (src0, label) = if !is_to_label {
             let operands =
@@ -127,6 +158,48 @@ impl Ret {
     }
 }
 
+impl std::fmt::Display for Ret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut modifiers = vec![match self.variant {
+            RetOpcode::Ok => Self::ALL_CANONICAL_MODIFIERS[0],
+            RetOpcode::Revert => Self::ALL_CANONICAL_MODIFIERS[1],
+            RetOpcode::Panic => Self::ALL_CANONICAL_MODIFIERS[2],
+        }];
+        let mut operands = vec![self.source_for_meta_args.to_string()];
+        if self.is_to_label {
+            modifiers.push(Self::TO_LABEL_MODIFIER);
+        }
+        if let Some(label) = self.label_for_return.as_ref() {
+            operands.push(label.to_canonical_string(false));
+        }
+        write_canonical_form(f, "ret", modifiers, self.condition, &operands)
+    }
+}
+
+impl InstructionMetadata for Ret {
+    fn condition(&self) -> Condition {
+        self.condition.0
+    }
+
+    fn opcode(&self) -> Opcode {
+        Opcode::Ret(self.variant)
+    }
+
+    fn operands(&self) -> Vec<OperandInfo<'_>> {
+        let mut operands = vec![OperandInfo::source(OperandRef::Register(
+            &self.source_for_meta_args,
+        ))];
+        if let Some(label) = self.label_for_return.as_ref() {
+            operands.push(OperandInfo::source(OperandRef::Full(label)));
+        }
+        operands
+    }
+
+    fn control_flow(&self) -> ControlFlow {
+        ControlFlow::Return(self.variant)
+    }
+}
+
 impl<const N: usize, E: VmEncodingMode<N>> TryFrom<Ret> for DecodedOpcode<N, E> {
     type Error = InstructionReadError;
     fn try_from(value: Ret) -> Result<Self, Self::Error> {
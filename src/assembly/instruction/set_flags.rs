@@ -8,6 +8,8 @@ use crate::InstructionReadError;
 /// The control flow jump instruction flag.
 ///
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct SetFlags(pub bool);
 
 impl SetFlags {
@@ -1,6 +1,7 @@
 use super::*;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Shift {
     /// Condition for execution
     pub condition: ConditionCase,
@@ -15,12 +16,48 @@ pub struct Shift {
     /// if it is set then source operands have to be swapped.
     pub swap_operands: bool,
     /// Type of shift
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::assembly::serialization::shift_opcode")
+    )]
     pub variant: ShiftOpcode,
 }
 
 impl Shift {
     pub const ALL_CANONICAL_MODIFIERS: [&'static str; 4] = ["shl", "shr", "rol", "ror"];
 
+    pub fn new(
+        variant: ShiftOpcode,
+        source_1: impl Into<FullOperand>,
+        source_2: RegisterOperand,
+        destination: impl Into<FullOperand>,
+    ) -> Self {
+        Self {
+            condition: ConditionCase(Condition::Always),
+            set_flags_option: SetFlags(false),
+            source_1: source_1.into(),
+            source_2,
+            destination: destination.into(),
+            swap_operands: false,
+            variant,
+        }
+    }
+
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = ConditionCase(condition);
+        self
+    }
+
+    pub fn with_set_flags(mut self, set_flags: bool) -> Self {
+        self.set_flags_option = SetFlags(set_flags);
+        self
+    }
+
+    pub fn with_swapped_operands(mut self, swap_operands: bool) -> Self {
+        self.swap_operands = swap_operands;
+        self
+    }
+
     #[track_caller]
     pub fn build_from_parts(
         mut modifiers: HashSet<&str>,
@@ -40,7 +77,7 @@ impl Shift {
         let set_flags_option = pick_setting_flags(&mut modifiers)?;
 
         let mut swap_operands = false;
-        if modifiers.remove("s") {
+        if modifiers.remove(SWAP_OPERANDS_MODIFIER) {
             swap_operands = true;
         }
 
@@ -118,6 +155,70 @@ impl Shift {
     }
 }
 
+impl std::fmt::Display for Shift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut modifiers = vec![match self.variant {
+            ShiftOpcode::Shl => Self::ALL_CANONICAL_MODIFIERS[0],
+            ShiftOpcode::Shr => Self::ALL_CANONICAL_MODIFIERS[1],
+            ShiftOpcode::Rol => Self::ALL_CANONICAL_MODIFIERS[2],
+            ShiftOpcode::Ror => Self::ALL_CANONICAL_MODIFIERS[3],
+        }];
+        if self.swap_operands {
+            modifiers.push(SWAP_OPERANDS_MODIFIER);
+        }
+        if self.set_flags_option.0 {
+            modifiers.push(SetFlags::ALL_CANONICAL_MODIFIERS[0]);
+        }
+        write_canonical_form(
+            f,
+            "shift",
+            modifiers,
+            self.condition,
+            &[
+                self.source_1.to_canonical_string(false),
+                self.source_2.to_string(),
+                self.destination.to_canonical_string(true),
+            ],
+        )
+    }
+}
+
+impl InstructionMetadata for Shift {
+    fn condition(&self) -> Condition {
+        self.condition.0
+    }
+
+    fn opcode(&self) -> Opcode {
+        Opcode::Shift(self.variant)
+    }
+
+    fn operands(&self) -> Vec<OperandInfo<'_>> {
+        vec![
+            OperandInfo::source(OperandRef::Full(&self.source_1)),
+            OperandInfo::source(OperandRef::Register(&self.source_2)),
+            OperandInfo::destination(OperandRef::Full(&self.destination)),
+        ]
+    }
+
+    fn sets_flags(&self) -> bool {
+        self.set_flags_option.0
+    }
+
+    fn swaps_operands(&self) -> bool {
+        self.swap_operands
+    }
+
+    fn memory_effects(&self) -> Vec<MemoryEffect> {
+        operand_memory_effect(&self.source_1, OperandRole::Source)
+            .into_iter()
+            .chain(operand_memory_effect(
+                &self.destination,
+                OperandRole::Destination,
+            ))
+            .collect()
+    }
+}
+
 impl<const N: usize, E: VmEncodingMode<N>> TryFrom<Shift> for DecodedOpcode<N, E> {
     type Error = InstructionReadError;
     fn try_from(value: Shift) -> Result<Self, Self::Error> {
@@ -12,6 +12,7 @@ use std::convert::TryFrom;
 /// Read value from execution context.
 ///
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UMA {
     /// Condition for execution
     pub condition: ConditionCase,
@@ -24,6 +25,10 @@ pub struct UMA {
     /// The destination register incremented source on read
     pub dst_1: RegisterOperand,
     /// Type of log
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::assembly::serialization::uma_opcode")
+    )]
     pub uma_type: UMAOpcode,
     /// increment offset or not
     pub increment_offset: bool,
@@ -42,6 +47,34 @@ impl UMA {
 
     pub const ALL_SHORTHARD_MODIFIERS: [&'static str; 5] = ["rh", "wh", "rah", "wah", "rptr"];
 
+    pub fn new(
+        uma_type: UMAOpcode,
+        src_0: NonMemoryOperand,
+        src_1: RegisterOperand,
+        dst_0: RegisterOperand,
+        dst_1: RegisterOperand,
+    ) -> Self {
+        Self {
+            condition: ConditionCase(Condition::Always),
+            src_0,
+            src_1,
+            dst_0,
+            dst_1,
+            uma_type,
+            increment_offset: false,
+        }
+    }
+
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.condition = ConditionCase(condition);
+        self
+    }
+
+    pub fn with_increment(mut self, increment_offset: bool) -> Self {
+        self.increment_offset = increment_offset;
+        self
+    }
+
     #[track_caller]
     pub fn build_from_parts(
         mut modifiers: HashSet<&str>,
@@ -151,19 +184,22 @@ impl UMA {
     ) -> Result<(), AssemblyParseError> {
         Ok(())
     }
-}
 
-impl<const N: usize, E: VmEncodingMode<N>> TryFrom<UMA> for DecodedOpcode<N, E> {
-    type Error = InstructionReadError;
-    fn try_from(value: UMA) -> Result<Self, Self::Error> {
+    ///
+    /// Encodes the instruction. The first source is only allowed to be an immediate since ISA version 1.
+    ///
+    pub fn encode<const N: usize, E: VmEncodingMode<N>>(
+        self,
+        isa_version: ISAVersion,
+    ) -> Result<DecodedOpcode<N, E>, InstructionReadError> {
+        let value = self;
         let mut new = DecodedOpcode::default();
         new.variant = OpcodeVariant {
             opcode: Opcode::UMA(value.uma_type),
             ..OpcodeVariant::default()
         };
-        match new.variant.opcode.input_operands(crate::get_isa_version())[0] {
+        match new.variant.opcode.input_operands(isa_version)[0] {
             Operand::RegOrImm(_) => {
-                assert!(crate::get_isa_version().0 == 1);
                 set_src_non_memory_operand(&value.src_0, &mut new);
             }
             Operand::RegOnly => {
@@ -181,3 +217,67 @@ impl<const N: usize, E: VmEncodingMode<N>> TryFrom<UMA> for DecodedOpcode<N, E>
         Ok(new)
     }
 }
+
+impl std::fmt::Display for UMA {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut modifiers = vec![match self.uma_type {
+            UMAOpcode::HeapRead => Self::ALL_CANONICAL_MODIFIERS[0],
+            UMAOpcode::HeapWrite => Self::ALL_CANONICAL_MODIFIERS[1],
+            UMAOpcode::AuxHeapRead => Self::ALL_CANONICAL_MODIFIERS[2],
+            UMAOpcode::AuxHeapWrite => Self::ALL_CANONICAL_MODIFIERS[3],
+            UMAOpcode::FatPointerRead => Self::ALL_CANONICAL_MODIFIERS[4],
+        }];
+        if self.increment_offset {
+            modifiers.push(Self::INCREMENT_OFFSET_MODIFIER);
+        }
+        write_canonical_form(
+            f,
+            "uma",
+            modifiers,
+            self.condition,
+            &[
+                self.src_0.to_string(),
+                self.src_1.to_string(),
+                self.dst_0.to_string(),
+                self.dst_1.to_string(),
+            ],
+        )
+    }
+}
+
+impl InstructionMetadata for UMA {
+    fn condition(&self) -> Condition {
+        self.condition.0
+    }
+
+    fn opcode(&self) -> Opcode {
+        Opcode::UMA(self.uma_type)
+    }
+
+    fn operands(&self) -> Vec<OperandInfo<'_>> {
+        vec![
+            OperandInfo::source(OperandRef::NonMemory(&self.src_0)),
+            OperandInfo::source(OperandRef::Register(&self.src_1)),
+            OperandInfo::destination(OperandRef::Register(&self.dst_0)),
+            OperandInfo::destination(OperandRef::Register(&self.dst_1)),
+        ]
+    }
+
+    fn memory_effects(&self) -> Vec<MemoryEffect> {
+        vec![match self.uma_type {
+            UMAOpcode::HeapRead => MemoryEffect::HeapRead,
+            UMAOpcode::HeapWrite => MemoryEffect::HeapWrite,
+            UMAOpcode::AuxHeapRead => MemoryEffect::AuxHeapRead,
+            UMAOpcode::AuxHeapWrite => MemoryEffect::AuxHeapWrite,
+            UMAOpcode::FatPointerRead => MemoryEffect::FatPointerRead,
+        }]
+    }
+}
+
+impl<const N: usize, E: VmEncodingMode<N>> TryFrom<UMA> for DecodedOpcode<N, E> {
+    type Error = InstructionReadError;
+    #[allow(deprecated)]
+    fn try_from(value: UMA) -> Result<Self, Self::Error> {
+        value.encode(crate::get_isa_version())
+    }
+}
@@ -4,7 +4,7 @@ pub fn pick_condition(
     modifiers: &mut HashSet<&str>,
 ) -> Result<ConditionCase, InstructionReadError> {
     let mut result = None;
-    for modifier in ConditionCase::ALL_CANONICAL_MODIFIERS.iter() {
+    for (modifier, _) in ConditionCase::ALL_MODIFIERS.iter() {
         if modifiers.contains(modifier) {
             if result.is_some() {
                 return Err(InstructionReadError::UnknownArgument(format!(
@@ -41,3 +41,25 @@ pub fn pick_setting_flags(modifiers: &mut HashSet<&str>) -> Result<SetFlags, Ins
 
     Ok(result.unwrap_or_default())
 }
+
+///
+/// Writes the instruction as `opcode.modifier.condition operand, operand`.
+///
+pub(crate) fn write_canonical_form(
+    f: &mut std::fmt::Formatter<'_>,
+    opcode: &str,
+    mut modifiers: Vec<&str>,
+    condition: ConditionCase,
+    operands: &[String],
+) -> std::fmt::Result {
+    modifiers.extend(condition.canonical_modifier());
+    write!(f, "{}", opcode)?;
+    for modifier in modifiers {
+        write!(f, ".{}", modifier)?;
+    }
+    if !operands.is_empty() {
+        write!(f, " {}", operands.join(", "))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,508 @@
+//!
+//! The source analysis for the editor support.
+//!
+//! The lines and columns are 0-based, and the columns count the characters of the line.
+//!
+
+use super::config::AssemblerConfig;
+use super::instruction::ALL_CANONICAL_OPCODES;
+use super::mnemonic::Mnemonic;
+use super::parse::code_element::parse_code_elements;
+use super::parse::literal::strip_comment;
+use super::parse::register_alias::RegisterAliases;
+use super::Assembly;
+use super::Instruction;
+use crate::error::{AssemblyParseError, InstructionReadError, SectionReadError};
+use std::collections::HashMap;
+
+///
+/// The range of the characters on the line.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceRange {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+///
+/// The parser or linker error at the source range.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceDiagnostic {
+    pub range: SourceRange,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Mnemonic,
+    Modifier,
+    Register,
+    Label,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+}
+
+///
+/// The assembly source being edited, which is analyzed even if it does not parse.
+///
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    text: String,
+    definitions: HashMap<String, SourceRange>,
+}
+
+impl SourceFile {
+    pub fn new(text: String) -> Self {
+        let mut definitions = HashMap::new();
+        for (line, code) in text.lines().enumerate() {
+            if let Some((label, range)) = label_definition(line, code) {
+                definitions.entry(label.to_owned()).or_insert(range);
+            }
+        }
+
+        Self { text, definitions }
+    }
+
+    pub fn text(&self) -> &str {
+        self.text.as_str()
+    }
+
+    ///
+    /// The range of the first definition of the label.
+    ///
+    pub fn definition(&self, label: &str) -> Option<SourceRange> {
+        self.definitions.get(label).copied()
+    }
+
+    ///
+    /// The label referenced with `@` or defined at the position.
+    ///
+    pub fn label_at(&self, line: usize, column: usize) -> Option<(String, SourceRange)> {
+        let code = strip_comment(self.text.lines().nth(line)?);
+        let chars: Vec<char> = code.chars().collect();
+        if column > chars.len() {
+            return None;
+        }
+
+        let start = chars[..column]
+            .iter()
+            .rposition(|c| !is_label_char(*c))
+            .map_or(0, |index| index + 1);
+        let end = chars[column..]
+            .iter()
+            .position(|c| !is_label_char(*c))
+            .map_or(chars.len(), |index| column + index);
+        if start == end {
+            return None;
+        }
+
+        let label: String = chars[start..end].iter().collect();
+        let range = SourceRange { line, start, end };
+        let is_reference = start > 0 && chars[start - 1] == '@';
+        let is_definition = label_definition(line, code).map(|(_, range)| range) == Some(range);
+        (is_reference || is_definition).then_some((label, range))
+    }
+
+    ///
+    /// The definition of the label at the position.
+    ///
+    pub fn definition_at(&self, line: usize, column: usize) -> Option<SourceRange> {
+        let (label, _) = self.label_at(line, column)?;
+        self.definition(label.as_str())
+    }
+
+    ///
+    /// The canonical form of the instruction on the line, with the shorthands expanded, or the
+    /// canonical instructions on separate lines for the pseudo-instructions like `select`.
+    ///
+    pub fn expansion(&self, line: usize) -> Option<String> {
+        let code = strip_comment(self.text.lines().nth(line)?).trim();
+        if code.is_empty() || code.starts_with('.') || code.ends_with(':') {
+            return None;
+        }
+
+        let instructions = parse_code_elements(&self.register_aliases(line).resolve(code)).ok()?;
+        Some(
+            instructions
+                .iter()
+                .map(Instruction::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+    }
+
+    ///
+    /// The errors of parsing and linking the source with the `config`.
+    ///
+    pub fn diagnostics(&self, config: AssemblerConfig) -> Vec<SourceDiagnostic> {
+        let result = Assembly::from_string_with_config(self.text.clone(), config)
+            .map_err(InstructionReadError::AssemblyParseError)
+            .and_then(|mut assembly| assembly.compile_to_bytecode());
+
+        let error = match result {
+            Ok(_) => return vec![],
+            Err(InstructionReadError::AssemblyParseError(error)) => error,
+            Err(error) => return vec![self.diagnostic(0, error.to_string())],
+        };
+
+        let message = error.to_string();
+        let mut diagnostics = match error {
+            AssemblyParseError::DataSectionInvalid(SectionReadError::LineReadError(errors))
+            | AssemblyParseError::GlobalsSectionInvalid(SectionReadError::LineReadError(errors))
+            | AssemblyParseError::TextSectionInvalid(SectionReadError::LineReadError(errors)) => {
+                let offset = self.trimmed_lines();
+                errors
+                    .into_iter()
+                    .map(|(line, (_, error))| self.diagnostic(line + offset, error.to_string()))
+                    .collect()
+            }
+            AssemblyParseError::DuplicateLabel(label) => self
+                .text
+                .lines()
+                .enumerate()
+                .filter_map(|(line, code)| label_definition(line, code))
+                .filter(|(defined, _)| *defined == label)
+                .skip(1)
+                .map(|(_, range)| SourceDiagnostic {
+                    range,
+                    message: message.clone(),
+                })
+                .collect(),
+            AssemblyParseError::LabelNotFound(label) => self
+                .references(label.as_str())
+                .into_iter()
+                .map(|range| SourceDiagnostic {
+                    range,
+                    message: message.clone(),
+                })
+                .collect(),
+            _ => vec![],
+        };
+        if diagnostics.is_empty() {
+            diagnostics.push(self.diagnostic(0, message));
+        }
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.range.line, diagnostic.range.start));
+        diagnostics
+    }
+
+    ///
+    /// The mnemonics at the start of the line, the modifiers after the `.` of the opcode, the
+    /// labels after `@`, and the registers elsewhere.
+    ///
+    pub fn completions(&self, line: usize, column: usize) -> Vec<Completion> {
+        let code = self.text.lines().nth(line).unwrap_or_default();
+        let before: String = code.chars().take(column).collect();
+        if before.contains(';') {
+            return vec![];
+        }
+
+        let word_start = before
+            .rfind(|c: char| !is_label_char(c) && c != '@')
+            .map_or(0, |index| index + 1);
+        let (head, word) = before.split_at(word_start);
+
+        if word.starts_with('@') {
+            let mut labels: Vec<&String> = self.definitions.keys().collect();
+            labels.sort();
+            return completions(labels, CompletionKind::Label);
+        }
+
+        if !head.trim().is_empty() {
+            let aliases = self.register_aliases(line);
+            let registers = (0..=zkevm_opcode_defs::REGISTERS_COUNT)
+                .map(|index| format!("r{}", index))
+                .chain(aliases.names().into_iter().map(str::to_owned));
+            return completions(registers, CompletionKind::Register);
+        }
+
+        match word.split_once('.') {
+            Some((opcode, _)) => completions(modifiers(opcode), CompletionKind::Modifier),
+            None => completions(
+                ALL_CANONICAL_OPCODES
+                    .iter()
+                    .copied()
+                    .chain(Mnemonic::names()),
+                CompletionKind::Mnemonic,
+            ),
+        }
+    }
+
+    ///
+    /// The ranges of the `@label` references.
+    ///
+    fn references(&self, label: &str) -> Vec<SourceRange> {
+        let pattern = format!("@{}", label);
+        let mut references = vec![];
+        for (line, code) in self.text.lines().enumerate() {
+            let code = strip_comment(code);
+            for (index, _) in code.match_indices(pattern.as_str()) {
+                let end = index + pattern.len();
+                if code[end..].starts_with(is_label_char) {
+                    continue;
+                }
+                let start = code[..index].chars().count() + 1;
+                references.push(SourceRange {
+                    line,
+                    start,
+                    end: start + label.chars().count(),
+                });
+            }
+        }
+        references
+    }
+
+    ///
+    /// The diagnostic spanning the code on the line.
+    ///
+    fn diagnostic(&self, line: usize, message: String) -> SourceDiagnostic {
+        let code = strip_comment(self.text.lines().nth(line).unwrap_or_default());
+        let start = code.chars().take_while(|c| c.is_whitespace()).count();
+        let end = code.trim_end().chars().count().max(start);
+        SourceDiagnostic {
+            range: SourceRange { line, start, end },
+            message,
+        }
+    }
+
+    ///
+    /// The register aliases in scope at the line, as defined by the directives above it.
+    ///
+    fn register_aliases(&self, line: usize) -> RegisterAliases {
+        let mut aliases = RegisterAliases::default();
+        for code in self.text.lines().take(line) {
+            let _ = aliases.apply_directive(strip_comment(code));
+        }
+        aliases
+    }
+
+    ///
+    /// The number of the leading empty lines, which `Assembly` trims before numbering the lines.
+    ///
+    fn trimmed_lines(&self) -> usize {
+        let newline = ['\r', '\n'];
+        let trimmed = self.text.len() - self.text.trim_start_matches(&newline[..]).len();
+        self.text[..trimmed].matches('\n').count()
+    }
+}
+
+fn is_label_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '$'
+}
+
+///
+/// The label defined by the line and the range of its name.
+///
+fn label_definition(line: usize, code: &str) -> Option<(&str, SourceRange)> {
+    let code = strip_comment(code);
+    let label = code.trim().strip_suffix(':')?;
+    if label.is_empty() || !label.chars().all(is_label_char) {
+        return None;
+    }
+
+    let start = code.chars().take_while(|c| c.is_whitespace()).count();
+    let range = SourceRange {
+        line,
+        start,
+        end: start + label.chars().count(),
+    };
+    Some((label, range))
+}
+
+///
+/// The modifiers accepted by the canonical opcode.
+///
+fn modifiers(opcode: &str) -> Vec<&'static str> {
+    use super::instruction::condition::ConditionCase;
+    use super::instruction::set_flags::SetFlags;
+    use super::instruction::*;
+
+    let mut modifiers: Vec<&'static str> = match opcode {
+        "add" | "mul" => SetFlags::ALL_CANONICAL_MODIFIERS.to_vec(),
+        "sub" | "div" => [
+            &[SWAP_OPERANDS_MODIFIER],
+            &SetFlags::ALL_CANONICAL_MODIFIERS[..],
+        ]
+        .concat(),
+        "context" => context::Context::ALL_CANONICAL_MODIFIERS.to_vec(),
+        "shift" => [
+            &shift::Shift::ALL_CANONICAL_MODIFIERS[..],
+            &[SWAP_OPERANDS_MODIFIER],
+            &SetFlags::ALL_CANONICAL_MODIFIERS[..],
+        ]
+        .concat(),
+        "binop" => [
+            &bitwise::Bitwise::ALL_CANONICAL_MODIFIERS[..],
+            &SetFlags::ALL_CANONICAL_MODIFIERS[..],
+        ]
+        .concat(),
+        "ptr" => [
+            &ptr::Ptr::ALL_CANONICAL_MODIFIERS[..],
+            &[SWAP_OPERANDS_MODIFIER],
+        ]
+        .concat(),
+        "log" => [
+            &log::Log::ALL_CANONICAL_MODIFIERS[..],
+            &[log::Log::INITIAL_MODIFIER],
+        ]
+        .concat(),
+        "far_call" => [
+            &far_call::FarCall::ALL_CANONICAL_MODIFIERS[..],
+            &[
+                far_call::FarCall::STATIC_MODIFIER,
+                far_call::FarCall::SHARD_MODIFIER,
+            ],
+        ]
+        .concat(),
+        "ret" => [
+            &ret::Ret::ALL_CANONICAL_MODIFIERS[..],
+            &[ret::Ret::TO_LABEL_MODIFIER],
+        ]
+        .concat(),
+        "uma" => [
+            &uma::UMA::ALL_CANONICAL_MODIFIERS[..],
+            &[uma::UMA::INCREMENT_OFFSET_MODIFIER],
+        ]
+        .concat(),
+        "invalid" | "nop" | "jump" | "near_call" => vec![],
+        _ => return vec![],
+    };
+    modifiers.extend(
+        ConditionCase::ALL_MODIFIERS
+            .iter()
+            .map(|(modifier, _)| *modifier),
+    );
+    modifiers
+}
+
+fn completions<T: ToString>(
+    labels: impl IntoIterator<Item = T>,
+    kind: CompletionKind,
+) -> Vec<Completion> {
+    labels
+        .into_iter()
+        .map(|label| Completion {
+            label: label.to_string(),
+            kind,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SOURCE: &str = r#"
+    .text
+__entry:
+    add @CPI0_0[0], r0, r1; load
+    near_call r0, @f, @DEFAULT_UNWIND
+    ret
+f:
+    jump @missing
+    .rodata
+CPI0_0:
+    .cell 1
+"#;
+
+    #[test]
+    fn test_navigation() {
+        let file = SourceFile::new(SOURCE.to_owned());
+        let (label, range) = file.label_at(3, 10).unwrap();
+        assert_eq!(label, "CPI0_0");
+        assert_eq!(
+            range,
+            SourceRange {
+                line: 3,
+                start: 9,
+                end: 15
+            }
+        );
+        assert_eq!(
+            file.definition_at(3, 10),
+            Some(SourceRange {
+                line: 9,
+                start: 0,
+                end: 6
+            })
+        );
+        assert_eq!(
+            file.definition_at(4, 20),
+            Some(SourceRange {
+                line: 6,
+                start: 0,
+                end: 1
+            })
+        );
+        assert_eq!(file.label_at(3, 22), None);
+        assert_eq!(file.label_at(3, 28), None);
+
+        assert_eq!(file.expansion(5), Some("ret.ok r1".to_owned()));
+        assert_eq!(file.expansion(6), None);
+        let aliased = SourceFile::new("    .reg value, r3\n    add value, a0, r2".to_owned());
+        assert_eq!(aliased.expansion(1), Some("add r3, r1, r2".to_owned()));
+    }
+
+    #[test]
+    fn test_diagnostics_and_completions() {
+        let file = SourceFile::new(SOURCE.to_owned());
+        let config = AssemblerConfig::new(
+            crate::RunningVmEncodingMode::Production,
+            crate::DEFAULT_ISA_VERSION,
+        );
+        let diagnostics = file.diagnostics(config);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].range,
+            SourceRange {
+                line: 7,
+                start: 10,
+                end: 17
+            }
+        );
+
+        let file = SourceFile::new(SOURCE.replace("jump @missing", "jump r1, r2"));
+        let diagnostics = file.diagnostics(config);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].range,
+            SourceRange {
+                line: 7,
+                start: 4,
+                end: 15
+            }
+        );
+        assert!(file.diagnostics(config)[0]
+            .message
+            .contains("invalid number of arguments"));
+
+        let kinds = |line, column| -> Vec<CompletionKind> {
+            file.completions(line, column)
+                .into_iter()
+                .map(|completion| completion.kind)
+                .collect()
+        };
+        assert!(kinds(5, 5)
+            .iter()
+            .all(|kind| *kind == CompletionKind::Mnemonic));
+        assert!(kinds(4, 19)
+            .iter()
+            .all(|kind| *kind == CompletionKind::Label));
+        assert!(kinds(4, 16)
+            .iter()
+            .all(|kind| *kind == CompletionKind::Register));
+        assert!(kinds(3, 30).is_empty());
+        let modifiers: Vec<String> = SourceFile::new("    ret.".to_owned())
+            .completions(0, 8)
+            .into_iter()
+            .map(|completion| completion.label)
+            .collect();
+        assert!(modifiers.contains(&"to_label".to_owned()));
+        assert!(modifiers.contains(&"revert".to_owned()));
+    }
+}
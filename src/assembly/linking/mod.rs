@@ -1,9 +1,18 @@
 use zkevm_opcode_defs::decoding::encoding_mode_production::EncodingModeProduction;
 use zkevm_opcode_defs::decoding::VmEncodingMode;
+use zkevm_opcode_defs::ISAVersion;
 
 use super::*;
+use crate::assembly::config::{AssemblerConfig, LandingPadPolicy};
 use crate::assembly::constants::*;
+use crate::assembly::instruction::add::Add;
+use crate::assembly::instruction::metadata::{InstructionMetadata, OperandRef};
+use crate::assembly::instruction::nop::Nop;
+use crate::assembly::mnemonic::LOAD_IMMEDIATE_LABEL_PREFIX;
+use crate::assembly::operand::{GenericOperand, RegisterOperand};
 use crate::assembly::section::*;
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
 
 pub const DEFAULT_UNWIND_LABEL: &str = "DEFAULT_UNWIND";
 const DEFAULT_UNWIND_LANDING_PAD_ASSEMBLY: &str = "ret.panic.to_label r0, @DEFAULT_UNWIND";
@@ -38,6 +47,181 @@ pub enum AlignedRawBytecode {
     Data(ConstantValue),
 }
 
+/// The unpacked bytecode, the PC to line mapping, the function labels, the function sizes, the number
+/// of deduplicated constants and the number of the global variable words.
+pub(crate) type LinkerOutput = (
+    Vec<AlignedRawBytecode>,
+    HashMap<usize, usize>,
+    HashMap<String, usize>,
+    HashMap<String, usize>,
+    usize,
+    usize,
+);
+
+///
+/// The assembly linked for the particular encoding mode.
+///
+/// Everything that depends on the encoding mode is computed once, so using the
+/// result for another mode is a compile-time error.
+///
+/// With the `serde` feature, the unpacked bytecode is serialized with the ISA version, the
+/// bytecode words as 0x-prefixed hex strings, `pc_line_mapping`, `function_labels`,
+/// `function_sizes`, `deduplicated_constants` and `globals_size`. The instructions are encoded
+/// again for the mode `E` when deserialized, and must produce the same bytecode words.
+///
+#[derive(Debug, Clone)]
+pub struct LinkedAssembly<const N: usize = 8, E: VmEncodingMode<N> = EncodingModeProduction> {
+    pub(crate) raw_bytecode: Vec<AlignedRawBytecode>,
+    pub(crate) bytecode: Vec<[u8; 32]>,
+    pub(crate) instructions: Vec<Instruction>,
+    pub(crate) opcodes: Vec<DecodedOpcode<N, E>>,
+    pub(crate) pc_line_mapping: HashMap<usize, usize>,
+    pub(crate) function_labels: HashMap<String, usize>,
+    pub(crate) function_sizes: HashMap<String, usize>,
+    pub(crate) deduplicated_constants: usize,
+    pub(crate) globals_size: usize,
+    pub(crate) isa_version: ISAVersion,
+}
+
+impl<const N: usize, E: VmEncodingMode<N>> LinkedAssembly<N, E> {
+    pub(crate) fn new(
+        raw_bytecode: Vec<AlignedRawBytecode>,
+        pc_line_mapping: HashMap<usize, usize>,
+        function_labels: HashMap<String, usize>,
+        function_sizes: HashMap<String, usize>,
+        deduplicated_constants: usize,
+        globals_size: usize,
+        isa_version: ISAVersion,
+    ) -> Result<Self, InstructionReadError> {
+        let opcodes_per_word = 32 / N;
+        assert!(32 % N == 0, "unaligned bytecode packing");
+
+        let mut bytecode = Vec::with_capacity(raw_bytecode.len());
+        let mut instructions = Vec::with_capacity(raw_bytecode.len() * opcodes_per_word);
+        let mut opcodes = Vec::with_capacity(raw_bytecode.len() * opcodes_per_word);
+
+        for el in raw_bytecode.iter() {
+            match el {
+                AlignedRawBytecode::Instructions(chunk) => {
+                    assert_eq!(opcodes_per_word, chunk.len());
+                    let mut result = [0u8; 32];
+                    for (i, instr) in chunk.iter().enumerate() {
+                        let opcode: DecodedOpcode<N, E> = instr.clone().encode(isa_version)?;
+                        let serialized_bytecode = opcode.serialize_as_bytes();
+                        result[N * i..N * (i + 1)].copy_from_slice(&serialized_bytecode);
+                        instructions.push(instr.clone());
+                        opcodes.push(opcode);
+                    }
+                    bytecode.push(result);
+                }
+                AlignedRawBytecode::Data(data) => {
+                    bytecode.push(data.clone().serialize());
+                }
+            }
+        }
+
+        use zkevm_opcode_defs::decoding::AllowedPcOrImm;
+
+        let num_instructions = instructions.len() as u64;
+        if num_instructions > E::PcOrImm::max().as_u64() {
+            return Err(InstructionReadError::TooManyOpcodes(
+                E::PcOrImm::max().as_u64(),
+                num_instructions,
+            ));
+        }
+
+        if bytecode.len() as u64 > E::PcOrImm::max().as_u64() {
+            return Err(InstructionReadError::CodeIsTooLong(
+                E::PcOrImm::max().as_u64(),
+                bytecode.len() as u64,
+            ));
+        }
+
+        Ok(Self {
+            raw_bytecode,
+            bytecode,
+            instructions,
+            opcodes,
+            pc_line_mapping,
+            function_labels,
+            function_sizes,
+            deduplicated_constants,
+            globals_size,
+            isa_version,
+        })
+    }
+
+    /// The bytecode words, including the data section and the metadata hash.
+    pub fn bytecode(&self) -> &[[u8; 32]] {
+        &self.bytecode
+    }
+
+    /// The linked instructions, including the padding ones.
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// The encoded instructions, including the padding ones.
+    pub fn opcodes(&self) -> &[DecodedOpcode<N, E>] {
+        &self.opcodes
+    }
+
+    pub fn pc_line_mapping(&self) -> &HashMap<usize, usize> {
+        &self.pc_line_mapping
+    }
+
+    pub fn function_labels(&self) -> &HashMap<String, usize> {
+        &self.function_labels
+    }
+
+    ///
+    /// The label at the PC. The global labels are preferred to the local `.`-prefixed ones.
+    ///
+    pub fn label_at(&self, pc: usize) -> Option<&str> {
+        label_at(&self.function_labels, pc)
+    }
+
+    ///
+    /// The number of instructions of every function, including its local `.`-prefixed blocks.
+    ///
+    pub fn function_sizes(&self) -> &HashMap<String, usize> {
+        &self.function_sizes
+    }
+
+    /// The number of data section words saved by sharing identical constants.
+    pub fn deduplicated_constants(&self) -> usize {
+        self.deduplicated_constants
+    }
+
+    ///
+    /// The number of the global variable words, which the code at PC 0 allocates on the stack
+    /// with `nop stack+=[..]` before initializing them.
+    ///
+    pub fn globals_size(&self) -> usize {
+        self.globals_size
+    }
+
+    pub fn into_bytecode(self) -> Vec<[u8; 32]> {
+        self.bytecode
+    }
+
+    pub fn into_instructions(self) -> Vec<Instruction> {
+        self.instructions
+    }
+
+    pub fn into_opcodes(self) -> Vec<DecodedOpcode<N, E>> {
+        self.opcodes
+    }
+}
+
+pub(crate) fn label_at(function_labels: &HashMap<String, usize>, pc: usize) -> Option<&str> {
+    function_labels
+        .iter()
+        .filter(|(_, label_pc)| **label_pc == pc)
+        .map(|(label, _)| label.as_str())
+        .min_by_key(|label| (label.starts_with('.'), *label))
+}
+
 pub fn production_linker() -> Linker<8, EncodingModeProduction> {
     Linker::new()
 }
@@ -45,38 +229,45 @@ pub fn production_linker() -> Linker<8, EncodingModeProduction> {
 #[derive(Clone, Copy, Debug)]
 
 pub struct Linker<const N: usize = 8, E: VmEncodingMode<N> = EncodingModeProduction> {
+    config: AssemblerConfig,
     _marker: std::marker::PhantomData<E>,
 }
 
 impl<const N: usize, E: VmEncodingMode<N>> Linker<N, E> {
     pub fn new() -> Self {
+        Self::with_config(AssemblerConfig::default())
+    }
+
+    pub fn with_config(config: AssemblerConfig) -> Self {
         Self {
+            config,
             _marker: std::marker::PhantomData,
         }
     }
 
     pub(crate) fn link(
         &self,
-        sections: Vec<ParsedSection>,
+        mut sections: Vec<ParsedSection>,
         mut labels: HashSet<String>,
+        entry: Option<&str>,
         metadata_hash: Option<[u8; 32]>,
-    ) -> Result<
-        (
-            Vec<AlignedRawBytecode>,
-            HashMap<usize, usize>,
-            HashMap<String, usize>,
-        ),
-        AssemblyParseError,
-    > {
+    ) -> Result<LinkerOutput, AssemblyParseError> {
+        if let Some(entry) = entry {
+            place_entry_first(&mut sections, entry)?;
+        }
+
         let mut result = vec![];
 
         let mut aligned_code = vec![];
         let mut aligned_constants = vec![];
         let mut aligned_globals_values = vec![];
         let mut function_labels_to_pc = HashMap::new();
+        let mut function_sizes = HashMap::new();
         let mut constant_labels_to_offset = HashMap::new();
         let mut globals_labels_to_offset = HashMap::new();
         let mut pc_to_line_mapping = HashMap::new();
+        let mut interned_cells = HashMap::new();
+        let mut deduplicated_constants = 0usize;
 
         let mut non_trivial_initializers = vec![];
 
@@ -123,10 +314,15 @@ impl<const N: usize, E: VmEncodingMode<N>> Linker<N, E> {
         }
 
         if !aligned_globals_values.is_empty() {
-            use crate::assembly::parse::code_element::parse_code_element;
-            let asm_line = format!("nop r0, r0, stack+=[{}], r0", aligned_globals_values.len());
-            let opcode = parse_code_element(&asm_line).unwrap();
-            aligned_code.push(opcode);
+            // nop r0, r0, stack+=[G], r0
+            let opcode = Nop::new(
+                GenericOperand::register(RegisterOperand::Null),
+                GenericOperand::stack_push_pop(
+                    RegisterOperand::Null,
+                    aligned_globals_values.len() as u64,
+                ),
+            );
+            aligned_code.push(opcode.into());
         }
 
         // and now we also add non-trivial initializers
@@ -136,26 +332,30 @@ impl<const N: usize, E: VmEncodingMode<N>> Linker<N, E> {
         // to do so we have to add some values into data section first
         for (label, in_variable_idx, constant) in non_trivial_initializers.into_iter() {
             let initializing_label = format!("_INTERNAL_INIT_{}_{}", &label, in_variable_idx);
-            let offset = aligned_constants.len();
+            let (offset, is_reused) =
+                intern_constant(&mut aligned_constants, &mut interned_cells, constant);
+            if is_reused {
+                deduplicated_constants += 1;
+            }
             constant_labels_to_offset.insert(initializing_label.clone(), offset);
-            aligned_constants.push(constant);
 
-            // and add the corresponding instruction
-            use crate::assembly::parse::code_element::parse_code_element;
-            let asm_line = format!(
-                "add @{}[0], r0, stack[@{} + {}]",
-                initializing_label, label, in_variable_idx,
+            // and add the corresponding instruction, add @INIT[0], r0, stack[@label + idx]
+            let opcode = Add::new(
+                FullOperand::constant(&initializing_label, RegisterOperand::Null, 0),
+                RegisterOperand::Null,
+                FullOperand::global(&label, RegisterOperand::Null, in_variable_idx as u64),
             );
-            let opcode = parse_code_element(&asm_line).unwrap();
-            aligned_code.push(opcode);
+            aligned_code.push(opcode.into());
         }
 
         // and now we can just continue with other sections
 
+        let indexed_labels = indexed_constant_labels(&sections);
         for section in sections.into_iter() {
             // just copy
             match section {
                 ParsedSection::Text(section) => {
+                    let mut function: Option<String> = None;
                     for el in section.elements.into_iter() {
                         match el {
                             TextSectionElement::Unlabeled(code) => {
@@ -174,6 +374,13 @@ impl<const N: usize, E: VmEncodingMode<N>> Linker<N, E> {
                             }) => {
                                 let pc = aligned_code.len();
                                 assert!(labels.remove(&*label));
+                                if !label.starts_with('.') || function.is_none() {
+                                    function = Some(label.clone());
+                                }
+                                if let Some(function) = function.as_ref() {
+                                    *function_sizes.entry(function.clone()).or_insert(0) +=
+                                        content.len();
+                                }
                                 function_labels_to_pc.insert(label, pc);
                                 for code in content.into_iter() {
                                     let CodeElement {
@@ -203,11 +410,32 @@ impl<const N: usize, E: VmEncodingMode<N>> Linker<N, E> {
                                 source_line: _,
                                 content,
                             }) => {
-                                let offset = aligned_constants.len();
                                 assert!(labels.remove(&*label));
-                                constant_labels_to_offset.insert(label, offset);
-                                for constant in content.into_iter() {
-                                    aligned_constants.push(constant);
+                                if content.len() == 1 && !indexed_labels.contains(&label) {
+                                    // single words can be shared between labels, unless some
+                                    // access reads past the word, e.g. `@CPI0_0[1]`, as the next
+                                    // word may belong to some other label then
+                                    let constant = content.into_iter().next().unwrap();
+                                    let (offset, is_reused) = intern_constant(
+                                        &mut aligned_constants,
+                                        &mut interned_cells,
+                                        constant,
+                                    );
+                                    if is_reused {
+                                        deduplicated_constants += 1;
+                                    }
+                                    constant_labels_to_offset.insert(label, offset);
+                                } else {
+                                    // arrays must stay contiguous, but any of their words
+                                    // can be reused by the single word constants that follow
+                                    let offset = aligned_constants.len();
+                                    constant_labels_to_offset.insert(label, offset);
+                                    for (idx, constant) in content.into_iter().enumerate() {
+                                        if let ConstantValue::Cell(value) = &constant {
+                                            interned_cells.entry(*value).or_insert(offset + idx);
+                                        }
+                                        aligned_constants.push(constant);
+                                    }
                                 }
                             }
                         }
@@ -241,13 +469,26 @@ impl<const N: usize, E: VmEncodingMode<N>> Linker<N, E> {
             }
         }
 
+        deduplicated_constants += materialize_immediates::<N, E>(
+            &mut aligned_code,
+            &mut aligned_constants,
+            &mut interned_cells,
+            &mut constant_labels_to_offset,
+        );
+
         // add all default landing pads
 
+        let referenced_labels = referenced_labels(&aligned_code);
         let add_landing_pad = |label: &str,
                                landing_pad_instruction: Instruction,
                                function_labels_to_pc: &mut HashMap<String, usize>,
                                aligned_code: &mut Vec<Instruction>| {
-            if !function_labels_to_pc.contains_key(label) {
+            let is_needed = match self.config.landing_pad_policy {
+                LandingPadPolicy::Default => true,
+                LandingPadPolicy::Referenced => referenced_labels.contains(label),
+                LandingPadPolicy::Omit => false,
+            };
+            if is_needed && !function_labels_to_pc.contains_key(label) {
                 let pc = aligned_code.len();
                 aligned_code.push(landing_pad_instruction);
                 function_labels_to_pc.insert(label.to_owned(), pc);
@@ -346,6 +587,202 @@ impl<const N: usize, E: VmEncodingMode<N>> Linker<N, E> {
 
         assert_eq!(result.len() % 2, 1);
 
-        Ok((result, pc_to_line_mapping, function_labels_to_pc))
+        Ok((
+            result,
+            pc_to_line_mapping,
+            function_labels_to_pc,
+            function_sizes,
+            deduplicated_constants,
+            aligned_globals_values.len(),
+        ))
+    }
+}
+
+///
+/// Moves the entry function with its local `.`-prefixed blocks to the start of the first text
+/// section, so it is placed right after the globals initializer at PC 0.
+///
+/// The moved code must end in an unconditional return, jump or panic, and the code before it
+/// must not fall through into it, as neither would reach the same instruction after the move.
+///
+fn place_entry_first(
+    sections: &mut [ParsedSection],
+    entry: &str,
+) -> Result<(), AssemblyParseError> {
+    let mut function = None;
+    let mut falls_through = false;
+    for section in sections.iter_mut() {
+        let section = match section {
+            ParsedSection::Text(section) => section,
+            _ => continue,
+        };
+        let start = match section.elements.iter().position(|element| {
+            matches!(element, TextSectionElement::Labeled(function) if function.label == entry)
+        }) {
+            Some(start) => start,
+            None => {
+                if let Some(last) = last_instruction(&section.elements) {
+                    falls_through = !last.is_terminator();
+                }
+                continue;
+            }
+        };
+        if let Some(last) = last_instruction(&section.elements[..start]) {
+            falls_through = !last.is_terminator();
+        }
+        let length = 1 + section.elements[start + 1..]
+            .iter()
+            .take_while(|element| {
+                matches!(element, TextSectionElement::Labeled(function) if function.label.starts_with('.'))
+            })
+            .count();
+        function = Some(
+            section
+                .elements
+                .drain(start..start + length)
+                .collect::<Vec<_>>(),
+        );
+        break;
     }
+    let function = function.ok_or_else(|| AssemblyParseError::LabelNotFound(entry.to_owned()))?;
+    if falls_through {
+        return Err(AssemblyParseError::EntryFallenInto(entry.to_owned()));
+    }
+    if !last_instruction(&function).is_some_and(Instruction::is_terminator) {
+        return Err(AssemblyParseError::EntryNotTerminated(entry.to_owned()));
+    }
+
+    let first = sections
+        .iter_mut()
+        .find_map(|section| match section {
+            ParsedSection::Text(section) => Some(section),
+            _ => None,
+        })
+        .expect("the entry is in a text section");
+    first.elements.splice(0..0, function);
+
+    Ok(())
+}
+
+///
+/// The last instruction of the text section elements in the source order.
+///
+fn last_instruction(elements: &[TextSectionElement]) -> Option<&Instruction> {
+    elements
+        .iter()
+        .rev()
+        .find_map(|element| match element {
+            TextSectionElement::Unlabeled(code) => Some(code),
+            TextSectionElement::Labeled(function) => function.content.last(),
+        })
+        .map(|code| &code.instruction)
+}
+
+///
+/// The constant labels accessed with an offset or a register, e.g. `@CPI0_0[1]` or
+/// `@CPI0_0[r1]`, which must keep the words that follow them.
+///
+fn indexed_constant_labels(sections: &[ParsedSection]) -> HashSet<String> {
+    sections
+        .iter()
+        .filter_map(|section| match section {
+            ParsedSection::Text(section) => Some(section),
+            _ => None,
+        })
+        .flat_map(TextSection::code_elements)
+        .flat_map(|element| element.instruction().operands())
+        .filter_map(|operand| match operand.operand {
+            OperandRef::Full(FullOperand::Constant(constant))
+                if constant.immediate != 0 || constant.register != RegisterOperand::Null =>
+            {
+                Some(constant.label.clone())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+///
+/// The labels the instructions refer to in their operands.
+///
+fn referenced_labels(instructions: &[Instruction]) -> HashSet<String> {
+    instructions
+        .iter()
+        .flat_map(|instruction| instruction.operands())
+        .filter_map(|operand| match operand.operand {
+            OperandRef::Full(FullOperand::Constant(constant)) => Some(constant.label.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+///
+/// Loads the `li` values with the immediates where they fit into `E::PcOrImm`, and from the data
+/// section words otherwise, which are shared with the identical constants. Returns the number of
+/// the reused words.
+///
+fn materialize_immediates<const N: usize, E: VmEncodingMode<N>>(
+    aligned_code: &mut [Instruction],
+    aligned_constants: &mut Vec<ConstantValue>,
+    interned_cells: &mut HashMap<[u8; 32], usize>,
+    constant_labels_to_offset: &mut HashMap<String, usize>,
+) -> usize {
+    use zkevm_opcode_defs::decoding::AllowedPcOrImm;
+
+    let mut deduplicated_constants = 0;
+    for instruction in aligned_code.iter_mut() {
+        let add = match instruction {
+            Instruction::Add(add) => add,
+            _ => continue,
+        };
+        let label = match &add.source_1 {
+            FullOperand::Constant(constant) => constant.label.clone(),
+            _ => continue,
+        };
+        let value = match label.strip_prefix(LOAD_IMMEDIATE_LABEL_PREFIX) {
+            Some(digits) => BigUint::parse_bytes(digits.as_bytes(), 16)
+                .expect("the loaded immediates are validated by the parser"),
+            None => continue,
+        };
+
+        match value.to_u64() {
+            Some(immediate) if immediate <= E::PcOrImm::max().as_u64() => {
+                add.source_1 = GenericOperand::immediate(immediate).into();
+            }
+            _ if constant_labels_to_offset.contains_key(&label) => {}
+            _ => {
+                let mut cell = [0u8; 32];
+                let bytes = value.to_bytes_be();
+                cell[32 - bytes.len()..].copy_from_slice(&bytes);
+                let (offset, is_reused) =
+                    intern_constant(aligned_constants, interned_cells, ConstantValue::Cell(cell));
+                if is_reused {
+                    deduplicated_constants += 1;
+                }
+                constant_labels_to_offset.insert(label, offset);
+            }
+        }
+    }
+
+    deduplicated_constants
+}
+
+/// Places a single word constant into the data section, or reuses the offset of an
+/// identical word that was placed before. Returns the offset and whether it was reused.
+fn intern_constant(
+    aligned_constants: &mut Vec<ConstantValue>,
+    interned_cells: &mut HashMap<[u8; 32], usize>,
+    constant: ConstantValue,
+) -> (usize, bool) {
+    if let ConstantValue::Cell(value) = &constant {
+        if let Some(offset) = interned_cells.get(value).copied() {
+            return (offset, true);
+        }
+        interned_cells.insert(*value, aligned_constants.len());
+    }
+
+    let offset = aligned_constants.len();
+    aligned_constants.push(constant);
+
+    (offset, false)
 }
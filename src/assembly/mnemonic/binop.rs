@@ -3,7 +3,20 @@ use super::*;
 // we use binop for unconditional move with two arguments src, dst
 pub(crate) fn parse_mov_combinator(input: &str) -> IResult<&str, String> {
     let (rest, (_, args)) = parse_mnemonic(input, "mov", 2)?;
-    let canonical = format!("binop.xor {}, r0, {}, r0", &args[0], &args[1]);
+    let canonical = format!("binop.xor {}, r0, {}", &args[0], &args[1]);
+
+    Ok((rest, canonical))
+}
+
+// the conditional move is the `mov` under the condition modifiers
+pub(crate) fn parse_cmov_combinator(input: &str) -> IResult<&str, String> {
+    let (rest, (_, modifiers, args)) = parse_mnemonic_allow_modifiers(input, "cmov", 2)?;
+    let canonical = format!(
+        "binop.xor{} {}, r0, {}",
+        format_modifiers_into_canonical(modifiers),
+        &args[0],
+        &args[1]
+    );
 
     Ok((rest, canonical))
 }
@@ -49,14 +62,14 @@ pub(crate) fn parse_and_combinator(input: &str) -> IResult<&str, String> {
 
 pub(crate) fn parse_push_combinator(input: &str) -> IResult<&str, String> {
     let (rest, (_, args)) = parse_mnemonic(input, "push", 1)?;
-    let canonical = format!("binop.xor {}, r0, stack+=[1], r0", &args[0]);
+    let canonical = format!("binop.xor {}, r0, stack+=[1]", &args[0]);
 
     Ok((rest, canonical))
 }
 
 pub(crate) fn parse_pop_combinator(input: &str) -> IResult<&str, String> {
     let (rest, (_, args)) = parse_mnemonic(input, "pop", 1)?;
-    let canonical = format!("binop.xor stack-=[1], r0, {}, r0", &args[0]);
+    let canonical = format!("binop.xor stack-=[1], r0, {}", &args[0]);
 
     Ok((rest, canonical))
 }
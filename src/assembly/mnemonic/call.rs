@@ -22,6 +22,13 @@ pub(crate) fn parse_shorthand_revert(input: &str) -> IResult<&str, String> {
     Ok((rest, canonical))
 }
 
+pub(crate) fn parse_shorthand_throw(input: &str) -> IResult<&str, String> {
+    let (rest, (_, _)) = parse_mnemonic(input, "throw", 0)?;
+    let canonical = "ret.revert r1".to_owned();
+
+    Ok((rest, canonical))
+}
+
 pub(crate) fn parse_shorthand_panic(input: &str) -> IResult<&str, String> {
     let (rest, (_, _)) = parse_mnemonic(input, "panic", 0)?;
     let canonical = "ret.panic r0".to_owned();
@@ -49,12 +56,22 @@ pub(crate) fn parse_invoke_combinator(input: &str) -> IResult<&str, String> {
 
 pub(crate) fn parse_shorthand_exceptionless_near_call(input: &str) -> IResult<&str, String> {
     use crate::assembly::linking::DEFAULT_UNWIND_LABEL;
+    parse_exceptionless_near_call_unwinding_to(input, DEFAULT_UNWIND_LABEL)
+}
+
+///
+/// The `call target` shorthand with the unwind handler set by the `.unwind` directive.
+///
+pub(crate) fn parse_exceptionless_near_call_unwinding_to<'a>(
+    input: &'a str,
+    unwind_label: &str,
+) -> IResult<&'a str, String> {
     let (rest, (_, modifiers, args)) = parse_mnemonic_allow_modifiers(input, "call", 1)?;
     let canonical = format!(
         "near_call{} r0, {}, @{}",
         format_modifiers_into_canonical(modifiers),
         args[0],
-        DEFAULT_UNWIND_LABEL
+        unwind_label
     );
 
     Ok((rest, canonical))
@@ -69,6 +86,36 @@ pub(crate) fn parse_shorthand_delegate_call(input: &str) -> IResult<&str, String
     Ok((rest, canonical))
 }
 
+pub(crate) fn parse_shorthand_far_call(input: &str) -> IResult<&str, String> {
+    parse_far_call_alias(input, "callf", "far_call")
+}
+
+pub(crate) fn parse_shorthand_far_call_delegate(input: &str) -> IResult<&str, String> {
+    parse_far_call_alias(input, "callfd", "far_call.delegate")
+}
+
+pub(crate) fn parse_shorthand_far_call_static(input: &str) -> IResult<&str, String> {
+    parse_far_call_alias(input, "callfs", "far_call.static")
+}
+
+fn parse_far_call_alias<'a>(
+    input: &'a str,
+    tag: &'a str,
+    opcode: &str,
+) -> IResult<&'a str, String> {
+    let (rest, (_, modifiers, args)) = parse_mnemonic_allow_modifiers(input, tag, 3)?;
+    let canonical = format!(
+        "{}{} {}, {}, {}",
+        opcode,
+        format_modifiers_into_canonical(modifiers),
+        args[0],
+        args[1],
+        args[2]
+    );
+
+    Ok((rest, canonical))
+}
+
 pub(crate) fn parse_shorthand_near_call(input: &str) -> IResult<&str, String> {
     let (rest, (_, modifiers, args)) = parse_mnemonic_allow_modifiers(input, "call", 3)?;
     let canonical = format!(
@@ -0,0 +1,61 @@
+use super::*;
+use crate::assembly::instruction::condition::ConditionCase;
+use nom::error::ParseError;
+
+///
+/// The `jXX` shorthand of `jump.XX` for every condition modifier, including the aliases, the
+/// legacy `je` one of `jump.eq`, and the `j` one of the unconditional `jump`.
+///
+pub(crate) fn parse_shorthand_conditional_jump(input: &str) -> IResult<&str, String> {
+    let mut parser = nom::sequence::tuple::<_, _, nom::error::Error<_>, _>((
+        nom::character::complete::space0,
+        nom::bytes::complete::tag("j"),
+        nom::character::complete::alphanumeric0,
+        nom::branch::alt((nom::character::complete::space1, nom::combinator::eof)),
+        nom::combinator::rest,
+    ));
+
+    let (rest, (_, _, condition, _, arguments)) = parser(input)?;
+    let canonical = if condition.is_empty() {
+        format!("jump {}", arguments)
+    } else if condition == "e" {
+        format!("jump.eq {}", arguments)
+    } else if ConditionCase::ALL_MODIFIERS
+        .iter()
+        .any(|(modifier, _)| *modifier == condition)
+    {
+        format!("jump.{} {}", condition, arguments)
+    } else {
+        return Err(nom::Err::Error(nom::error::Error::from_error_kind(
+            input,
+            nom::error::ErrorKind::Tag,
+        )));
+    };
+
+    Ok((rest, canonical))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::assembly::parse::code_element::parse_code_element;
+
+    #[test]
+    fn test_conditional_jump_shorthands() {
+        for (shorthand, canonical) in [
+            ("j @.BB0_1", "jump @.BB0_1"),
+            ("je @.BB0_1", "jump.eq @.BB0_1"),
+            ("jz @.BB0_1", "jump.eq @.BB0_1"),
+            ("jnz @.BB0_1", "jump.ne @.BB0_1"),
+            ("jof r1", "jump.lt r1"),
+            ("jgtlt @.BB0_1", "jump.gtlt @.BB0_1"),
+        ] {
+            assert_eq!(
+                parse_code_element(shorthand).unwrap().to_string(),
+                canonical
+            );
+        }
+        assert!(parse_shorthand_conditional_jump("jump @.BB0_1").is_err());
+        assert!(parse_code_element("jxx @.BB0_1").is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use super::*;
+use crate::assembly::parse::data_element::{parse_cell_value, serialize_biguint};
+use crate::assembly::parse::literal::radix_name;
+use crate::error::InstructionReadError;
+
+///
+/// The prefix of the constant labels the `li` values are loaded from, followed by the value in
+/// hexadecimal. The linker turns them into the immediates or the data section words.
+///
+pub(crate) const LOAD_IMMEDIATE_LABEL_PREFIX: &str = "_INTERNAL_IMM_";
+
+///
+/// The `li value, dst` pseudo-instruction, also written as `load_imm`, which loads the word of
+/// up to 256 bits, written like the `.cell` values, into `dst`.
+///
+/// It is expanded into `add @_INTERNAL_IMM_<value>, r0, dst`, as the encoding mode, which decides
+/// whether the value fits into the immediate, is only known to the linker. The internal label is
+/// seen in the expansion returned by `Mnemonic::expand`, while the parsed instruction is printed
+/// back as `li 0x<value>, dst` until it is linked.
+///
+pub(crate) fn expand_load_immediate(
+    input: &str,
+) -> Option<Result<Vec<String>, InstructionReadError>> {
+    let (_, (_, args)) = parse_mnemonic(input, "li", 2)
+        .or_else(|_| parse_mnemonic(input, "load_imm", 2))
+        .ok()?;
+
+    let literal = args[0].trim();
+    let value = match parse_cell_value(literal) {
+        Ok(value) => value,
+        Err(error) => return Some(Err(error)),
+    };
+    if serialize_biguint(value.clone()).is_none() {
+        return Some(Err(InstructionReadError::LiteralTooLarge {
+            literal: literal.to_owned(),
+            radix: radix_name(literal.trim_start_matches(&['-', '+'][..])),
+            bits: 256,
+        }));
+    }
+
+    Some(Ok(vec![format!(
+        "add @{}{:x}, r0, {}",
+        LOAD_IMMEDIATE_LABEL_PREFIX,
+        value,
+        args[1].trim()
+    )]))
+}
+
+///
+/// The `li` expansion for the registry.
+///
+pub(crate) fn parse_load_immediate_combinator(input: &str) -> IResult<&str, String> {
+    match expand_load_immediate(input) {
+        Some(Ok(instructions)) => Ok(("", instructions.join("\n"))),
+        _ => Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Tag,
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_load_immediate_expansion() {
+        let expand = |input: &str| expand_load_immediate(input).unwrap();
+        assert_eq!(
+            expand("li 0xdead_beef, r5").unwrap(),
+            vec!["add @_INTERNAL_IMM_deadbeef, r0, r5"]
+        );
+        assert_eq!(
+            expand("load_imm 'a', stack[r1]").unwrap(),
+            vec!["add @_INTERNAL_IMM_61, r0, stack[r1]"]
+        );
+        assert_eq!(
+            expand("li -1, r1").unwrap(),
+            vec![format!("add @_INTERNAL_IMM_{}, r0, r1", "f".repeat(64))]
+        );
+        assert!(matches!(
+            expand(&format!("li 0x1{}, r1", "0".repeat(64))),
+            Err(InstructionReadError::LiteralTooLarge { bits: 256, .. })
+        ));
+        assert!(expand_load_immediate("link r1, r2").is_none());
+    }
+}
@@ -2,16 +2,18 @@
 //! The assembly operation mnemonic.
 //!
 
+use crate::assembly::parse::literal::find_unquoted;
 use nom::IResult;
 
-use crate::error::InstructionReadError;
-use std::convert::TryFrom;
-
 mod binop;
 mod call;
 mod context;
+mod jump;
+mod load_immediate;
 mod log;
 mod nop;
+mod registry;
+mod select;
 mod set_flags;
 mod shift;
 mod uma;
@@ -19,152 +21,16 @@ mod uma;
 pub(crate) use self::binop::*;
 pub(crate) use self::call::*;
 pub(crate) use self::context::*;
+pub(crate) use self::jump::*;
+pub(crate) use self::load_immediate::*;
 pub(crate) use self::log::*;
 pub(crate) use self::nop::*;
+pub use self::registry::Mnemonic;
+pub(crate) use self::select::*;
 pub(crate) use self::set_flags::*;
 pub(crate) use self::shift::*;
 pub(crate) use self::uma::*;
 
-///
-/// The assembly operation mnemonic.
-///
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Mnemonic {
-    /// The eponymous opcode keyword.
-    NoOperation,
-    /// The eponymous opcode keyword.
-    Addition,
-    /// The eponymous opcode keyword.
-    Subtraction,
-    /// The eponymous opcode keyword.
-    SubtractionSwapped,
-    /// The eponymous opcode keyword.
-    Multiplication,
-    /// The eponymous opcode keyword.
-    Division,
-    /// The unconditional case of `jump`.
-    JumpUnconditional,
-    /// The `eq` case of `jump`.
-    JumpEquals,
-    /// The `ne` case of `jump`.
-    JumpNotEquals,
-    /// The `ge` case of `jump`.
-    JumpGreaterEquals,
-    /// The `le` case of `jump`.
-    JumpLesserEquals,
-    /// The `gt` case of `jump`.
-    JumpGreater,
-    /// The `lt` case of `jump`.
-    JumpLesser,
-    /// The source_zero case of `jump`.
-    JumpSourceZero,
-    /// The `call` case of the function jump.
-    Call,
-    /// The `external call` case of the function jump.
-    CallExternal,
-    /// The `delegade call` case of the function jump.
-    CallDelegate,
-    /// The `static call` case of the function jump.
-    CallStatic,
-    /// The `account abstraction call` case of the function jump.
-    CallAA,
-    /// The `return` case of the function jump.
-    Return,
-    /// The `return with error` case of the function jump.
-    Throw,
-    // Panic (throw without well defined error)
-    Panic,
-    /// The push keyword
-    Push,
-    /// The pop keyword
-    Pop,
-    /// The `load` case of the contract storage opcode.
-    StorageLoad,
-    /// The `store` case of the contract storage opcode.
-    StorageStore,
-    /// Initialize Event sequence
-    EventInit,
-    /// Event sequence element
-    Event,
-    /// Initialize L1 messages sequence
-    L1MessageInit,
-    /// L1 messages sequence element
-    L1Message,
-    /// Pure non-revertable precompile call
-    CallPrecompilePure,
-    /// Revertable precompile call (marker purposes)
-    CallPrecompileMarker,
-    /// The `ctx` keyword
-    Context,
-    /// The `and` keyword (bitwise)
-    And,
-    /// The `or` keyword (bitwise)
-    Or,
-    /// The `xor` keyword (bitwise)
-    Xor,
-    /// The `shl` keyword (bitwise shift left)
-    Shl,
-    /// The `shr` keyword (bitwise shift right)
-    Shr,
-    /// The `rol` keyword (bitwise cyclical shift left)
-    Rol,
-    /// The `ror` keyword (bitwise cyclical shift right)
-    Ror,
-    /// Unconditional copy
-    Mov,
-    /// Conditinal copy
-    CMov,
-}
-
-impl TryFrom<&str> for Mnemonic {
-    type Error = InstructionReadError;
-
-    fn try_from(input: &str) -> Result<Self, Self::Error> {
-        Ok(match input {
-            "nop" => Self::NoOperation,
-            "add" => Self::Addition,
-            "sub" => Self::Subtraction,
-            "mul" => Self::Multiplication,
-            "div" => Self::Division,
-            "j" => Self::JumpUnconditional,
-            "je" => Self::JumpEquals,
-            "jne" => Self::JumpNotEquals,
-            "jge" => Self::JumpGreaterEquals,
-            "jle" => Self::JumpLesserEquals,
-            "jgt" => Self::JumpGreater,
-            "jlt" => Self::JumpLesser,
-            "jz" => Self::JumpSourceZero,
-            "call" => Self::Call,
-            "callf" => Self::CallExternal,
-            "callfd" => Self::CallDelegate,
-            "callfs" => Self::CallStatic,
-            "callaa" => Self::CallAA,
-            "ret" => Self::Return,
-            "throw" => Self::Throw,
-            "panic" => Self::Panic,
-            "push" => Self::Push,
-            "pop" => Self::Pop,
-            "ld" => Self::StorageLoad,
-            "st" => Self::StorageStore,
-            "evt.i" => Self::EventInit,
-            "evt" => Self::Event,
-            "msg.i" => Self::L1MessageInit,
-            "msg" => Self::L1Message,
-            "ctx" => Self::Context,
-            "and" => Self::And,
-            "or" => Self::Or,
-            "xor" => Self::Xor,
-            "shl" => Self::Shl,
-            "shr" => Self::Shr,
-            "rol" => Self::Rol,
-            "ror" => Self::Ror,
-            "mov" => Self::Mov,
-            "cmov" => Self::CMov,
-            input => return Err(InstructionReadError::UnknownMnemonic(input.to_owned())),
-        })
-    }
-}
-
 pub(crate) fn parse_mnemonic<'a, 'b>(
     input: &'a str,
     tag: &'a str,
@@ -510,11 +376,25 @@ where
     }
 }
 
+///
+/// Takes the operand up to the comma, which is consumed, skipping the commas of the character
+/// literals.
+///
+fn parse_operand_until_comma(input: &str) -> IResult<&str, &str> {
+    match find_unquoted(input, |c| c == ',') {
+        Some(index) if index > 0 => Ok((&input[index + 1..], &input[..index])),
+        _ => Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Many1,
+        ))),
+    }
+}
+
 pub(crate) fn parse_ops_2(input: &str) -> IResult<&str, [&str; 2]> {
     let mut parser = nom::sequence::tuple::<_, _, nom::error::Error<_>, _>((
         // 1
         nom::character::complete::space0,
-        all_until1(nom::bytes::complete::tag(",")),
+        parse_operand_until_comma,
         nom::character::complete::space0, // may be cleanup space
         // 2
         nom::combinator::rest,
@@ -529,10 +409,10 @@ pub(crate) fn parse_ops_3(input: &str) -> IResult<&str, [&str; 3]> {
     let mut parser = nom::sequence::tuple::<_, _, nom::error::Error<_>, _>((
         // 1
         nom::character::complete::space0,
-        all_until1(nom::bytes::complete::tag(",")),
+        parse_operand_until_comma,
         nom::character::complete::space0,
         // 2
-        all_until1(nom::bytes::complete::tag(",")),
+        parse_operand_until_comma,
         nom::character::complete::space0,
         // 3
         nom::combinator::rest,
@@ -547,13 +427,13 @@ pub(crate) fn parse_ops_4(input: &str) -> IResult<&str, [&str; 4]> {
     let mut parser = nom::sequence::tuple::<_, _, nom::error::Error<_>, _>((
         // 1
         nom::character::complete::space0,
-        all_until1(nom::bytes::complete::tag(",")),
+        parse_operand_until_comma,
         nom::character::complete::space0,
         // 2
-        all_until1(nom::bytes::complete::tag(",")),
+        parse_operand_until_comma,
         nom::character::complete::space0,
         // 3
-        all_until1(nom::bytes::complete::tag(",")),
+        parse_operand_until_comma,
         nom::character::complete::space0,
         // 4
         nom::combinator::rest,
@@ -0,0 +1,403 @@
+//!
+//! The registry of the mnemonic shorthands.
+//!
+//! Every shorthand is rewritten into its canonical instruction before the instruction is parsed,
+//! so the table below is the single place which defines what the shorthands mean. The entries are
+//! tried in order and the first matching one wins, so the mnemonics sharing a prefix with some
+//! other one, e.g. `callf` and `call`, must come first.
+//!
+
+use super::*;
+
+///
+/// The rewrite of the shorthand line into the canonical instruction.
+///
+pub(crate) type MnemonicTransformer = fn(&str) -> IResult<&str, String>;
+
+///
+/// The shorthand mnemonic, which is expanded into the canonical instruction.
+///
+#[derive(Debug, Clone, Copy)]
+pub struct Mnemonic {
+    /// The mnemonic keyword, including the fixed modifiers, e.g. `ld.1`.
+    pub name: &'static str,
+    /// The shorthand syntax.
+    pub syntax: &'static str,
+    /// The canonical instruction the shorthand is expanded into.
+    pub expansion: &'static str,
+    /// Whether the shorthand is a pseudo-instruction, which is expanded into several canonical
+    /// instructions or into the ones with the internal labels, and is not handled by `expand_any`.
+    pub is_pseudo_instruction: bool,
+    /// The rewrite of the shorthand line.
+    transformer: MnemonicTransformer,
+}
+
+impl Mnemonic {
+    const fn new(
+        name: &'static str,
+        syntax: &'static str,
+        expansion: &'static str,
+        transformer: MnemonicTransformer,
+    ) -> Self {
+        Self {
+            name,
+            syntax,
+            expansion,
+            is_pseudo_instruction: false,
+            transformer,
+        }
+    }
+
+    const fn pseudo_instruction(
+        name: &'static str,
+        syntax: &'static str,
+        expansion: &'static str,
+        transformer: MnemonicTransformer,
+    ) -> Self {
+        Self {
+            is_pseudo_instruction: true,
+            ..Self::new(name, syntax, expansion, transformer)
+        }
+    }
+
+    ///
+    /// All the shorthands in the order they are tried.
+    ///
+    pub fn all() -> &'static [Self] {
+        &ALL_MNEMONICS
+    }
+
+    ///
+    /// The distinct shorthand keywords, e.g. for the completions.
+    ///
+    pub fn names() -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = vec![];
+        for mnemonic in ALL_MNEMONICS.iter() {
+            if !names.contains(&mnemonic.name) {
+                names.push(mnemonic.name);
+            }
+        }
+        names
+    }
+
+    ///
+    /// Expands the instruction line if it is this shorthand.
+    ///
+    /// The pseudo-instructions are expanded into the canonical instructions joined with newlines,
+    /// which may reference the internal labels, e.g. `add @_INTERNAL_IMM_2a, r0, r1` for `li`.
+    ///
+    pub fn expand(&self, input: &str) -> Option<String> {
+        (self.transformer)(input)
+            .ok()
+            .map(|(_, canonical)| canonical)
+    }
+
+    ///
+    /// Expands the instruction line with the first matching shorthand into the single canonical
+    /// instruction. The pseudo-instructions are skipped.
+    ///
+    pub fn expand_any(input: &str) -> Option<String> {
+        ALL_MNEMONICS
+            .iter()
+            .filter(|mnemonic| !mnemonic.is_pseudo_instruction)
+            .find_map(|mnemonic| mnemonic.expand(input))
+    }
+}
+
+///
+/// The shorthands, in the order they are tried.
+///
+static ALL_MNEMONICS: [Mnemonic; 46] = [
+    Mnemonic::new("nop", "nop", "nop r0, r0, r0, r0", parse_nop_combinator),
+    Mnemonic::new(
+        "mov",
+        "mov src, dst",
+        "binop.xor src, r0, dst",
+        parse_mov_combinator,
+    ),
+    Mnemonic::new(
+        "cmov",
+        "cmov.<cond> src, dst",
+        "binop.xor.<cond> src, r0, dst",
+        parse_cmov_combinator,
+    ),
+    Mnemonic::pseudo_instruction(
+        "select",
+        "select.<cond> a, b, dst",
+        "add b, r0, dst\nadd.<cond> a, r0, dst",
+        parse_select_combinator,
+    ),
+    Mnemonic::pseudo_instruction(
+        "li",
+        "li value, dst",
+        "add value, r0, dst",
+        parse_load_immediate_combinator,
+    ),
+    Mnemonic::pseudo_instruction(
+        "load_imm",
+        "load_imm value, dst",
+        "add value, r0, dst",
+        parse_load_immediate_combinator,
+    ),
+    Mnemonic::new(
+        "xor",
+        "xor src0, src1, dst",
+        "binop.xor src0, src1, dst",
+        parse_xor_combinator,
+    ),
+    Mnemonic::new(
+        "and",
+        "and src0, src1, dst",
+        "binop.and src0, src1, dst",
+        parse_and_combinator,
+    ),
+    Mnemonic::new(
+        "or",
+        "or src0, src1, dst",
+        "binop.or src0, src1, dst",
+        parse_or_combinator,
+    ),
+    Mnemonic::new(
+        "shl",
+        "shl src0, src1, dst",
+        "shift.shl src0, src1, dst",
+        parse_shl_combinator,
+    ),
+    Mnemonic::new(
+        "shr",
+        "shr src0, src1, dst",
+        "shift.shr src0, src1, dst",
+        parse_shr_combinator,
+    ),
+    Mnemonic::new(
+        "rol",
+        "rol src0, src1, dst",
+        "shift.rol src0, src1, dst",
+        parse_rol_combinator,
+    ),
+    Mnemonic::new(
+        "ror",
+        "ror src0, src1, dst",
+        "shift.ror src0, src1, dst",
+        parse_ror_combinator,
+    ),
+    Mnemonic::new(
+        "j",
+        "j<cond> target",
+        "jump.<cond> target",
+        parse_shorthand_conditional_jump,
+    ),
+    Mnemonic::new("ret", "ret", "ret.ok r1", parse_shorthand_ret),
+    Mnemonic::new("revert", "revert", "ret.revert r1", parse_shorthand_revert),
+    Mnemonic::new("throw", "throw", "ret.revert r1", parse_shorthand_throw),
+    Mnemonic::new("panic", "panic", "ret.panic r0", parse_shorthand_panic),
+    Mnemonic::new(
+        "invoke",
+        "invoke operands",
+        "near_call operands",
+        parse_invoke_combinator,
+    ),
+    Mnemonic::new(
+        "push",
+        "push src",
+        "binop.xor src, r0, stack+=[1]",
+        parse_push_combinator,
+    ),
+    Mnemonic::new(
+        "pop",
+        "pop dst",
+        "binop.xor stack-=[1], r0, dst",
+        parse_pop_combinator,
+    ),
+    Mnemonic::new(
+        "sread",
+        "sread key, dst",
+        "log.sread key, r0, dst",
+        parse_sread_combinator,
+    ),
+    Mnemonic::new(
+        "sload",
+        "sload key, dst",
+        "log.sread key, r0, dst",
+        parse_sload_combinator,
+    ),
+    Mnemonic::new(
+        "sstore",
+        "sstore key, value",
+        "log.swrite key, value, r0",
+        parse_sstore_combinator,
+    ),
+    Mnemonic::new(
+        "event",
+        "event key, value",
+        "log.event key, value, r0",
+        parse_event_combinator,
+    ),
+    Mnemonic::new(
+        "to_l1",
+        "to_l1 key, value",
+        "log.to_l1 key, value, r0",
+        parse_to_l1_combinator,
+    ),
+    Mnemonic::new(
+        "context.gas_left",
+        "context.gas_left dst",
+        "context.ergs_left dst",
+        parse_gas_left_combinator,
+    ),
+    Mnemonic::new(
+        "context.set_gas_per_pubdata",
+        "context.set_gas_per_pubdata src",
+        "context.set_ergs_per_pubdata src",
+        parse_set_gas_per_pubdatagas_left_combinator,
+    ),
+    Mnemonic::new(
+        "precompile",
+        "precompile src0, src1, dst",
+        "log.precompile src0, src1, dst",
+        parse_precompile_combinator,
+    ),
+    Mnemonic::new(
+        "nop",
+        "nop stack+=[offset]",
+        "nop r0, r0, stack+=[offset], r0",
+        parse_increase_sp_shorthard,
+    ),
+    Mnemonic::new(
+        "nop",
+        "nop stack-=[offset]",
+        "nop stack-=[offset], r0, r0, r0",
+        parse_decrease_sp_shorthard,
+    ),
+    Mnemonic::new(
+        "callfd",
+        "callfd address, abi, handler",
+        "far_call.delegate address, abi, handler",
+        parse_shorthand_far_call_delegate,
+    ),
+    Mnemonic::new(
+        "callfs",
+        "callfs address, abi, handler",
+        "far_call.static address, abi, handler",
+        parse_shorthand_far_call_static,
+    ),
+    Mnemonic::new(
+        "callf",
+        "callf address, abi, handler",
+        "far_call address, abi, handler",
+        parse_shorthand_far_call,
+    ),
+    Mnemonic::new(
+        "call",
+        "call abi, target, handler",
+        "near_call abi, target, handler",
+        parse_shorthand_near_call,
+    ),
+    Mnemonic::new(
+        "call",
+        "call target",
+        "near_call r0, target, @DEFAULT_UNWIND",
+        parse_shorthand_exceptionless_near_call,
+    ),
+    Mnemonic::new(
+        "ld.1",
+        "ld.1 pointer, dst",
+        "uma.heap_read pointer, r0, dst, r0",
+        parse_uma_heap_read_combinator,
+    ),
+    Mnemonic::new(
+        "ld.2",
+        "ld.2 pointer, dst",
+        "uma.aux_heap_read pointer, r0, dst, r0",
+        parse_uma_aux_heap_read_combinator,
+    ),
+    Mnemonic::new(
+        "st.1",
+        "st.1 pointer, value",
+        "uma.heap_write pointer, value, r0, r0",
+        parse_uma_heap_write_combinator,
+    ),
+    Mnemonic::new(
+        "st.2",
+        "st.2 pointer, value",
+        "uma.aux_heap_write pointer, value, r0, r0",
+        parse_uma_aux_heap_write_combinator,
+    ),
+    Mnemonic::new(
+        "ld",
+        "ld pointer, dst",
+        "uma.fat_ptr_read pointer, r0, dst, r0",
+        parse_uma_fat_ptr_read_combinator,
+    ),
+    Mnemonic::new(
+        "ld.1.inc",
+        "ld.1.inc pointer, dst, next",
+        "uma.heap_read.inc pointer, r0, dst, next",
+        parse_uma_heap_read_increment_combinator,
+    ),
+    Mnemonic::new(
+        "ld.2.inc",
+        "ld.2.inc pointer, dst, next",
+        "uma.aux_heap_read.inc pointer, r0, dst, next",
+        parse_uma_aux_heap_read_increment_combinator,
+    ),
+    Mnemonic::new(
+        "st.1.inc",
+        "st.1.inc pointer, value, next",
+        "uma.heap_write.inc pointer, value, next, r0",
+        parse_uma_heap_write_increment_combinator,
+    ),
+    Mnemonic::new(
+        "st.2.inc",
+        "st.2.inc pointer, value, next",
+        "uma.aux_heap_write.inc pointer, value, next, r0",
+        parse_uma_aux_heap_write_increment_combinator,
+    ),
+    Mnemonic::new(
+        "ld.inc",
+        "ld.inc pointer, dst, next",
+        "uma.fat_ptr_read.inc pointer, r0, dst, next",
+        parse_uma_fat_ptr_read_increment_combinator,
+    ),
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::assembly::parse::code_element::parse_code_element;
+
+    #[test]
+    fn test_registry_aliases() {
+        for (shorthand, canonical) in [
+            ("mov r2, r3", "binop.xor r2, r0, r3"),
+            ("cmov.lt r2, r3", "binop.xor.lt r2, r0, r3"),
+            ("throw", "ret.revert r1"),
+            ("callf r1, r2, @handler", "far_call r1, r2, @handler"),
+            (
+                "callfd r1, r2, @handler",
+                "far_call.delegate r1, r2, @handler",
+            ),
+            (
+                "callfs r1, r2, @handler",
+                "far_call.static r1, r2, @handler",
+            ),
+            ("call r1, @f, @handler", "near_call r1, @f, @handler"),
+        ] {
+            assert_eq!(Mnemonic::expand_any(shorthand).unwrap(), canonical);
+            assert!(parse_code_element(shorthand).is_ok(), "{}", shorthand);
+        }
+        assert_eq!(Mnemonic::expand_any("add r1, r2, r3"), None);
+        assert_eq!(Mnemonic::expand_any("select.eq r1, r2, r3"), None);
+        assert_eq!(Mnemonic::expand_any("li 42, r1"), None);
+        assert!(Mnemonic::all()
+            .iter()
+            .filter(|mnemonic| mnemonic.is_pseudo_instruction)
+            .all(|mnemonic| ["select", "li", "load_imm"].contains(&mnemonic.name)));
+
+        let names = Mnemonic::names();
+        assert_eq!(names.iter().filter(|name| **name == "nop").count(), 1);
+        assert!(["cmov", "callf", "callfd", "callfs", "throw"]
+            .iter()
+            .all(|name| names.contains(name)));
+    }
+}
@@ -0,0 +1,185 @@
+use super::*;
+use crate::assembly::instruction::condition::ConditionCase;
+use crate::assembly::instruction::registers::RegisterSet;
+use crate::assembly::instruction::utils::pick_condition;
+use crate::assembly::operand::{FullOperand, GenericOperand, RegisterOperand};
+use crate::assembly::parse::addressing::parse_full_operand;
+use crate::error::InstructionReadError;
+use std::collections::HashSet;
+use zkevm_opcode_defs::{Condition, ImmMemHandlerFlags};
+
+///
+/// The `select.<cond> a, b, dst` pseudo-instruction, which writes `a` to `dst` if the condition
+/// holds and `b` otherwise, expanded into the copies with `add`, which leave the flags intact.
+///
+/// The unconditional copy of `b` is followed by the conditional copy of `a`, which is only done
+/// if `dst` is a register that `a` does not read, as `a` must not see the first copy. The first
+/// copy is omitted if `dst` is `b`, and if `dst` is `a`, `b` is copied under the inverse
+/// condition instead, which only `eq` and `ne` have, as the other flags may be set together. Any
+/// other destination is rejected. None of the operands may push or pop, as the copies are
+/// conditional.
+///
+pub(crate) fn expand_select(input: &str) -> Option<Result<Vec<String>, InstructionReadError>> {
+    let (_, (_, modifiers, args)) = parse_mnemonic_allow_modifiers(input, "select", 3).ok()?;
+
+    Some(select_expansion(modifiers, args))
+}
+
+///
+/// The `select` expansion for the registry, with the instructions on separate lines.
+///
+pub(crate) fn parse_select_combinator(input: &str) -> IResult<&str, String> {
+    match expand_select(input) {
+        Some(Ok(instructions)) => Ok(("", instructions.join("\n"))),
+        _ => Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Tag,
+        ))),
+    }
+}
+
+fn select_expansion(
+    modifiers: Vec<&str>,
+    args: Vec<&str>,
+) -> Result<Vec<String>, InstructionReadError> {
+    let mut modifiers: HashSet<&str> = modifiers.into_iter().collect();
+    let condition = pick_condition(&mut modifiers)?;
+    if !modifiers.is_empty() {
+        return Err(InstructionReadError::UnknownArgument(format!(
+            "Select pseudo-instruction contains unknown modifiers: {:?}",
+            modifiers
+        )));
+    }
+
+    let (a, b, dst) = (args[0].trim(), args[1].trim(), args[2].trim());
+    let parse = |operand: &str| {
+        parse_full_operand(operand)
+            .map(|(_, parsed)| parsed)
+            .map_err(|_| {
+                InstructionReadError::UnknownArgument(format!(
+                    "select operand `{}` is invalid",
+                    operand
+                ))
+            })
+    };
+    let (a_operand, b_operand, dst_operand) = (parse(a)?, parse(b)?, parse(dst)?);
+    // the copies are executed twice or under the condition, so they cannot move the stack pointer
+    if let Some(operand) = [(a, &a_operand), (b, &b_operand), (dst, &dst_operand)]
+        .into_iter()
+        .find_map(|(operand, parsed)| match parsed {
+            FullOperand::Full(GenericOperand {
+                r#type: ImmMemHandlerFlags::UseStackWithPushPop,
+                ..
+            }) => Some(operand),
+            _ => None,
+        })
+    {
+        return Err(InstructionReadError::UnknownArgument(format!(
+            "select operand `{}` would adjust the stack pointer conditionally or twice",
+            operand
+        )));
+    }
+    let copy = |condition: ConditionCase, source: &str| {
+        let modifier = condition
+            .canonical_modifier()
+            .map(|modifier| format!(".{}", modifier))
+            .unwrap_or_default();
+        format!("add{} {}, r0, {}", modifier, source, dst)
+    };
+
+    if dst_operand == b_operand {
+        return Ok(vec![copy(condition, a)]);
+    }
+    if dst_operand == a_operand {
+        let inverse = match condition.condition() {
+            Condition::Eq => Condition::Ne,
+            Condition::Ne => Condition::Eq,
+            _ => {
+                return Err(InstructionReadError::UnknownArgument(format!(
+                    "select destination `{}` is the first source and the condition has no inverse",
+                    dst
+                )))
+            }
+        };
+        return Ok(vec![copy(inverse.into(), b)]);
+    }
+    // the second copy must read `a` as it was before the first copy wrote the destination
+    if let FullOperand::Register(register @ RegisterOperand::Register(_))
+    | FullOperand::Full(GenericOperand {
+        r#type: ImmMemHandlerFlags::UseRegOnly,
+        register: register @ RegisterOperand::Register(_),
+        ..
+    }) = dst_operand
+    {
+        let mut reads = RegisterSet::new();
+        a_operand.source_registers(&mut reads);
+        if !reads.contains(register) {
+            return Ok(vec![copy(ConditionCase::default(), b), copy(condition, a)]);
+        }
+    }
+
+    Err(InstructionReadError::UnknownArgument(format!(
+        "select destination `{}` must be a register that `{}` does not read, or one of the sources",
+        dst, a
+    )))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::assembly::Assembly;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_select_expansion() {
+        let expand = |input: &str| expand_select(input).unwrap();
+        assert_eq!(
+            expand("select.lt r1, stack[r2], r3").unwrap(),
+            vec!["add stack[r2], r0, r3", "add.lt r1, r0, r3"]
+        );
+        assert_eq!(
+            expand("select.of r1, r3, r3").unwrap(),
+            vec!["add.lt r1, r0, r3"]
+        );
+        assert_eq!(
+            expand("select.z r3, 5, r3").unwrap(),
+            vec!["add.ne 5, r0, r3"]
+        );
+        assert!(expand("select.gt stack[r3], r2, r3").is_err());
+        assert!(expand("select.eq stack[r3], r2, r3").is_err());
+        assert!(expand("select.gt stack[r1], r2, stack[r1]").is_err());
+        assert!(expand("select.gt r1, r2, stack[r1]").is_err());
+        assert_eq!(
+            expand("select.eq stack[r1], r2, stack[r1]").unwrap(),
+            vec!["add.ne r2, r0, stack[r1]"]
+        );
+        assert_eq!(
+            expand("select.gt r1, stack[r2], stack[r2]").unwrap(),
+            vec!["add.gt r1, r0, stack[r2]"]
+        );
+        assert!(expand("select.lt r1, r2, stack+=[1]").is_err());
+        assert!(expand("select.lt stack-=[1], r2, r3").is_err());
+        assert!(expand("select.lt r1, stack-=[1], r3").is_err());
+        assert!(expand("select.lt.set_flags r1, r2, r3").is_err());
+        assert!(expand_select("add.lt r1, r2, r3").is_none());
+    }
+
+    #[test]
+    fn test_select_source_lines() {
+        let code = r#"
+    .text
+__entry:
+    sub! r1, r2, r0
+    select.gt r1, r2, r3
+    ret
+"#;
+        let assembly = Assembly::try_from(code.to_owned()).unwrap();
+        let elements: Vec<_> = assembly
+            .code_elements()
+            .map(|element| (element.source_line(), element.instruction().to_string()))
+            .collect();
+        assert_eq!(elements[1], (3, "add r2, r0, r3".to_owned()));
+        assert_eq!(elements[2], (3, "add.gt r1, r0, r3".to_owned()));
+        assert_eq!(elements[3].0, 4);
+    }
+}
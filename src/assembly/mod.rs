@@ -2,24 +2,39 @@
 //! The assembly entity.
 //!
 
-use std::convert::TryInto;
-
 // pub mod bytecode;
 // pub mod data_operation;
+pub mod analysis;
+pub mod batch;
+pub mod builder;
+pub mod config;
 pub mod constants;
+pub mod debugger;
 pub mod instruction;
+pub mod language;
 pub mod linking;
 pub mod mnemonic;
 pub mod operand;
 pub mod parse;
 pub mod section;
-
+#[cfg(feature = "serde")]
+pub(crate) mod serialization;
+pub mod simulator;
+
+use self::analysis::cost::CostReport;
+use self::analysis::liveness::LivenessReport;
+use self::analysis::stack::StackReport;
+use self::config::{AssemblerConfig, LandingPadPolicy, NullDestinationPolicy};
 use self::instruction::Instruction;
-use self::section::ParsedSection;
-use crate::assembly::linking::AlignedRawBytecode;
+use self::parse::literal::strip_comment;
+use self::section::{
+    CodeElement, DataSection, GlobalsSection, LabeledConstant, LabeledFunction, LabeledGlobal,
+    ParsedSection, TextSection,
+};
+use crate::assembly::linking::{AlignedRawBytecode, LinkedAssembly};
 use crate::assembly::mnemonic::all_until1;
-use crate::error::{AssemblyParseError, Error};
-use crate::{get_encoding_mode, InstructionReadError, RunningVmEncodingMode};
+use crate::error::{AssemblyParseError, Error, SectionReadError};
+use crate::{InstructionReadError, RunningVmEncodingMode};
 use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::fs;
@@ -29,7 +44,6 @@ use std::path::PathBuf;
 use zkevm_opcode_defs::{Condition, DecodedOpcode};
 
 use nom::{IResult, Parser};
-use sha3::Digest;
 
 use self::operand::FullOperand;
 
@@ -148,27 +162,65 @@ pub(crate) fn try_parse_opcode_and_modifiers(
     Ok((operands_body, (opcode, mods)))
 }
 
-use crate::assembly::section::LabeledGlobal;
 use zkevm_opcode_defs::decoding::encoding_mode_production::EncodingModeProduction;
 use zkevm_opcode_defs::decoding::{EncodingModeTesting, VmEncodingMode};
 
 ///
 /// The assembly entity.
 ///
+/// # Serialization
+///
+/// With the `serde` feature, the assembly is serialized with the stable JSON shape, which
+/// follows the canonical assembly syntax:
+///
+/// - The instructions are externally tagged with the canonical opcode names, for example
+///   `{"add": {...}}`, `{"binop": {...}}` or `{"near_call": {...}}`, and their fields keep the
+///   names of the struct fields.
+/// - The registers are the strings `"r0"` to `"r15"`, where `"r0"` is the null register.
+/// - The conditions are `"always"` or the condition modifier, such as `"eq"` or `"gtlt"`.
+/// - The opcode variants are their canonical modifiers, such as `"shl"`, `"heap_read"` or
+///   `"to_l1"`. The far call variants are `"normal"`, `"delegate"` and `"mimic"`.
+/// - The operand addressing modes are `"reg"`, `"imm"`, `"stack_push_pop"`,
+///   `"stack_relative"`, `"stack_absolute"` and `"code"`.
+/// - The constants are `{"cell": "0x..."}` with the 32-byte big-endian word,
+///   `{"signed": {"value": "-1", "bits": 8}}` and `{"unsigned": {...}}` with the decimal
+///   value, and `{"byte_array": "0x..."}`.
+/// - The hashes are the `0x`-prefixed hex strings, and the ISA version is its number.
+///
+/// The outputs of the compilation, that is `bytecode`, `pc_line_mapping`, `function_labels`
+/// and `deduplicated_constants`, are not serialized, since `compile_to_bytecode` recomputes
+/// them. They are only written by `compile_to_bytecode` for the encoding mode of `config`, so
+/// `link` should be used for any other mode.
 ///
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Assembly {
     /// The contract metadata hash.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::assembly::serialization::optional_hash")
+    )]
     pub metadata_hash: Option<[u8; 32]>,
     /// The instructions vector.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub bytecode: Vec<AlignedRawBytecode>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub pc_line_mapping: HashMap<usize, usize>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub function_labels: HashMap<String, usize>,
+    /// The number of data section words saved by sharing identical constants.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub deduplicated_constants: usize,
+    /// The configuration the assembly is parsed and compiled with.
+    pub config: AssemblerConfig,
 
     pub assembly_code: String,
     pub(crate) global_variables: HashMap<String, LabeledGlobal>,
     pub(crate) parsed_sections: Vec<ParsedSection>,
     pub(crate) labels: HashSet<String>,
+    /// The function selected by the `.entry` directive, which is placed first.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) entry: Option<String>,
 }
 
 impl Assembly {
@@ -177,133 +229,244 @@ impl Assembly {
     /// The labels hashmap default capacity.
     pub const LABELS_DEFAULT_CAPACITY: usize = 64;
 
+    ///
+    /// The parsed sections in the source order.
+    ///
+    pub fn sections(&self) -> &[ParsedSection] {
+        self.parsed_sections.as_slice()
+    }
+
+    ///
+    /// The labeled functions of all the text sections in the source order, which is not changed
+    /// by the `.entry` directive.
+    ///
+    pub fn functions(&self) -> impl Iterator<Item = &LabeledFunction> {
+        self.text_sections().flat_map(TextSection::functions)
+    }
+
+    ///
+    /// The instructions of all the text sections in the source order.
+    ///
+    pub fn code_elements(&self) -> impl Iterator<Item = &CodeElement> {
+        self.text_sections().flat_map(TextSection::code_elements)
+    }
+
+    ///
+    /// The labeled constants of all the data sections in the source order, before they are
+    /// deduplicated by the linker.
+    ///
+    pub fn constants(&self) -> impl Iterator<Item = &LabeledConstant> {
+        self.parsed_sections
+            .iter()
+            .filter_map(|section| match section {
+                ParsedSection::Data(section) => Some(section),
+                _ => None,
+            })
+            .flat_map(DataSection::constants)
+    }
+
+    ///
+    /// The global variables of all the `.data` sections in the source order, which is the
+    /// order of their stack slots.
+    ///
+    pub fn globals(&self) -> impl Iterator<Item = &LabeledGlobal> {
+        self.parsed_sections
+            .iter()
+            .filter_map(|section| match section {
+                ParsedSection::Globals(section) => Some(section),
+                _ => None,
+            })
+            .flat_map(GlobalsSection::globals)
+    }
+
+    ///
+    /// The labels of the functions, constants and global variables.
+    ///
+    pub fn labels(&self) -> &HashSet<String> {
+        &self.labels
+    }
+
+    ///
+    /// The function the execution starts at, if selected with the `.entry` directive.
+    ///
+    pub fn entry(&self) -> Option<&str> {
+        self.entry.as_deref()
+    }
+
+    fn text_sections(&self) -> impl Iterator<Item = &TextSection> {
+        self.parsed_sections
+            .iter()
+            .filter_map(|section| match section {
+                ParsedSection::Text(section) => Some(section),
+                _ => None,
+            })
+    }
+
+    ///
+    /// Compiles the assembly for the encoding mode of the configuration, and keeps the linked
+    /// `bytecode`, `pc_line_mapping`, `function_labels` and `deduplicated_constants` of that mode.
+    ///
     pub fn compile_to_bytecode(&mut self) -> Result<Vec<[u8; 32]>, InstructionReadError> {
-        match get_encoding_mode() {
+        match self.config.encoding_mode {
             RunningVmEncodingMode::Production => {
-                self.compile_to_bytecode_for_mode::<8, EncodingModeProduction>()
+                self.compile_to_bytecode_for_config::<8, EncodingModeProduction>()
             }
             RunningVmEncodingMode::Testing => {
-                self.compile_to_bytecode_for_mode::<16, EncodingModeTesting>()
+                self.compile_to_bytecode_for_config::<16, EncodingModeTesting>()
             }
         }
     }
 
+    ///
+    /// Compiles the assembly for the encoding mode `E`, without keeping any of the linked
+    /// outputs, which would not match the encoding mode of the configuration.
+    ///
     pub fn compile_to_bytecode_for_mode<const N: usize, E: VmEncodingMode<N>>(
-        &mut self,
+        &self,
     ) -> Result<Vec<[u8; 32]>, InstructionReadError> {
-        use crate::assembly::linking::Linker;
-        let linker = Linker::<N, E>::new();
-
-        if self.bytecode.is_empty() {
-            let (unpacked_bytecode, pc_line_mapping, function_labels) = linker
-                .link(
-                    self.parsed_sections.clone(),
-                    self.labels.clone(),
-                    self.metadata_hash,
-                )
-                .map_err(InstructionReadError::AssemblyParseError)?;
-
-            self.bytecode = unpacked_bytecode;
-            self.pc_line_mapping = pc_line_mapping;
-            self.function_labels = function_labels;
-        }
-
-        let mut bytecode = Vec::with_capacity(self.bytecode.len());
-        let opcodes_per_word = 32 / N;
-        assert!(32 % N == 0, "unaligned bytecode packing");
-
-        let mut num_instructions = 0u64;
-
-        for el in self.bytecode.iter().cloned() {
-            match el {
-                AlignedRawBytecode::Instructions(instructions) => {
-                    assert_eq!(opcodes_per_word, instructions.len());
-                    let mut result = [0u8; 32];
-                    for (i, instr) in instructions.into_iter().enumerate() {
-                        let t: DecodedOpcode<N, E> = instr.try_into()?;
-                        let serialized_bytecode = t.serialize_as_bytes();
-                        result[N * i..N * (i + 1)].copy_from_slice(&serialized_bytecode);
-                    }
-                    bytecode.push(result);
-                    num_instructions += opcodes_per_word as u64;
-                }
-                AlignedRawBytecode::Data(data) => {
-                    let serialized = data.serialize();
-                    bytecode.push(serialized);
-                }
-            }
-        }
+        Ok(self.link::<N, E>()?.into_bytecode())
+    }
 
-        use zkevm_opcode_defs::decoding::AllowedPcOrImm;
+    fn compile_to_bytecode_for_config<const N: usize, E: VmEncodingMode<N>>(
+        &mut self,
+    ) -> Result<Vec<[u8; 32]>, InstructionReadError> {
+        let linked = self.link::<N, E>()?;
 
-        if num_instructions > E::PcOrImm::max().as_u64() {
-            return Err(InstructionReadError::TooManyOpcodes(
-                E::PcOrImm::max().as_u64(),
-                num_instructions,
-            ));
-        }
+        self.bytecode = linked.raw_bytecode.clone();
+        self.pc_line_mapping = linked.pc_line_mapping.clone();
+        self.function_labels = linked.function_labels.clone();
+        self.deduplicated_constants = linked.deduplicated_constants;
 
-        if bytecode.len() as u64 > E::PcOrImm::max().as_u64() {
-            return Err(InstructionReadError::CodeIsTooLong(
-                E::PcOrImm::max().as_u64(),
-                num_instructions,
-            ));
-        }
+        Ok(linked.into_bytecode())
+    }
 
-        Ok(bytecode)
+    ///
+    /// Links the assembly for the encoding mode `E`. The result holds everything
+    /// that depends on the mode, so it should be reused instead of linking again.
+    ///
+    pub fn link<const N: usize, E: VmEncodingMode<N>>(
+        &self,
+    ) -> Result<LinkedAssembly<N, E>, InstructionReadError> {
+        use crate::assembly::linking::Linker;
+        let linker = Linker::<N, E>::with_config(self.config);
+
+        let (
+            raw_bytecode,
+            pc_line_mapping,
+            function_labels,
+            function_sizes,
+            deduplicated_constants,
+            globals_size,
+        ) = linker
+            .link(
+                self.parsed_sections.clone(),
+                self.labels.clone(),
+                self.entry.as_deref(),
+                self.metadata_hash,
+            )
+            .map_err(InstructionReadError::AssemblyParseError)?;
+
+        LinkedAssembly::new(
+            raw_bytecode,
+            pc_line_mapping,
+            function_labels,
+            function_sizes,
+            deduplicated_constants,
+            globals_size,
+            self.config.isa_version,
+        )
     }
 
+    ///
+    /// Links the assembly only to return the instructions. Every call links and encodes again,
+    /// so the result of `link` should be kept instead.
+    ///
+    #[deprecated(note = "use link()")]
     pub fn instructions<const N: usize, E: VmEncodingMode<N>>(
         &self,
     ) -> Result<Vec<Instruction>, InstructionReadError> {
-        // dirty hack
-        let mut tmp = self.clone();
-        let _ = tmp.compile_to_bytecode_for_mode::<N, E>()?;
-        let mut result = Vec::with_capacity(tmp.bytecode.len() * 4);
-        for el in tmp.bytecode.iter() {
-            match el {
-                AlignedRawBytecode::Instructions(instructions) => {
-                    result.extend_from_slice(&instructions[..]);
-                }
-                AlignedRawBytecode::Data(_) => {}
-            }
-        }
-
-        Ok(result)
+        Ok(self.link::<N, E>()?.into_instructions())
     }
 
+    ///
+    /// Links the assembly only to return the opcodes. Every call links and encodes again,
+    /// so the result of `link` should be kept instead.
+    ///
+    #[deprecated(note = "use link()")]
     pub fn opcodes<const N: usize, E: VmEncodingMode<N>>(
         &self,
     ) -> Result<Vec<DecodedOpcode<N, E>>, InstructionReadError> {
-        // dirty hack
-        let mut tmp = self.clone();
-        let _ = tmp.compile_to_bytecode_for_mode::<N, E>()?;
-        let mut result = Vec::with_capacity(tmp.bytecode.len() * 4);
-        for el in tmp.bytecode.iter().cloned() {
-            match el {
-                AlignedRawBytecode::Instructions(instructions) => {
-                    for (_i, instr) in instructions.into_iter().enumerate() {
-                        let t: DecodedOpcode<N, E> = instr.try_into().unwrap();
-                        result.push(t);
-                    }
-                }
-                AlignedRawBytecode::Data(_) => {}
-            }
-        }
+        Ok(self.link::<N, E>()?.into_opcodes())
+    }
 
-        Ok(result)
+    ///
+    /// Estimates the ergs cost of the instructions, basic blocks and functions.
+    ///
+    pub fn cost_report<const N: usize, E: VmEncodingMode<N>>(
+        &self,
+    ) -> Result<CostReport, InstructionReadError> {
+        Ok(CostReport::new(&self.link::<N, E>()?))
+    }
+
+    ///
+    /// Verifies the stack depth of every function.
+    ///
+    pub fn stack_report<const N: usize, E: VmEncodingMode<N>>(
+        &self,
+    ) -> Result<StackReport, InstructionReadError> {
+        Ok(StackReport::new(&self.link::<N, E>()?))
+    }
+
+    ///
+    /// Computes the register liveness and finds the uses of the undefined registers.
+    ///
+    pub fn liveness_report<const N: usize, E: VmEncodingMode<N>>(
+        &self,
+    ) -> Result<LivenessReport, InstructionReadError> {
+        Ok(LivenessReport::new(&self.link::<N, E>()?))
     }
 
     pub fn from_string(
         input: String,
         metadata_hash: Option<[u8; 32]>,
+    ) -> Result<Self, AssemblyParseError> {
+        let config = AssemblerConfig::default().with_metadata_policy(metadata_hash.into());
+        Self::from_string_with_config(input, config)
+    }
+
+    ///
+    /// Parses the assembly with the configuration.
+    ///
+    /// The `.landing_pads` directive of the file overrides the `Default` landing pad policy of
+    /// the configuration, while the other policies are treated as explicit, so the directive
+    /// selecting a different one is an error.
+    ///
+    pub fn from_string_with_config(
+        input: String,
+        config: AssemblerConfig,
     ) -> Result<Self, AssemblyParseError> {
         use crate::assembly::parse::*;
+        let metadata_hash = config.metadata_policy.metadata_hash(input.as_str());
         let newline = ['\r', '\n'];
         let text = input.trim_matches(&newline[..]);
 
         let (a, b) = split_into_sections(text)?;
-        let (_, sections, labels) = parse_sections(a, b)?;
+        let (_, sections, labels, landing_pad_policy, symbols) = parse_sections(a, b)?;
+        symbols.validate(&sections, &labels)?;
+        validate_instructions(&sections, text, &config)?;
+        let config = match landing_pad_policy {
+            Some(landing_pad_policy)
+                if config.landing_pad_policy != LandingPadPolicy::Default
+                    && config.landing_pad_policy != landing_pad_policy =>
+            {
+                return Err(AssemblyParseError::LandingPadPolicyConflict(
+                    landing_pad_policy,
+                    config.landing_pad_policy,
+                ));
+            }
+            Some(landing_pad_policy) => config.with_landing_pad_policy(landing_pad_policy),
+            None => config,
+        };
 
         let new = Self {
             metadata_hash,
@@ -311,9 +474,12 @@ impl Assembly {
             assembly_code: text.to_owned(),
             pc_line_mapping: HashMap::new(),
             function_labels: HashMap::new(),
+            deduplicated_constants: 0,
+            config,
             global_variables: HashMap::new(),
             parsed_sections: sections,
             labels,
+            entry: symbols.entry().map(str::to_owned),
         };
 
         Ok(new)
@@ -352,13 +518,50 @@ impl TryFrom<String> for Assembly {
     type Error = AssemblyParseError;
 
     fn try_from(input: String) -> Result<Self, Self::Error> {
-        let metadata_hash = sha3::Keccak256::digest(input.as_bytes()).into();
-        Self::from_string(input, Some(metadata_hash))
+        Self::from_string_with_config(input, AssemblerConfig::default())
+    }
+}
+
+///
+/// Checks that every instruction is available in the configured ISA version, and that it does
+/// not discard its result unless the configuration allows it.
+///
+fn validate_instructions(
+    sections: &[ParsedSection],
+    text: &str,
+    config: &AssemblerConfig,
+) -> Result<(), AssemblyParseError> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut errors = HashMap::new();
+    for section in sections.iter() {
+        let section = match section {
+            ParsedSection::Text(section) => section,
+            _ => continue,
+        };
+        for el in section.code_elements() {
+            let line = lines.get(el.source_line).copied().unwrap_or_default();
+            if let Err(e) = el.instruction.check_isa_version(config.isa_version) {
+                errors.insert(el.source_line, (line.trim().to_owned(), e));
+            } else if config.null_destination_policy == NullDestinationPolicy::Reject
+                && el.instruction.discards_result()
+            {
+                let e = InstructionReadError::DiscardedResult(el.instruction.to_string());
+                errors.insert(el.source_line, (line.trim().to_owned(), e));
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AssemblyParseError::TextSectionInvalid(
+            SectionReadError::LineReadError(errors),
+        ))
     }
 }
 
 fn trim_comments(str: &str) -> &str {
-    str.trim().split(';').next().unwrap_or("")
+    strip_comment(str.trim())
 }
 
 #[cfg(test)]
@@ -417,7 +620,42 @@ __eh:
     fn test_parse_tmp() {
         let mut assembly = Assembly::try_from(TMP.to_owned()).unwrap();
         let _ = assembly.compile_to_bytecode().unwrap();
-        let instructions = assembly.opcodes::<8, EncodingModeProduction>();
-        dbg!(&instructions);
+        let linked = assembly.link::<8, EncodingModeProduction>().unwrap();
+        dbg!(linked.opcodes());
+    }
+
+    #[test]
+    fn test_register_validation() {
+        let code = |line: &str| format!("\t.text\n__entry:\n\t{}\n\tret", line);
+        let line_errors = |error| match error {
+            AssemblyParseError::TextSectionInvalid(SectionReadError::LineReadError(errors)) => {
+                errors
+                    .into_values()
+                    .map(|(_, error)| error)
+                    .collect::<Vec<_>>()
+            }
+            error => panic!("unexpected error {:?}", error),
+        };
+
+        let error = Assembly::try_from(code("add r16, r0, stack[r1]")).unwrap_err();
+        assert_eq!(
+            line_errors(error),
+            vec![InstructionReadError::RegisterOutOfRange(
+                "r16".to_owned(),
+                zkevm_opcode_defs::REGISTERS_COUNT
+            )]
+        );
+
+        let error = Assembly::try_from(code("add r1, r2, r0")).unwrap_err();
+        assert_eq!(
+            line_errors(error),
+            vec![InstructionReadError::DiscardedResult(
+                "add r1, r2, r0".to_owned()
+            )]
+        );
+        let config =
+            AssemblerConfig::default().with_null_destination_policy(NullDestinationPolicy::Allow);
+        assert!(Assembly::from_string_with_config(code("add r1, r2, r0"), config).is_ok());
+        assert!(Assembly::try_from(code("sub! r1, r2, r0")).is_ok());
     }
 }
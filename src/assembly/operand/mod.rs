@@ -18,6 +18,8 @@ use crate::error::InstructionReadError;
 use zkevm_opcode_defs::{ImmMemHandlerFlags, RegOrImmFlags};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum FullOperand {
     /// Full including all memory modifiers
     Full(GenericOperand),
@@ -33,6 +35,7 @@ pub enum FullOperand {
 /// Structure representing address of the constant.
 ///
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConstantOperand {
     /// Label of the constant
     pub label: String,
@@ -43,6 +46,7 @@ pub struct ConstantOperand {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlobalVariable {
     /// Label of the constant
     pub label: String,
@@ -56,7 +60,12 @@ pub struct GlobalVariable {
 /// Structure representing address in memory.
 ///
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GenericOperand {
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::assembly::serialization::imm_mem_handler_flags")
+    )]
     pub r#type: ImmMemHandlerFlags,
     /// Offset to apply to sp for Stack memory and memory address for other types of memory
     pub immediate: u64,
@@ -76,7 +85,12 @@ pub enum RegisterOperand {
 /// Structure representing non-memory operand
 ///
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NonMemoryOperand {
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::assembly::serialization::reg_or_imm_flags")
+    )]
     pub r#type: RegOrImmFlags,
     /// Offset to apply to sp for Stack memory and memory address for other types of memory
     pub immediate: u64,
@@ -397,6 +411,25 @@ impl GenericOperand {
 }
 
 impl RegisterOperand {
+    ///
+    /// The register `r{index}`, where `r0` is the null one.
+    ///
+    /// # Panics
+    ///
+    /// If the index is greater than the number of the general purpose registers.
+    ///
+    pub fn new(index: u8) -> Self {
+        assert!(
+            index as usize <= zkevm_opcode_defs::REGISTERS_COUNT,
+            "register index {} is out of range",
+            index
+        );
+        match index {
+            0 => Self::Null,
+            index => Self::Register(index),
+        }
+    }
+
     ///
     /// Whether the register is the null (`r0`) one.
     ///
@@ -409,16 +442,236 @@ impl TryFrom<&str> for RegisterOperand {
     type Error = InstructionReadError;
 
     fn try_from(input: &str) -> Result<Self, Self::Error> {
-        let index: u8 = input
+        let index: u64 = input
             .parse()
             .map_err(|e| InstructionReadError::InvalidNumber(input.to_owned(), e))?;
         if index == 0 {
             return Ok(Self::Null);
         }
-        if index as usize > zkevm_opcode_defs::REGISTERS_COUNT {
-            return Err(InstructionReadError::UnknownRegister(input.to_owned()));
+        if index > zkevm_opcode_defs::REGISTERS_COUNT as u64 {
+            return Err(InstructionReadError::RegisterOutOfRange(
+                format!("r{}", input),
+                zkevm_opcode_defs::REGISTERS_COUNT,
+            ));
+        }
+
+        Ok(Self::Register(index as u8))
+    }
+}
+
+impl std::fmt::Display for RegisterOperand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Null => write!(f, "r0"),
+            Self::Register(index) => write!(f, "r{}", index),
         }
+    }
+}
 
-        Ok(Self::Register(index))
+impl std::fmt::Display for NonMemoryOperand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.r#type {
+            RegOrImmFlags::UseRegOnly => write!(f, "{}", self.register),
+            RegOrImmFlags::UseImm16Only => write!(f, "{}", self.immediate),
+        }
+    }
+}
+
+impl NonMemoryOperand {
+    pub fn register(register: RegisterOperand) -> Self {
+        Self {
+            r#type: RegOrImmFlags::UseRegOnly,
+            immediate: 0,
+            register,
+        }
+    }
+
+    pub fn immediate(immediate: u64) -> Self {
+        Self {
+            r#type: RegOrImmFlags::UseImm16Only,
+            immediate,
+            register: RegisterOperand::Null,
+        }
+    }
+}
+
+impl GenericOperand {
+    pub fn register(register: RegisterOperand) -> Self {
+        Self {
+            r#type: ImmMemHandlerFlags::UseRegOnly,
+            immediate: 0,
+            register,
+        }
+    }
+
+    ///
+    /// The immediate value. The zero is the null register, as it is parsed from `0`.
+    ///
+    pub fn immediate(immediate: u64) -> Self {
+        if immediate == 0 {
+            return Self::register(RegisterOperand::Null);
+        }
+        Self {
+            r#type: ImmMemHandlerFlags::UseImm16Only,
+            immediate,
+            register: RegisterOperand::Null,
+        }
+    }
+
+    ///
+    /// The `stack-=[register + immediate]` source or the `stack+=[register + immediate]` destination.
+    ///
+    pub fn stack_push_pop(register: RegisterOperand, immediate: u64) -> Self {
+        Self {
+            r#type: ImmMemHandlerFlags::UseStackWithPushPop,
+            immediate,
+            register,
+        }
+    }
+
+    ///
+    /// The `stack-[register + immediate]` operand relative to the stack pointer.
+    ///
+    pub fn stack_relative(register: RegisterOperand, immediate: u64) -> Self {
+        Self {
+            r#type: ImmMemHandlerFlags::UseStackWithOffset,
+            immediate,
+            register,
+        }
+    }
+
+    ///
+    /// The `stack[register + immediate]` operand.
+    ///
+    pub fn stack_absolute(register: RegisterOperand, immediate: u64) -> Self {
+        Self {
+            r#type: ImmMemHandlerFlags::UseAbsoluteOnStack,
+            immediate,
+            register,
+        }
+    }
+
+    ///
+    /// The `code[register + immediate]` operand.
+    ///
+    pub fn code(register: RegisterOperand, immediate: u64) -> Self {
+        Self {
+            r#type: ImmMemHandlerFlags::UseCodePage,
+            immediate,
+            register,
+        }
+    }
+}
+
+impl FullOperand {
+    ///
+    /// The `@label` operand, which is either a code label or a constant.
+    ///
+    pub fn label(label: &str) -> Self {
+        Self::constant(label, RegisterOperand::Null, 0)
+    }
+
+    ///
+    /// The `@label[register + immediate]` constant.
+    ///
+    pub fn constant(label: &str, register: RegisterOperand, immediate: u64) -> Self {
+        Self::Constant(ConstantOperand {
+            label: label.to_owned(),
+            register,
+            immediate,
+        })
+    }
+
+    ///
+    /// The `stack[@label + register + immediate]` global variable.
+    ///
+    pub fn global(label: &str, register: RegisterOperand, immediate: u64) -> Self {
+        Self::GlobalVariable(GlobalVariable {
+            label: label.to_owned(),
+            register,
+            immediate,
+        })
+    }
+}
+
+impl From<GenericOperand> for FullOperand {
+    fn from(operand: GenericOperand) -> Self {
+        Self::Full(operand)
+    }
+}
+
+impl From<RegisterOperand> for FullOperand {
+    fn from(register: RegisterOperand) -> Self {
+        Self::Full(GenericOperand::register(register))
+    }
+}
+
+///
+/// Formats the `[@label + rX + imm]` address, omitting the empty parts.
+///
+fn address_to_string(label: Option<&str>, register: RegisterOperand, immediate: u64) -> String {
+    let mut parts = Vec::with_capacity(3);
+    if let Some(label) = label {
+        parts.push(format!("@{}", label));
+    }
+    if !register.is_void() {
+        parts.push(register.to_string());
+    }
+    if immediate != 0 || parts.is_empty() {
+        parts.push(immediate.to_string());
+    }
+
+    format!("[{}]", parts.join(" + "))
+}
+
+impl GenericOperand {
+    ///
+    /// Formats the operand in the canonical assembly syntax.
+    ///
+    /// The push and pop addressing share the same flag, so `is_destination` picks between
+    /// `stack+=` and `stack-=`. The linked code page operands are printed as `code[...]`, which
+    /// is not a source syntax, as the source addresses the code page with the constant labels.
+    ///
+    pub fn to_canonical_string(self, is_destination: bool) -> String {
+        let address = || address_to_string(None, self.register, self.immediate);
+        match self.r#type {
+            ImmMemHandlerFlags::UseRegOnly => self.register.to_string(),
+            ImmMemHandlerFlags::UseImm16Only => self.immediate.to_string(),
+            ImmMemHandlerFlags::UseStackWithPushPop if is_destination => {
+                format!("stack+={}", address())
+            }
+            ImmMemHandlerFlags::UseStackWithPushPop => format!("stack-={}", address()),
+            ImmMemHandlerFlags::UseStackWithOffset => format!("stack-{}", address()),
+            ImmMemHandlerFlags::UseAbsoluteOnStack => format!("stack{}", address()),
+            ImmMemHandlerFlags::UseCodePage => format!("code{}", address()),
+        }
+    }
+}
+
+impl FullOperand {
+    ///
+    /// Formats the operand in the canonical assembly syntax.
+    ///
+    /// The constants without an offset and a register are printed as the bare `@label`.
+    ///
+    pub fn to_canonical_string(&self, is_destination: bool) -> String {
+        match self {
+            FullOperand::Full(operand) => operand.to_canonical_string(is_destination),
+            FullOperand::Register(register) => register.to_string(),
+            FullOperand::Constant(constant)
+                if constant.register.is_void() && constant.immediate == 0 =>
+            {
+                format!("@{}", constant.label)
+            }
+            FullOperand::Constant(constant) => format!(
+                "@{}{}",
+                constant.label,
+                address_to_string(None, constant.register, constant.immediate)
+            ),
+            FullOperand::GlobalVariable(global) => format!(
+                "stack{}",
+                address_to_string(Some(&global.label), global.register, global.immediate)
+            ),
+        }
     }
 }
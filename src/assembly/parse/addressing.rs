@@ -2,11 +2,13 @@ use super::*;
 
 use crate::assembly::mnemonic::all_from_tag_until_1_noconsume;
 use crate::assembly::mnemonic::all_until_1_noconsume_inclusive;
+use crate::assembly::parse::literal::{find_unquoted, parse_u64_literal};
 use crate::assembly::operand::ConstantOperand;
 use crate::assembly::operand::GenericOperand;
 use crate::assembly::operand::GlobalVariable;
 use crate::RegisterOperand;
 use nom::error::ParseError;
+use std::convert::TryFrom;
 use zkevm_opcode_defs::ImmMemHandlerFlags;
 
 pub(crate) fn parse_full_operand<'a>(input: &'a str) -> IResult<&'a str, FullOperand> {
@@ -225,7 +227,7 @@ pub(crate) fn parse_absolute_addressing_from_list<'a>(
 
         if let Ok((_, (register_index, _))) = register_parser.parse(chunk) {
             match u64::from_str_radix(register_index, 10) {
-                Ok(imm) => {
+                Ok(imm) if imm <= zkevm_opcode_defs::REGISTERS_COUNT as u64 => {
                     if imm == 0 {
                         register = RegisterOperand::Null;
                     } else {
@@ -243,7 +245,7 @@ pub(crate) fn parse_absolute_addressing_from_list<'a>(
                     pos_to_remove = Some(idx);
                     break;
                 }
-                Err(_) => {
+                _ => {
                     return Err(nom::Err::Error(nom::error::Error::from_error_kind(
                         chunk,
                         nom::error::ErrorKind::Digit,
@@ -305,7 +307,7 @@ pub(crate) fn parse_absolute_addressing_from_list<'a>(
     let mut imm = 0u64;
     for (idx, (mut _sign, chunk)) in input.iter().enumerate() {
         let immediate_body = chunk;
-        match u64::from_str_radix(immediate_body, 10) {
+        match parse_u64_literal(immediate_body) {
             Ok(parsed_imm) => {
                 imm = parsed_imm;
                 pos_to_remove = Some(idx);
@@ -354,6 +356,17 @@ pub(crate) fn parse_absolute_addressing_from_list<'a>(
     Ok(("", full_operand))
 }
 
+///
+/// Finds the register beyond the register file in the operand, so the operand error can name it.
+///
+pub(crate) fn find_invalid_register(operand: &str) -> Option<InstructionReadError> {
+    operand
+        .split(|c: char| !(c.is_ascii_alphanumeric() || "_.@$'\\".contains(c)))
+        .filter_map(|word| word.strip_prefix('r'))
+        .filter(|index| !index.is_empty() && index.chars().all(|c| c.is_ascii_digit()))
+        .find_map(|index| RegisterOperand::try_from(index).err())
+}
+
 fn parse_brackets_content<'a>(input: &'a str) -> IResult<&'a str, &'a str> {
     // we parse everything in brackets
 
@@ -400,14 +413,14 @@ pub(crate) fn parse_absolute_addressing_single<'a>(
         rest = tail;
 
         match u64::from_str_radix(register_index, 10) {
-            Ok(imm) => {
+            Ok(imm) if imm <= zkevm_opcode_defs::REGISTERS_COUNT as u64 => {
                 if imm == 0 {
                     RegisterOperand::Null
                 } else {
                     RegisterOperand::Register(imm as u8)
                 }
             }
-            Err(_) => {
+            _ => {
                 return Err(nom::Err::Error(nom::error::Error::from_error_kind(
                     rest,
                     nom::error::ErrorKind::Digit,
@@ -434,7 +447,7 @@ pub(crate) fn parse_absolute_addressing_single<'a>(
     let immediate = if let Ok((_, (_, tag, _, tail))) = imm_parser.parse(rest) {
         let immediate_body = tail;
 
-        match u64::from_str_radix(immediate_body, 10) {
+        match parse_u64_literal(immediate_body) {
             Ok(imm) => {
                 if tag == "-" {
                     // Do not rely on the assembler for it
@@ -466,7 +479,7 @@ pub(crate) fn parse_absolute_addressing_single<'a>(
         if let Ok((_, (_, body, _))) = imm_parser.parse(rest) {
             let immediate_body = body;
 
-            match u64::from_str_radix(immediate_body, 10) {
+            match parse_u64_literal(immediate_body) {
                 Ok(imm) => imm,
                 Err(_) => {
                     return Err(nom::Err::Error(nom::error::Error::from_error_kind(
@@ -548,77 +561,24 @@ fn parse_immediate<'a>(input: &'a str) -> IResult<&str, u64> {
 }
 
 fn parse_immediate_value<'a>(input: &'a str) -> IResult<&str, u64> {
-    let rest = input;
-    let mut hex_parser = nom::sequence::tuple::<_, _, nom::error::Error<_>, _>((
-        all_from_tag_until_1_noconsume(
-            "0x",
-            nom::branch::alt((nom::character::complete::space1, nom::combinator::eof)),
-        ),
-        nom::combinator::rest,
-    ));
-
-    if let Ok((_, args)) = hex_parser.parse(rest) {
-        let imm_body = args.0;
-        let rest = args.1;
-        match u64::from_str_radix(imm_body, 16) {
-            Ok(imm) => return Ok((rest, imm)),
-            Err(_) => {
-                return Err(nom::Err::Error(nom::error::Error::from_error_kind(
-                    rest,
-                    nom::error::ErrorKind::Digit,
-                )));
-            }
+    // the space of `' '` does not end the immediate
+    let (imm_body, rest) = match find_unquoted(input, |c| c == ' ' || c == '\t') {
+        Some(0) => {
+            return Err(nom::Err::Error(nom::error::Error::from_error_kind(
+                input,
+                nom::error::ErrorKind::Many1,
+            )))
         }
-    }
-
-    let mut binary_parser = nom::sequence::tuple::<_, _, nom::error::Error<_>, _>((
-        all_from_tag_until_1_noconsume(
-            "0b",
-            nom::branch::alt((nom::character::complete::space1, nom::combinator::eof)),
-        ),
-        nom::combinator::rest,
-    ));
-
-    if let Ok((_, args)) = binary_parser.parse(rest) {
-        let imm_body = args.0;
-        let rest = args.1;
-        match u64::from_str_radix(imm_body, 2) {
-            Ok(imm) => return Ok((rest, imm)),
-            Err(_) => {
-                return Err(nom::Err::Error(nom::error::Error::from_error_kind(
-                    rest,
-                    nom::error::ErrorKind::Digit,
-                )));
-            }
-        }
-    }
-
-    let mut decimal_parser = nom::sequence::tuple::<_, _, nom::error::Error<_>, _>((
-        all_until_1_noconsume_inclusive(nom::branch::alt((
-            nom::character::complete::space1,
-            nom::combinator::eof,
+        Some(index) => input.split_at(index),
+        None => (input, ""),
+    };
+    match parse_u64_literal(imm_body) {
+        Ok(imm) => Ok((rest, imm)),
+        Err(_) => Err(nom::Err::Error(nom::error::Error::from_error_kind(
+            rest,
+            nom::error::ErrorKind::Digit,
         ))),
-        nom::combinator::rest,
-    ));
-
-    if let Ok((_, args)) = decimal_parser.parse(rest) {
-        let imm_body = args.0;
-        let rest = args.1;
-        match u64::from_str_radix(imm_body, 10) {
-            Ok(imm) => return Ok((rest, imm)),
-            Err(_) => {
-                return Err(nom::Err::Error(nom::error::Error::from_error_kind(
-                    rest,
-                    nom::error::ErrorKind::Digit,
-                )));
-            }
-        }
     }
-
-    Err(nom::Err::Error(nom::error::Error::from_error_kind(
-        rest,
-        nom::error::ErrorKind::Digit,
-    )))
 }
 
 #[cfg(test)]
@@ -8,6 +8,8 @@ use crate::assembly::mnemonic::all_from_tag_until_1_noconsume;
 use crate::assembly::mnemonic::all_until_1_noconsume_inclusive;
 
 use crate::assembly::operand::GenericOperand;
+use crate::assembly::parse::addressing::find_invalid_register;
+use crate::assembly::parse::literal::{find_invalid_literal, find_unquoted};
 use nom::error::ParseError;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -60,6 +62,21 @@ pub(crate) fn parse_opcode_and_rest<'a>(input: &'a str) -> IResult<&'a str, (&'a
     Ok(("", (opcode, arguments)))
 }
 
+///
+/// Takes the argument up to the comma, which is consumed, or the end, skipping the commas of the
+/// character literals.
+///
+fn parse_argument(input: &str) -> IResult<&str, &str> {
+    match find_unquoted(input, |c| c == ',') {
+        Some(index) if index > 0 => Ok((&input[index + 1..], &input[..index])),
+        None if !input.is_empty() => Ok(("", input)),
+        _ => Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Many1,
+        ))),
+    }
+}
+
 #[track_caller]
 pub(crate) fn split_arguments<'a>(input: &'a str) -> IResult<&'a str, Vec<&'a str>> {
     // only split opcode from any potential arguments
@@ -75,10 +92,7 @@ pub(crate) fn split_arguments<'a>(input: &'a str) -> IResult<&'a str, Vec<&'a st
         if i != 3 {
             let mut parser = nom::sequence::tuple::<_, _, nom::error::Error<_>, _>((
                 nom::character::complete::space0,
-                all_until1(nom::branch::alt((
-                    nom::bytes::complete::tag(","),
-                    nom::combinator::eof,
-                ))),
+                parse_argument,
                 nom::character::complete::space0,
                 nom::combinator::rest,
             ));
@@ -189,6 +203,19 @@ pub(crate) fn parse_code_element_inner<'a>(
     Ok((body, modifiers, arguments))
 }
 
+///
+/// The error of the operand that failed to parse, which names the register out of range or the
+/// malformed literal if there is one.
+///
+fn operand_error(
+    input: &str,
+    fallback: fn(String) -> InstructionReadError,
+) -> InstructionReadError {
+    find_invalid_register(input)
+        .or_else(|| find_invalid_literal(input))
+        .unwrap_or_else(|| fallback(input.to_owned()))
+}
+
 #[track_caller]
 pub(crate) fn parse_canonical_operands_sequence<'a>(
     arguments: Vec<&'a str>,
@@ -215,14 +242,17 @@ pub(crate) fn parse_canonical_operands_sequence<'a>(
                     zkevm_opcode_defs::Operand::Full(_) => {
                         use crate::assembly::parse::addressing::parse_full_operand;
                         let (_, operand) = parse_full_operand(input).map_err(|_| {
-                            InstructionReadError::InvalidGenericOperand(input.to_owned())
+                            operand_error(input, InstructionReadError::InvalidGenericOperand)
                         })?;
                         results.push(operand);
                     }
                     zkevm_opcode_defs::Operand::RegOnly => {
                         let (_, (register, imm)) = parse_absolute_addressing_single(input)
                             .map_err(|_| {
-                                InstructionReadError::InvalidAbsoluteLikeAddress(input.to_owned())
+                                operand_error(
+                                    input,
+                                    InstructionReadError::InvalidAbsoluteLikeAddress,
+                                )
                             })?;
 
                         use crate::assembly::parse::addressing::parse_absolute_addressing_single;
@@ -238,7 +268,7 @@ pub(crate) fn parse_canonical_operands_sequence<'a>(
                     zkevm_opcode_defs::Operand::RegOrImm(_) => {
                         use crate::assembly::parse::addressing::parse_full_operand;
                         let (_, operand) = parse_full_operand(input).map_err(|_| {
-                            InstructionReadError::InvalidGenericOperand(input.to_owned())
+                            operand_error(input, InstructionReadError::InvalidGenericOperand)
                         })?;
                         let _as_reg_imm = operand.clone().as_non_memory_operand(idx)?;
                         results.push(operand);
@@ -251,7 +281,7 @@ pub(crate) fn parse_canonical_operands_sequence<'a>(
                 use crate::assembly::parse::constant_operand::parse_constant_operand;
 
                 let (_, label) = parse_constant_operand(input).map_err(|_| {
-                    InstructionReadError::InvalidLabeledConstantOperand(input.to_owned())
+                    operand_error(input, InstructionReadError::InvalidLabeledConstantOperand)
                 })?;
                 results.push(label);
             }
@@ -268,7 +298,7 @@ pub(crate) fn parse_canonical_operands_sequence<'a>(
                     zkevm_opcode_defs::Operand::Full(_) => {
                         use crate::assembly::parse::addressing::parse_full_operand;
                         let (_, operand) = parse_full_operand(input).map_err(|_| {
-                            InstructionReadError::InvalidGenericOperand(input.to_owned())
+                            operand_error(input, InstructionReadError::InvalidGenericOperand)
                         })?;
                         match &operand {
                             FullOperand::Full(GenericOperand { r#type: t, .. }) => {
@@ -288,7 +318,10 @@ pub(crate) fn parse_canonical_operands_sequence<'a>(
                     zkevm_opcode_defs::Operand::RegOnly => {
                         let (_, (register, imm)) = parse_absolute_addressing_single(input)
                             .map_err(|_| {
-                                InstructionReadError::InvalidAbsoluteLikeAddress(input.to_owned())
+                                operand_error(
+                                    input,
+                                    InstructionReadError::InvalidAbsoluteLikeAddress,
+                                )
                             })?;
 
                         use crate::assembly::parse::addressing::parse_absolute_addressing_single;
@@ -324,48 +357,6 @@ use lazy_static::lazy_static;
 lazy_static! {
     pub(crate) static ref ALL_CANONICALIZATION_TRANSFORMERS: Vec<Box<dyn Fn(&str) -> IResult<&str, String> + 'static + Send + Sync>> =
         vec![Box::from(parse_set_flags_combinator),];
-    pub(crate) static ref ALL_MNEMONIC_TRANSFORMERS: Vec<Box<dyn Fn(&str) -> IResult<&str, String> + 'static + Send + Sync>> = {
-        vec![
-            Box::from(parse_nop_combinator),
-            Box::from(parse_mov_combinator),
-            Box::from(parse_xor_combinator),
-            Box::from(parse_and_combinator),
-            Box::from(parse_or_combinator),
-            Box::from(parse_shl_combinator),
-            Box::from(parse_shr_combinator),
-            Box::from(parse_rol_combinator),
-            Box::from(parse_ror_combinator),
-            // Box::from(parse_advance_sp_combinator),
-            Box::from(parse_shorthand_ret),
-            Box::from(parse_shorthand_revert),
-            Box::from(parse_shorthand_panic),
-            Box::from(parse_invoke_combinator),
-            Box::from(parse_push_combinator),
-            Box::from(parse_pop_combinator),
-            Box::from(parse_sread_combinator),
-            Box::from(parse_sload_combinator),
-            Box::from(parse_sstore_combinator),
-            Box::from(parse_event_combinator),
-            Box::from(parse_to_l1_combinator),
-            Box::from(parse_gas_left_combinator),
-            Box::from(parse_set_gas_per_pubdatagas_left_combinator),
-            Box::from(parse_precompile_combinator),
-            Box::from(parse_increase_sp_shorthard),
-            Box::from(parse_decrease_sp_shorthard),
-            Box::from(parse_shorthand_near_call),
-            Box::from(parse_shorthand_exceptionless_near_call),
-            Box::from(parse_uma_heap_read_combinator),
-            Box::from(parse_uma_aux_heap_read_combinator),
-            Box::from(parse_uma_heap_write_combinator),
-            Box::from(parse_uma_aux_heap_write_combinator),
-            Box::from(parse_uma_fat_ptr_read_combinator),
-            Box::from(parse_uma_heap_read_increment_combinator),
-            Box::from(parse_uma_aux_heap_read_increment_combinator),
-            Box::from(parse_uma_heap_write_increment_combinator),
-            Box::from(parse_uma_aux_heap_write_increment_combinator),
-            Box::from(parse_uma_fat_ptr_read_increment_combinator),
-        ]
-    };
 }
 
 #[track_caller]
@@ -379,11 +370,8 @@ pub(crate) fn parse_code_element<'a>(input: &'a str) -> Result<Instruction, Inst
         }
     }
     // then apply some short mnemonics into full forms
-    for transformer in ALL_MNEMONIC_TRANSFORMERS.iter() {
-        if let Ok((_, transformed)) = transformer(&canonical) {
-            canonical = transformed;
-            break;
-        }
+    if let Some(transformed) = Mnemonic::expand_any(&canonical) {
+        canonical = transformed;
     }
     // now parse
     let (opcode, modifiers, arguments) = parse_code_element_inner(&canonical)?;
@@ -391,6 +379,31 @@ pub(crate) fn parse_code_element<'a>(input: &'a str) -> Result<Instruction, Inst
     Instruction::try_from_parts(opcode, modifiers, arguments)
 }
 
+///
+/// The pseudo-instruction expansions, which report the invalid operands themselves.
+///
+type PseudoInstructionExpansion = fn(&str) -> Option<Result<Vec<String>, InstructionReadError>>;
+
+const ALL_PSEUDO_INSTRUCTIONS: [PseudoInstructionExpansion; 2] =
+    [expand_select, expand_load_immediate];
+
+///
+/// Parses the instruction line, which is several instructions in the case of the `select`
+/// pseudo-instruction.
+///
+pub(crate) fn parse_code_elements(input: &str) -> Result<Vec<Instruction>, InstructionReadError> {
+    match ALL_PSEUDO_INSTRUCTIONS
+        .iter()
+        .find_map(|expansion| expansion(input))
+    {
+        Some(expansion) => expansion?
+            .iter()
+            .map(|line| parse_code_element(line))
+            .collect(),
+        None => parse_code_element(input).map(|instruction| vec![instruction]),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -462,4 +475,50 @@ mod test {
         let opcode = parse_code_element("add! stack[@var + 4], r5, stack=[42]").unwrap();
         dbg!(opcode);
     }
+
+    #[test]
+    fn test_canonical_form_round_trip() {
+        let lines = [
+            "invalid",
+            "nop stack+=[2]",
+            "add @CPI0_0[0], r0, r1",
+            "add.lt stack-[r1 + 3], r0, stack[@var + 4]",
+            "sub.s! stack-=[r1 + 2], r2, stack-[1]",
+            "mul @CPI0_0[r1 + 1], r2, r3, r4",
+            "div.s r1, r2, r3, r4",
+            "jump @label",
+            "jump.eq 42",
+            "context.this r1",
+            "context.inc_tx_num",
+            "shr! @CPI0_0[1], r2, r3",
+            "xor stack=[7], r2, r3",
+            "ptr.add.s @CPI0_0[r2], r2, r3",
+            "sstore r1, r2",
+            "event.first r6, r5",
+            "near_call r1, @f, @DEFAULT_UNWIND",
+            "far_call.static r1, r2, @DEFAULT_FAR_REVERT",
+            "ret.ok.to_label r1, @DEFAULT_FAR_RETURN",
+            "ret.panic r0",
+            "ld.1.inc r1, r2, r3",
+            "st.2 42, r2",
+        ];
+
+        let mut opcodes = HashSet::new();
+        for line in lines {
+            let instruction = parse_code_element(line).unwrap();
+            let printed = instruction.to_string();
+            assert_eq!(
+                parse_code_element(&printed).unwrap(),
+                instruction,
+                "`{}` is printed as `{}`",
+                line,
+                printed
+            );
+            opcodes.insert(std::mem::discriminant(&instruction));
+        }
+        assert_eq!(
+            opcodes.len(),
+            crate::assembly::instruction::ALL_CANONICAL_OPCODES.len()
+        );
+    }
 }
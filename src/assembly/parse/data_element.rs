@@ -1,7 +1,7 @@
 use super::*;
 use crate::assembly::constants::*;
 
-use nom::error::ParseError;
+use super::literal::{parse_literal, radix_name};
 use num_bigint::*;
 use num_traits::*;
 
@@ -12,7 +12,7 @@ pub(crate) fn parse_data_element_as_constant<'a>(
 
     for parser in ALL_DATA_PARSERS.iter() {
         if let Ok((_, constant)) = parser(input) {
-            return Ok(constant);
+            return constant;
         }
     }
 
@@ -23,42 +23,74 @@ pub(crate) fn parse_data_element_as_constant<'a>(
 
 use lazy_static::lazy_static;
 
+///
+/// The data directive parser, which succeeds if the directive is recognized, and then returns
+/// the result of reading its value.
+///
+type DataParser = Box<
+    dyn Fn(&str) -> IResult<&str, Result<Vec<ConstantValue>, InstructionReadError>>
+        + 'static
+        + Send
+        + Sync,
+>;
+
 lazy_static! {
-    pub(crate) static ref ALL_DATA_PARSERS: Vec<Box<dyn Fn(&str) -> IResult<&str, Vec<ConstantValue>> + 'static + Send + Sync>> = {
+    pub(crate) static ref ALL_DATA_PARSERS: Vec<DataParser> = {
         vec![
-            Box::from(parse_cell_into_constant)
-                as Box<dyn Fn(&str) -> IResult<&str, Vec<ConstantValue>> + 'static + Send + Sync>,
+            Box::from(parse_cell_into_constant) as DataParser,
             Box::from(parse_zeroes_into_constant),
         ]
     };
 }
 
-fn parse_cell_into_constant(input: &str) -> IResult<&str, Vec<ConstantValue>> {
-    let (_, biguint) = parse_cell(input)?;
-    if let Some(serialized) = serialize_biguint(biguint) {
-        Ok(("", vec![ConstantValue::Cell(serialized)]))
-    } else {
-        Err(nom::Err::Error(nom::error::Error::from_error_kind(
-            input,
-            nom::error::ErrorKind::TooLarge,
-        )))
-    }
+fn parse_cell_into_constant(
+    input: &str,
+) -> IResult<&str, Result<Vec<ConstantValue>, InstructionReadError>> {
+    let (_, value) = parse_cell(input)?;
+    let constant = parse_cell_value(value).and_then(|biguint| {
+        serialize_biguint(biguint)
+            .map(|serialized| vec![ConstantValue::Cell(serialized)])
+            .ok_or_else(|| InstructionReadError::LiteralTooLarge {
+                literal: value.to_owned(),
+                radix: radix_name(value.trim_start_matches(&['-', '+'][..])),
+                bits: 256,
+            })
+    });
+
+    Ok(("", constant))
 }
 
-fn parse_zeroes_into_constant(input: &str) -> IResult<&str, Vec<ConstantValue>> {
-    let (_, length) = parse_zeroes(input)?;
-    if length % 32 != 0 {
-        return Err(nom::Err::Error(nom::error::Error::from_error_kind(
-            input,
-            nom::error::ErrorKind::TooLarge,
-        )));
-    }
-    Ok(("", vec![ConstantValue::Cell([0u8; 32]); length / 32]))
+fn parse_zeroes_into_constant(
+    input: &str,
+) -> IResult<&str, Result<Vec<ConstantValue>, InstructionReadError>> {
+    let (_, value) = parse_zeroes(input)?;
+    let length = if value.is_empty() {
+        Ok(0)
+    } else {
+        parse_literal(value).and_then(|length| {
+            length
+                .to_usize()
+                .ok_or_else(|| InstructionReadError::LiteralTooLarge {
+                    literal: value.to_owned(),
+                    radix: radix_name(value),
+                    bits: usize::BITS as u64,
+                })
+        })
+    };
+    let constant = length.and_then(|length| {
+        if length % 32 != 0 {
+            return Err(InstructionReadError::InvalidLabeledConstant(
+                input.to_owned(),
+            ));
+        }
+        Ok(vec![ConstantValue::Cell([0u8; 32]); length / 32])
+    });
+
+    Ok(("", constant))
 }
 
-fn parse_zeroes<'a>(input: &'a str) -> IResult<&str, usize> {
-    // we want to parse something `.cell signed_integer`
-    // and transform it into the unsigned 32 byte
+fn parse_zeroes<'a>(input: &'a str) -> IResult<&'a str, &'a str> {
+    // we want to parse something `.zero length`
 
     let mut parser = nom::sequence::tuple::<_, _, nom::error::Error<_>, _>((
         nom::character::complete::space0,
@@ -73,23 +105,10 @@ fn parse_zeroes<'a>(input: &'a str) -> IResult<&str, usize> {
 
     let (_, result) = parser(input)?;
 
-    let value = result.3;
-    if value.is_empty() {
-        return Ok(("", 0));
-    }
-
-    let unsigned = usize::from_str_radix(value, 10);
-    if unsigned.is_err() {
-        return Err(nom::Err::Error(nom::error::Error::from_error_kind(
-            value,
-            nom::error::ErrorKind::Digit,
-        )));
-    }
-
-    Ok(("", unsigned.unwrap()))
+    Ok(("", result.3))
 }
 
-fn serialize_biguint(input: BigUint) -> Option<[u8; 32]> {
+pub(crate) fn serialize_biguint(input: BigUint) -> Option<[u8; 32]> {
     // deal with endianess and size
     let mut result = [0u8; 32];
     let as_bytes = input.to_bytes_be();
@@ -101,9 +120,8 @@ fn serialize_biguint(input: BigUint) -> Option<[u8; 32]> {
     Some(result)
 }
 
-fn parse_cell<'a>(input: &'a str) -> IResult<&str, BigUint> {
+fn parse_cell<'a>(input: &'a str) -> IResult<&'a str, &'a str> {
     // we want to parse something `.cell signed_integer`
-    // and transform it into the unsigned 32 byte
 
     let mut parser = nom::sequence::tuple::<_, _, nom::error::Error<_>, _>((
         nom::character::complete::space0,
@@ -118,9 +136,13 @@ fn parse_cell<'a>(input: &'a str) -> IResult<&str, BigUint> {
 
     let (_, result) = parser(input)?;
 
-    let value = result.3;
+    Ok(("", result.3))
+}
+
+pub(crate) fn parse_cell_value(value: &str) -> Result<BigUint, InstructionReadError> {
+    // we transform the signed integer into the unsigned 32 byte
     if value.is_empty() {
-        return Ok(("", BigUint::zero()));
+        return Ok(BigUint::zero());
     }
 
     let mut value = value;
@@ -133,20 +155,20 @@ fn parse_cell<'a>(input: &'a str) -> IResult<&str, BigUint> {
         value = v;
     }
 
-    let unsigned = BigUint::from_str_radix(value, 10);
-    if unsigned.is_err() {
-        return Err(nom::Err::Error(nom::error::Error::from_error_kind(
-            value,
-            nom::error::ErrorKind::Digit,
-        )));
-    }
-
-    let mut unsigned = unsigned.unwrap();
+    let mut unsigned = parse_literal(value)?;
     if is_negative {
-        unsigned = (BigUint::from(1u64) << 256u32) - unsigned;
+        let modulus = BigUint::from(1u64) << 256u32;
+        if unsigned > modulus {
+            return Err(InstructionReadError::LiteralTooLarge {
+                literal: value.to_owned(),
+                radix: radix_name(value),
+                bits: 256,
+            });
+        }
+        unsigned = modulus - unsigned;
     }
 
-    Ok(("", unsigned))
+    Ok(unsigned)
 }
 
 #[cfg(test)]
@@ -156,6 +178,32 @@ mod test {
     #[test]
     fn test_parse_cell() {
         let (_, value) = parse_cell("     .cell -57896044618658097711785492504343953926634992332820282019728792003956564819968").unwrap();
+        let value = parse_cell_value(value).unwrap();
         dbg!(value);
     }
+
+    #[test]
+    fn test_parse_cell_literals() {
+        let cell = |input: &str| -> Vec<[u8; 32]> {
+            parse_data_element_as_constant(input)
+                .unwrap()
+                .into_iter()
+                .map(ConstantValue::serialize)
+                .collect()
+        };
+        let mut expected = [0u8; 32];
+        expected[28..].copy_from_slice(&[0xff; 4]);
+        assert_eq!(cell(".cell 0xffff_ffff"), vec![expected]);
+        assert_eq!(cell(".cell 4_294_967_295"), vec![expected]);
+        assert_eq!(cell(".cell -1"), vec![[0xff; 32]]);
+        assert_eq!(cell(".cell 'a'")[0][31], b'a');
+        assert_eq!(cell(".zero 0x40").len(), 2);
+        assert_eq!(
+            parse_data_element_as_constant(".cell 0o19").unwrap_err(),
+            InstructionReadError::InvalidLiteral {
+                literal: "0o19".to_owned(),
+                radix: "octal",
+            }
+        );
+    }
 }
@@ -0,0 +1,113 @@
+//!
+//! The landing pad directives.
+//!
+//! The `.landing_pads default|referenced|omit` directive overrides the `Default` landing pad
+//! policy of the configuration for the file, and conflicts with the other ones, and the
+//! `.unwind @label` one makes the `call target` shorthands in the rest of the function unwind to
+//! `label` instead of `DEFAULT_UNWIND`. A pad body of one's own is provided by defining its label
+//! in the code.
+//!
+
+use crate::assembly::config::LandingPadPolicy;
+use crate::error::InstructionReadError;
+
+///
+/// The landing pad settings in effect at some line of the file.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct LandingPads {
+    /// The policy set by the `.landing_pads` directive.
+    policy: Option<LandingPadPolicy>,
+    /// The unwind handler of the current function set by the `.unwind` directive.
+    unwind_label: Option<String>,
+}
+
+impl LandingPads {
+    ///
+    /// Applies the `.landing_pads` or `.unwind` directive on the line, returning whether it was
+    /// one.
+    ///
+    pub(crate) fn apply_directive(&mut self, line: &str) -> Result<bool, InstructionReadError> {
+        let line = line.trim();
+        let (directive, argument) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let argument = argument.trim();
+        let invalid = || InstructionReadError::InvalidLandingPadDirective(line.to_owned());
+
+        match directive {
+            ".landing_pads" => {
+                let policy = match argument {
+                    "default" => LandingPadPolicy::Default,
+                    "referenced" => LandingPadPolicy::Referenced,
+                    "omit" => LandingPadPolicy::Omit,
+                    _ => return Err(invalid()),
+                };
+                if self.policy.is_some_and(|defined| defined != policy) {
+                    return Err(invalid());
+                }
+                self.policy = Some(policy);
+                Ok(true)
+            }
+            ".unwind" => {
+                let label = argument.strip_prefix('@').ok_or_else(invalid)?;
+                if label.is_empty() || label.contains(char::is_whitespace) {
+                    return Err(invalid());
+                }
+                self.unwind_label = Some(label.to_owned());
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    ///
+    /// Starts the function at the label, unless it is a local `.`-prefixed one.
+    ///
+    pub(crate) fn enter_label(&mut self, label: &str) {
+        if !label.starts_with('.') {
+            self.unwind_label = None;
+        }
+    }
+
+    pub(crate) fn policy(&self) -> Option<LandingPadPolicy> {
+        self.policy
+    }
+
+    pub(crate) fn unwind_label(&self) -> Option<&str> {
+        self.unwind_label.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_directives() {
+        let mut landing_pads = LandingPads::default();
+        assert!(landing_pads
+            .apply_directive(".landing_pads referenced")
+            .unwrap());
+        assert!(landing_pads.apply_directive("  .unwind @handler").unwrap());
+        assert!(!landing_pads.apply_directive(".text").unwrap());
+        assert_eq!(landing_pads.policy(), Some(LandingPadPolicy::Referenced));
+        assert_eq!(landing_pads.unwind_label(), Some("handler"));
+
+        landing_pads.enter_label(".BB0_1");
+        assert_eq!(landing_pads.unwind_label(), Some("handler"));
+        landing_pads.enter_label("next");
+        assert_eq!(landing_pads.unwind_label(), None);
+
+        for invalid in [
+            ".landing_pads omit",
+            ".landing_pads some",
+            ".unwind handler",
+        ] {
+            assert_eq!(
+                landing_pads.apply_directive(invalid),
+                Err(InstructionReadError::InvalidLandingPadDirective(
+                    invalid.to_owned()
+                ))
+            );
+        }
+    }
+}
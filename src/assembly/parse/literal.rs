@@ -0,0 +1,283 @@
+//!
+//! The numeric and character literals of immediates, offsets and data directives.
+//!
+//! The numbers are written in base 10, or with the `0x`, `0b` or `0o` prefix, and may contain
+//! the `_` digit separators, e.g. `0xffff_ffff` or `1_000`. The character literals, e.g. `'a'`
+//! or `'\n'`, stand for the code point of the character. The comments and operands are only
+//! split outside the character literals, so `';'`, `','` and `' '` can be written as well.
+//!
+
+use crate::error::InstructionReadError;
+use num_bigint::BigUint;
+use num_traits::{Num, ToPrimitive};
+
+///
+/// Parses the literal into the unbounded unsigned value.
+///
+pub(crate) fn parse_literal(input: &str) -> Result<BigUint, InstructionReadError> {
+    let (radix, name, digits) = if let Some(body) = input.strip_prefix('\'') {
+        return parse_character(input, body).map(BigUint::from);
+    } else if let Some(digits) = strip_prefix_ignore_case(input, "0x") {
+        (16, "hexadecimal", digits)
+    } else if let Some(digits) = strip_prefix_ignore_case(input, "0b") {
+        (2, "binary", digits)
+    } else if let Some(digits) = strip_prefix_ignore_case(input, "0o") {
+        (8, "octal", digits)
+    } else {
+        (10, "decimal", input)
+    };
+
+    let error = || InstructionReadError::InvalidLiteral {
+        literal: input.to_owned(),
+        radix: name,
+    };
+    // the separators are only allowed between the digits
+    if digits.starts_with('_') || digits.ends_with('_') {
+        return Err(error());
+    }
+    let digits: String = digits.chars().filter(|c| *c != '_').collect();
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return Err(error());
+    }
+
+    BigUint::from_str_radix(digits.as_str(), radix).map_err(|_| error())
+}
+
+///
+/// Parses the literal into the value of at most `u64::BITS` bits.
+///
+pub(crate) fn parse_u64_literal(input: &str) -> Result<u64, InstructionReadError> {
+    parse_literal(input)?
+        .to_u64()
+        .ok_or_else(|| InstructionReadError::LiteralTooLarge {
+            literal: input.to_owned(),
+            radix: radix_name(input),
+            bits: u64::BITS as u64,
+        })
+}
+
+///
+/// Returns the radix name of the literal for the error messages.
+///
+pub(crate) fn radix_name(input: &str) -> &'static str {
+    if input.starts_with('\'') {
+        "character"
+    } else if strip_prefix_ignore_case(input, "0x").is_some() {
+        "hexadecimal"
+    } else if strip_prefix_ignore_case(input, "0b").is_some() {
+        "binary"
+    } else if strip_prefix_ignore_case(input, "0o").is_some() {
+        "octal"
+    } else {
+        "decimal"
+    }
+}
+
+///
+/// Finds the first malformed literal in the operand, so the operand error can name it.
+///
+pub(crate) fn find_invalid_literal(operand: &str) -> Option<InstructionReadError> {
+    let mut rest = operand;
+    while let Some(start) = rest.find(|c: char| c == '\'' || c.is_ascii_digit()) {
+        // the digits inside names, e.g. `r15` or `@CPI0_0`, are not literals
+        // `is_none_or` would raise the minimal Rust version to 1.82
+        #[allow(clippy::unnecessary_map_or)]
+        let is_standalone = rest[..start]
+            .chars()
+            .next_back()
+            .map_or(true, |c| !(c.is_alphanumeric() || "_.@$".contains(c)));
+        rest = &rest[start..];
+        let length = if rest.starts_with('\'') {
+            character_length(rest)
+        } else {
+            rest.find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len())
+        };
+        let (token, tail) = rest.split_at(length);
+        if is_standalone {
+            if let Err(error) = parse_u64_literal(token) {
+                return Some(error);
+            }
+        }
+        rest = tail;
+    }
+
+    None
+}
+
+///
+/// Strips the comment, which starts at the first `;` that is not a character literal.
+///
+pub(crate) fn strip_comment(line: &str) -> &str {
+    &line[..find_unquoted(line, |c| c == ';').unwrap_or(line.len())]
+}
+
+///
+/// Finds the first separator outside the character literals, so `';'`, `','` and `' '` can be
+/// written in the operands.
+///
+pub(crate) fn find_unquoted(input: &str, is_separator: impl Fn(char) -> bool) -> Option<usize> {
+    let mut index = 0;
+    while let Some(c) = input[index..].chars().next() {
+        if is_separator(c) {
+            return Some(index);
+        }
+        let rest = &input[index..];
+        let length = character_length(rest);
+        index += if c == '\'' && length > 1 && rest[..length].ends_with('\'') {
+            length
+        } else {
+            c.len_utf8()
+        };
+    }
+
+    None
+}
+
+fn strip_prefix_ignore_case<'a>(input: &'a str, prefix: &str) -> Option<&'a str> {
+    if input.len() >= prefix.len()
+        && input.is_char_boundary(prefix.len())
+        && input[..prefix.len()].eq_ignore_ascii_case(prefix)
+    {
+        Some(&input[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+///
+/// Returns the length of the quoted character literal at the start of the input, or the whole
+/// input if the literal is not terminated.
+///
+fn character_length(input: &str) -> usize {
+    let mut escaped = false;
+    for (index, c) in input.char_indices().skip(1) {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '\'' if !escaped => return index + 1,
+            _ => escaped = false,
+        }
+    }
+
+    input.len()
+}
+
+fn parse_character(literal: &str, body: &str) -> Result<u32, InstructionReadError> {
+    let error = || InstructionReadError::InvalidLiteral {
+        literal: literal.to_owned(),
+        radix: "character",
+    };
+    let body = body.strip_suffix('\'').ok_or_else(error)?;
+
+    let mut chars = body.chars();
+    let value = match chars.next().ok_or_else(error)? {
+        '\\' => match chars.next().ok_or_else(error)? {
+            'n' => '\n' as u32,
+            'r' => '\r' as u32,
+            't' => '\t' as u32,
+            '0' => 0,
+            '\\' => '\\' as u32,
+            '\'' => '\'' as u32,
+            '"' => '"' as u32,
+            'x' => {
+                let digits = chars.as_str();
+                chars = "".chars();
+                if digits.len() != 2 {
+                    return Err(error());
+                }
+                u32::from_str_radix(digits, 16).map_err(|_| error())?
+            }
+            _ => return Err(error()),
+        },
+        '\'' => return Err(error()),
+        c => c as u32,
+    };
+    if chars.next().is_some() {
+        return Err(error());
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_literals() {
+        assert_eq!(parse_u64_literal("1_000").unwrap(), 1000);
+        assert_eq!(parse_u64_literal("0xffff_ffff").unwrap(), 0xffff_ffff);
+        assert_eq!(parse_u64_literal("0b1010").unwrap(), 10);
+        assert_eq!(parse_u64_literal("0o17").unwrap(), 15);
+        assert_eq!(parse_u64_literal("'a'").unwrap(), 97);
+        assert_eq!(parse_u64_literal("'\\n'").unwrap(), 10);
+        assert_eq!(parse_u64_literal("'\\x41'").unwrap(), 65);
+        assert_eq!(
+            parse_literal("0x8000000000000000000000000000000000000000000000000000000000000000")
+                .unwrap(),
+            BigUint::from(1u64) << 255u32
+        );
+    }
+
+    #[test]
+    fn test_invalid_literals() {
+        let invalid = |literal: &str, radix: &'static str| InstructionReadError::InvalidLiteral {
+            literal: literal.to_owned(),
+            radix,
+        };
+        assert_eq!(parse_literal("0b102"), Err(invalid("0b102", "binary")));
+        assert_eq!(parse_literal("0xfg"), Err(invalid("0xfg", "hexadecimal")));
+        assert_eq!(parse_literal("0o8"), Err(invalid("0o8", "octal")));
+        assert_eq!(parse_literal("1__"), Err(invalid("1__", "decimal")));
+        assert_eq!(parse_literal("'ab'"), Err(invalid("'ab'", "character")));
+        assert_eq!(
+            parse_u64_literal("0x1_0000_0000_0000_0000"),
+            Err(InstructionReadError::LiteralTooLarge {
+                literal: "0x1_0000_0000_0000_0000".to_owned(),
+                radix: "hexadecimal",
+                bits: 64,
+            })
+        );
+        assert_eq!(
+            find_invalid_literal("stack[r1 + 0xzz]"),
+            Some(invalid("0xzz", "hexadecimal"))
+        );
+        assert_eq!(find_invalid_literal("@CPI0_0[r2 + 1_0]"), None);
+    }
+
+    #[test]
+    fn test_separator_characters() {
+        use crate::assembly::Assembly;
+        use std::convert::TryFrom;
+
+        assert_eq!(
+            strip_comment("add ';', r0, r1 ; the ';'"),
+            "add ';', r0, r1 "
+        );
+        assert_eq!(strip_comment("add 'a, r0 ; comment"), "add 'a, r0 ");
+        assert_eq!(find_unquoted("',', r1", |c| c == ','), Some(3));
+
+        let code = r#"
+    .text
+__entry:
+    add ';', r0, r1; the semicolon
+    add ',', r0, r2
+    add ' ', r0, r3
+    ret
+    .rodata
+CPI0_0:
+    .cell ','
+    .cell ';'; the semicolon
+"#;
+        let assembly = Assembly::try_from(code.to_owned()).unwrap();
+        let instructions: Vec<_> = assembly
+            .code_elements()
+            .map(|element| element.instruction().to_string())
+            .collect();
+        assert_eq!(instructions[0], "add 59, r0, r1");
+        assert_eq!(instructions[1], "add 44, r0, r2");
+        assert_eq!(instructions[2], "add 32, r0, r3");
+        let constants: Vec<_> = assembly.constants().collect();
+        assert_eq!(constants[0].content.len(), 2);
+    }
+}
@@ -7,15 +7,88 @@ pub mod addressing;
 pub mod code_element;
 pub mod constant_operand;
 pub mod data_element;
-
+pub mod landing_pad;
+pub mod literal;
+pub mod register_alias;
+pub mod symbol;
+
+use self::landing_pad::LandingPads;
+use self::literal::strip_comment;
+use self::register_alias::RegisterAliases;
+use self::symbol::Symbols;
+use crate::assembly::config::LandingPadPolicy;
+use crate::assembly::mnemonic::parse_exceptionless_near_call_unwinding_to;
 use crate::error::SectionReadError;
 use crate::RegisterOperand;
 
 pub(crate) fn may_be_split_prefix<'a>(input: &'a str, prefix: &str) -> Option<&'a str> {
-    match input.split_once(prefix) {
-        Some((_, rest)) => Some(rest),
-        _ => None,
+    input.strip_prefix(prefix)
+}
+
+///
+/// Whether the line error is the one of an unsupported directive, which is skipped, rather than
+/// of the malformed value of a known one.
+///
+fn is_unknown_directive(error: &InstructionReadError) -> bool {
+    matches!(
+        error,
+        InstructionReadError::InvalidLabeledConstant(_)
+            | InstructionReadError::UnexpectedInstruction(_)
+    )
+}
+
+///
+/// Applies the symbol directive on the data line, or skips the unsupported directive, returning
+/// the error otherwise.
+///
+fn apply_data_directive(
+    symbols: &mut Symbols,
+    line: &str,
+    error: InstructionReadError,
+) -> Result<(), InstructionReadError> {
+    if symbols.apply_directive(line)? || line.starts_with('.') && is_unknown_directive(&error) {
+        return Ok(());
     }
+    Err(error)
+}
+
+///
+/// Applies the alias, landing pad or symbol directive on the text line, returning whether it was
+/// one.
+///
+fn apply_text_directive(
+    register_aliases: &mut RegisterAliases,
+    landing_pads: &mut LandingPads,
+    symbols: &mut Symbols,
+    line: &str,
+) -> Result<bool, InstructionReadError> {
+    Ok(register_aliases.apply_directive(line)?
+        || landing_pads.apply_directive(line)?
+        || symbols.apply_directive(line)?)
+}
+
+///
+/// Parses the instructions on the text line with the register aliases and the unwind handler in
+/// scope, unless the line is a directive.
+///
+fn parse_text_line(
+    register_aliases: &RegisterAliases,
+    landing_pads: &LandingPads,
+    line: &str,
+    is_directive: bool,
+) -> Result<Vec<Instruction>, InstructionReadError> {
+    if is_directive {
+        return Ok(vec![]);
+    }
+
+    let line = register_aliases.resolve(line);
+    if let Some(unwind_label) = landing_pads.unwind_label() {
+        if let Ok((_, canonical)) = parse_exceptionless_near_call_unwinding_to(&line, unwind_label)
+        {
+            return self::code_element::parse_code_element(&canonical).map(|call| vec![call]);
+        }
+    }
+    self::code_element::parse_code_elements(&line)
 }
 
 pub(crate) fn parse_label(input: &str) -> IResult<&str, &str> {
@@ -87,7 +160,7 @@ pub(crate) fn split_into_sections<'a>(
 
     // split into sections, and trim comments
     for (step, line) in lines_with_numbers.clone().enumerate() {
-        let without_comment = line.line.split(';').next().unwrap();
+        let without_comment = strip_comment(line.line);
 
         if parse_rodata_section(without_comment).is_ok() {
             if let Some(current_section) = current_unparsed_section.take() {
@@ -172,6 +245,8 @@ pub(crate) fn parse_sections<'a>(
         impl Iterator<Item = Wrapper<&'a str>> + Clone,
         Vec<ParsedSection>,
         HashSet<String>,
+        Option<LandingPadPolicy>,
+        Symbols,
     ),
     AssemblyParseError,
 > {
@@ -183,6 +258,10 @@ pub(crate) fn parse_sections<'a>(
     let mut all_labels: HashSet<String> = HashSet::new();
     let mut all_globals: HashSet<String> = HashSet::new();
 
+    let mut register_aliases = RegisterAliases::default();
+    let mut landing_pads = LandingPads::default();
+    let mut symbols = Symbols::default();
+
     let mut all_data_section_errors = HashMap::new();
     let mut all_text_section_errors = HashMap::new();
     let mut all_globals_section_errors = HashMap::new();
@@ -215,7 +294,7 @@ pub(crate) fn parse_sections<'a>(
                 'lines: for _ in 0..(label.start - this_line) {
                     let (line_number, line) = lines_iter.next().unwrap();
                     let line = line.line.trim_start();
-                    let without_comment = line.split(';').next().unwrap();
+                    let without_comment = strip_comment(line);
                     assert_eq!(this_line, line_number);
                     this_line += 1;
                     if without_comment.is_empty() {
@@ -242,9 +321,9 @@ pub(crate) fn parse_sections<'a>(
                                     // tmp_data_section.elements.push(section_element);
                                 }
                                 Err(e) => {
-                                    if without_comment.starts_with('.') {
-                                        // some remnant section
-                                    } else {
+                                    if let Err(e) =
+                                        apply_data_directive(&mut symbols, without_comment, e)
+                                    {
                                         all_data_section_errors
                                             .insert(line_number, (without_comment.to_owned(), e));
                                     }
@@ -271,9 +350,9 @@ pub(crate) fn parse_sections<'a>(
                                     // tmp_globals_section.elements.push(section_element);
                                 }
                                 Err(e) => {
-                                    if without_comment.starts_with('.') {
-                                        // some remnant section
-                                    } else {
+                                    if let Err(e) =
+                                        apply_data_directive(&mut symbols, without_comment, e)
+                                    {
                                         all_globals_section_errors
                                             .insert(line_number, (without_comment.to_owned(), e));
                                     }
@@ -281,18 +360,34 @@ pub(crate) fn parse_sections<'a>(
                             }
                         }
                         SectionType::Text => {
-                            match self::code_element::parse_code_element(without_comment) {
-                                Ok(instruction) => {
-                                    let code_element = CodeElement {
-                                        source_line: line_number,
-                                        instruction,
-                                    };
-                                    let section_element =
-                                        TextSectionElement::Unlabeled(code_element);
-                                    tmp_text_section.elements.push(section_element);
+                            match apply_text_directive(
+                                &mut register_aliases,
+                                &mut landing_pads,
+                                &mut symbols,
+                                without_comment,
+                            )
+                            .and_then(|is_directive| {
+                                parse_text_line(
+                                    &register_aliases,
+                                    &landing_pads,
+                                    without_comment,
+                                    is_directive,
+                                )
+                            }) {
+                                Ok(instructions) => {
+                                    for instruction in instructions {
+                                        let code_element = CodeElement {
+                                            source_line: line_number,
+                                            instruction,
+                                        };
+                                        let section_element =
+                                            TextSectionElement::Unlabeled(code_element);
+                                        tmp_text_section.elements.push(section_element);
+                                    }
                                 }
                                 Err(e) => {
-                                    if without_comment.starts_with('.') {
+                                    if without_comment.starts_with('.') && is_unknown_directive(&e)
+                                    {
                                         // some remnant section
                                     } else {
                                         all_text_section_errors
@@ -311,6 +406,9 @@ pub(crate) fn parse_sections<'a>(
                 let _ = lines_iter.next().unwrap();
                 // do nothing, it's a label itself
             }
+            if section.section_type == SectionType::Text {
+                landing_pads.enter_label(label.label);
+            }
 
             let mut labeled_data_tmp_content = Vec::with_capacity(1024);
             let mut labeled_text_tmp_content = Vec::with_capacity(1024);
@@ -318,7 +416,7 @@ pub(crate) fn parse_sections<'a>(
             for _ in 0..(label.end - this_line) {
                 let (line_number, line) = lines_iter.next().unwrap();
                 let line = line.line.trim_start();
-                let without_comment = line.split(';').next().unwrap();
+                let without_comment = strip_comment(line);
                 assert_eq!(this_line, line_number);
                 this_line += 1;
                 if without_comment.is_empty() {
@@ -332,9 +430,9 @@ pub(crate) fn parse_sections<'a>(
                                 labeled_data_tmp_content.extend(constants);
                             }
                             Err(e) => {
-                                if without_comment.starts_with('.') {
-                                    // some remnant section
-                                } else {
+                                if let Err(e) =
+                                    apply_data_directive(&mut symbols, without_comment, e)
+                                {
                                     all_data_section_errors
                                         .insert(line_number, (without_comment.to_owned(), e));
                                 }
@@ -347,9 +445,9 @@ pub(crate) fn parse_sections<'a>(
                                 labeled_globals_tmp_content.extend(constants);
                             }
                             Err(e) => {
-                                if without_comment.starts_with('.') {
-                                    // some remnant section
-                                } else {
+                                if let Err(e) =
+                                    apply_data_directive(&mut symbols, without_comment, e)
+                                {
                                     all_globals_section_errors
                                         .insert(line_number, (without_comment.to_owned(), e));
                                 }
@@ -357,16 +455,30 @@ pub(crate) fn parse_sections<'a>(
                         }
                     }
                     SectionType::Text => {
-                        match self::code_element::parse_code_element(without_comment) {
-                            Ok(instruction) => {
-                                let code_element = CodeElement {
-                                    source_line: line_number,
-                                    instruction,
-                                };
-                                labeled_text_tmp_content.push(code_element);
+                        match apply_text_directive(
+                            &mut register_aliases,
+                            &mut landing_pads,
+                            &mut symbols,
+                            without_comment,
+                        )
+                        .and_then(|is_directive| {
+                            parse_text_line(
+                                &register_aliases,
+                                &landing_pads,
+                                without_comment,
+                                is_directive,
+                            )
+                        }) {
+                            Ok(instructions) => {
+                                labeled_text_tmp_content.extend(instructions.into_iter().map(
+                                    |instruction| CodeElement {
+                                        source_line: line_number,
+                                        instruction,
+                                    },
+                                ));
                             }
                             Err(e) => {
-                                if without_comment.starts_with('.') {
+                                if without_comment.starts_with('.') && is_unknown_directive(&e) {
                                     // some remnant section
                                 } else {
                                     all_text_section_errors
@@ -469,7 +581,13 @@ pub(crate) fn parse_sections<'a>(
         ));
     }
 
-    Ok((lines_with_numbers, parsed_sections, all_labels))
+    Ok((
+        lines_with_numbers,
+        parsed_sections,
+        all_labels,
+        landing_pads.policy(),
+        symbols,
+    ))
 }
 
 #[cfg(test)]
@@ -0,0 +1,245 @@
+//!
+//! The symbolic register aliases.
+//!
+//! The file starts with the ABI names of the calling convention, and the `.reg name, rN`
+//! directive defines a new alias up to the `.unreg name` one or the end of the file. The aliases
+//! are resolved into the `rN` names before the operands are parsed.
+//!
+
+use crate::error::InstructionReadError;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+///
+/// The ABI names of the registers: the return value and the first arguments.
+///
+pub(crate) const ABI_REGISTER_ALIASES: [(&str, u8); 5] =
+    [("rv", 1), ("a0", 1), ("a1", 2), ("a2", 3), ("a3", 4)];
+
+///
+/// The operand keywords, which cannot be used as the alias names.
+///
+const RESERVED_NAMES: [&str; 2] = ["stack", "code"];
+
+///
+/// The register aliases in scope at some line of the file.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RegisterAliases {
+    /// The register index by the alias name.
+    aliases: HashMap<String, u8>,
+    /// The names defined by `.reg` in the file, which cannot be redefined without `.unreg`.
+    defined: Vec<String>,
+}
+
+impl Default for RegisterAliases {
+    fn default() -> Self {
+        Self {
+            aliases: ABI_REGISTER_ALIASES
+                .iter()
+                .map(|(name, index)| (name.to_string(), *index))
+                .collect(),
+            defined: vec![],
+        }
+    }
+}
+
+impl RegisterAliases {
+    ///
+    /// Applies the `.reg` or `.unreg` directive on the line, returning whether it was one.
+    ///
+    pub(crate) fn apply_directive(&mut self, line: &str) -> Result<bool, InstructionReadError> {
+        let line = line.trim();
+        let (directive, arguments) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let invalid = || InstructionReadError::InvalidRegisterAlias(line.to_owned());
+
+        match directive {
+            ".reg" => {
+                let (name, register) = arguments.split_once(',').ok_or_else(invalid)?;
+                let (name, register) = (name.trim(), register.trim());
+                if !is_alias_name(name) {
+                    return Err(invalid());
+                }
+                if self.defined.iter().any(|defined| defined == name) {
+                    return Err(InstructionReadError::DuplicateRegisterAlias(
+                        name.to_owned(),
+                    ));
+                }
+                let index = match self.aliases.get(register) {
+                    Some(index) => *index,
+                    None => register_index(register).ok_or_else(invalid)?,
+                };
+
+                self.aliases.insert(name.to_owned(), index);
+                self.defined.push(name.to_owned());
+                Ok(true)
+            }
+            ".unreg" => {
+                let name = arguments.trim();
+                if self.aliases.remove(name).is_none() {
+                    return Err(InstructionReadError::UnknownRegisterAlias(name.to_owned()));
+                }
+                self.defined.retain(|defined| defined != name);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    ///
+    /// Rewrites the aliases in the operands of the instruction into the `rN` names.
+    ///
+    pub(crate) fn resolve<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        let line_start = line.len() - line.trim_start().len();
+        let operands_start = line[line_start..]
+            .find(char::is_whitespace)
+            .map_or(line.len(), |index| line_start + index);
+
+        let mut resolved = String::with_capacity(line.len());
+        let mut copied = 0;
+        let mut previous = None;
+        let mut chars = line[operands_start..].char_indices().peekable();
+        while let Some((index, c)) = chars.next() {
+            let is_word = is_name_char(c);
+            // the names after `@`, `.` or inside other words are labels, numbers or literals
+            // `is_none_or` would raise the minimal Rust version to 1.82
+            #[allow(clippy::unnecessary_map_or)]
+            let is_standalone =
+                previous.map_or(true, |p: char| !(is_name_char(p) || "@.'\\$".contains(p)));
+            previous = Some(c);
+            if !is_word || !is_standalone {
+                continue;
+            }
+
+            let start = operands_start + index;
+            let mut end = start + c.len_utf8();
+            while let Some((index, c)) = chars.next_if(|(_, c)| is_name_char(*c)) {
+                end = operands_start + index + c.len_utf8();
+                previous = Some(c);
+            }
+            if let Some(index) = self.aliases.get(&line[start..end]) {
+                resolved.push_str(&line[copied..start]);
+                resolved.push_str(format!("r{}", index).as_str());
+                copied = end;
+            }
+        }
+
+        if copied == 0 {
+            return Cow::Borrowed(line);
+        }
+        resolved.push_str(&line[copied..]);
+        Cow::Owned(resolved)
+    }
+
+    ///
+    /// The alias names in scope, sorted.
+    ///
+    pub(crate) fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.aliases.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn is_alias_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(is_name_char)
+        && register_index(name).is_none()
+        && !RESERVED_NAMES.contains(&name)
+}
+
+///
+/// The index of the `rN` general purpose register.
+///
+fn register_index(name: &str) -> Option<u8> {
+    let index: u8 = name.strip_prefix('r')?.parse().ok()?;
+    (1..=zkevm_opcode_defs::REGISTERS_COUNT as u8)
+        .contains(&index)
+        .then_some(index)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::assembly::Assembly;
+    use crate::error::AssemblyParseError;
+    use crate::error::SectionReadError;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_resolve() {
+        let mut aliases = RegisterAliases::default();
+        assert!(aliases.apply_directive(".reg counter, r5").unwrap());
+        assert!(aliases.apply_directive("  .reg total, a1").unwrap());
+        assert!(!aliases.apply_directive(".text").unwrap());
+        assert_eq!(
+            aliases.resolve("\tadd.lt counter, total, stack[a0 + 'a']"),
+            "\tadd.lt r5, r2, stack[r1 + 'a']"
+        );
+        assert_eq!(
+            aliases.resolve("jump @counter.a0"),
+            Cow::Borrowed("jump @counter.a0")
+        );
+
+        assert_eq!(
+            aliases.apply_directive(".reg counter, r6"),
+            Err(InstructionReadError::DuplicateRegisterAlias(
+                "counter".to_owned()
+            ))
+        );
+        assert!(aliases.apply_directive(".unreg counter").unwrap());
+        assert_eq!(
+            aliases.resolve("add counter, r0, r1"),
+            "add counter, r0, r1"
+        );
+        assert!(aliases.apply_directive(".reg counter, r6").is_ok());
+        for invalid in [".reg r3, r4", ".reg x, r16", ".reg stack, r1", ".reg x"] {
+            assert_eq!(
+                aliases.apply_directive(invalid),
+                Err(InstructionReadError::InvalidRegisterAlias(
+                    invalid.to_owned()
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn test_aliases_in_assembly() {
+        let instructions = |code: &str| -> Vec<String> {
+            Assembly::try_from(code.to_owned())
+                .unwrap()
+                .code_elements()
+                .map(|element| element.instruction().to_string())
+                .collect()
+        };
+        let aliased = r#"
+    .text
+__entry:
+    .reg value, r3
+    add a0, r0, value
+    add value, rv, a1
+    .unreg value
+    ret
+"#;
+        let plain = r#"
+    .text
+__entry:
+    add r1, r0, r3
+    add r3, r1, r2
+    ret
+"#;
+        assert_eq!(instructions(aliased), instructions(plain));
+
+        let unscoped = aliased.replace("    ret\n", "    add value, r0, r1\n    ret\n");
+        match Assembly::try_from(unscoped).unwrap_err() {
+            AssemblyParseError::TextSectionInvalid(SectionReadError::LineReadError(errors)) => {
+                assert_eq!(errors.keys().collect::<Vec<_>>(), vec![&6]);
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//!
+//! The symbol directives.
+//!
+//! The `.type sym,@function` directive declares the function, and once the file declares any,
+//! only the declared functions can be called. The `.size sym, expression` directive is accepted
+//! for the defined symbols, as the linker measures the functions itself, and the `.entry sym`
+//! directive selects the function the execution starts at. The directives are accepted in every
+//! section, e.g. `.type val,@object` before a data label.
+//!
+
+use super::section::*;
+use crate::assembly::instruction::Instruction;
+use crate::assembly::operand::FullOperand;
+use crate::error::{AssemblyParseError, InstructionReadError};
+use std::collections::HashSet;
+
+///
+/// The symbol attributes declared in the file.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Symbols {
+    /// The symbols declared with `.type sym,@function`.
+    functions: HashSet<String>,
+    /// The symbols of the `.type` and `.size` directives in the order of the file.
+    referenced: Vec<String>,
+    /// The function selected by `.entry`.
+    entry: Option<String>,
+}
+
+impl Symbols {
+    ///
+    /// Applies the `.type`, `.size` or `.entry` directive on the line, returning whether it was
+    /// one.
+    ///
+    pub(crate) fn apply_directive(&mut self, line: &str) -> Result<bool, InstructionReadError> {
+        let line = line.trim();
+        let (directive, arguments) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let invalid = || InstructionReadError::InvalidSymbolDirective(line.to_owned());
+        let symbol = |name: &str| {
+            let name = name.trim();
+            if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == ',') {
+                return Err(invalid());
+            }
+            Ok(name.to_owned())
+        };
+
+        match directive {
+            ".type" => {
+                let (name, kind) = arguments.split_once(',').ok_or_else(invalid)?;
+                let name = symbol(name)?;
+                match kind.trim().trim_start_matches(&['@', '%'][..]) {
+                    "function" => {
+                        self.functions.insert(name.clone());
+                    }
+                    "object" | "notype" => {}
+                    _ => return Err(invalid()),
+                }
+                self.referenced.push(name);
+                Ok(true)
+            }
+            ".size" => {
+                let (name, size) = arguments.split_once(',').ok_or_else(invalid)?;
+                if size.trim().is_empty() {
+                    return Err(invalid());
+                }
+                self.referenced.push(symbol(name)?);
+                Ok(true)
+            }
+            ".entry" => {
+                let name = symbol(arguments)?;
+                if self.entry.as_ref().is_some_and(|entry| *entry != name) {
+                    return Err(invalid());
+                }
+                self.entry = Some(name);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    ///
+    /// The function the execution starts at, if selected.
+    ///
+    pub(crate) fn entry(&self) -> Option<&str> {
+        self.entry.as_deref()
+    }
+
+    ///
+    /// Checks that the symbols are defined, and that only the declared functions are called.
+    ///
+    pub(crate) fn validate(
+        &self,
+        sections: &[ParsedSection],
+        labels: &HashSet<String>,
+    ) -> Result<(), AssemblyParseError> {
+        if let Some(undefined) = self
+            .referenced
+            .iter()
+            .chain(self.entry.as_ref())
+            .find(|symbol| !labels.contains(*symbol))
+        {
+            return Err(AssemblyParseError::LabelNotFound(undefined.to_owned()));
+        }
+        if self.functions.is_empty() {
+            return Ok(());
+        }
+
+        let text_labels: HashSet<&str> = sections
+            .iter()
+            .filter_map(|section| match section {
+                ParsedSection::Text(section) => Some(section),
+                _ => None,
+            })
+            .flat_map(TextSection::functions)
+            .map(LabeledFunction::label)
+            .collect();
+        for element in sections.iter().filter_map(|section| match section {
+            ParsedSection::Text(section) => Some(section),
+            _ => None,
+        }) {
+            for code in element.code_elements() {
+                let target = match code.instruction() {
+                    Instruction::NearCall(call) => match &call.destination {
+                        FullOperand::Constant(constant) => constant.label.as_str(),
+                        _ => continue,
+                    },
+                    _ => continue,
+                };
+                if text_labels.contains(target) && !self.functions.contains(target) {
+                    return Err(AssemblyParseError::NotAFunction(target.to_owned()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_directives() {
+        let mut symbols = Symbols::default();
+        assert!(symbols.apply_directive(".type f,@function").unwrap());
+        assert!(symbols.apply_directive("\t.type val, %object").unwrap());
+        assert!(symbols.apply_directive(".size f, .Lfunc_end0-f").unwrap());
+        assert!(symbols.apply_directive(".entry f").unwrap());
+        assert!(!symbols.apply_directive(".globl f").unwrap());
+        assert_eq!(symbols.entry(), Some("f"));
+        assert!(symbols.functions.contains("f"));
+        assert_eq!(symbols.referenced, vec!["f", "val", "f"]);
+
+        for invalid in [".type f", ".type f,@section", ".size f,", ".entry g"] {
+            assert_eq!(
+                symbols.apply_directive(invalid),
+                Err(InstructionReadError::InvalidSymbolDirective(
+                    invalid.to_owned()
+                ))
+            );
+        }
+    }
+}
@@ -1,3 +1,10 @@
+//!
+//! The parsed sections of the assembly.
+//!
+//! The sections, their elements and the code of every function are kept in the source order.
+//! The source lines are the 0-based indexes of the lines of `Assembly::assembly_code`.
+//!
+
 use super::*;
 
 use crate::assembly::constants::ConstantValue;
@@ -26,78 +33,340 @@ pub(crate) struct UnparsedLabel<'a> {
 }
 
 #[derive(Clone, Debug)]
-pub(crate) enum ParsedSection {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ParsedSection {
     Globals(GlobalsSection),
     Data(DataSection),
     Text(TextSection),
 }
 
 #[derive(Clone, Debug)]
-pub(crate) struct TextSection {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextSection {
     pub(crate) elements: Vec<TextSectionElement>,
 }
 
 // Text section can contain either labeled or unlabeled code
 
 #[derive(Clone, Debug)]
-pub(crate) enum TextSectionElement {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum TextSectionElement {
     Unlabeled(CodeElement),
     Labeled(LabeledFunction),
 }
 
 #[derive(Clone, Debug)]
-pub(crate) struct LabeledFunction {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LabeledFunction {
     pub(crate) label: String,
     pub(crate) source_line: usize,
     pub(crate) content: Vec<CodeElement>,
 }
 
 #[derive(Clone, Debug)]
-pub(crate) struct CodeElement {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CodeElement {
     pub(crate) source_line: usize,
     pub(crate) instruction: Instruction,
 }
 
 // Data section can only contraint constants
 #[derive(Clone, Debug)]
-pub(crate) struct DataSection {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DataSection {
     pub(crate) elements: Vec<DataSectionElement>,
 }
 
 #[derive(Clone, Debug)]
-pub(crate) enum DataSectionElement {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DataSectionElement {
     Unlabeled(ConstantElement),
     Labeled(LabeledConstant),
 }
 
 #[derive(Clone, Debug)]
-pub(crate) struct LabeledConstant {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LabeledConstant {
     pub(crate) label: String,
     pub(crate) source_line: usize,
     pub(crate) content: Vec<ConstantValue>,
 }
 
 #[derive(Clone, Debug)]
-pub(crate) struct ConstantElement {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConstantElement {
     pub(crate) source_line: usize,
     pub(crate) content_type: ConstantValue,
 }
 
 // Globals section can only containt named globals
 #[derive(Clone, Debug)]
-pub(crate) struct GlobalsSection {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GlobalsSection {
     pub(crate) elements: Vec<GlobalsSectionElement>,
 }
 
 #[derive(Clone, Debug)]
-pub(crate) enum GlobalsSectionElement {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum GlobalsSectionElement {
     Unlabeled(ConstantElement),
     Labeled(LabeledGlobal),
 }
 
 #[derive(Clone, Debug)]
-pub(crate) struct LabeledGlobal {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LabeledGlobal {
     pub(crate) label: String,
     pub(crate) source_line: usize,
     pub(crate) content: Vec<ConstantValue>,
 }
+
+impl TextSection {
+    ///
+    /// The instructions before the first label and the labeled functions in the source order.
+    ///
+    pub fn elements(&self) -> &[TextSectionElement] {
+        self.elements.as_slice()
+    }
+
+    ///
+    /// The labeled functions in the source order.
+    ///
+    pub fn functions(&self) -> impl Iterator<Item = &LabeledFunction> {
+        self.elements.iter().filter_map(|element| match element {
+            TextSectionElement::Labeled(function) => Some(function),
+            TextSectionElement::Unlabeled(_) => None,
+        })
+    }
+
+    ///
+    /// The instructions of the section, including the ones before the first label.
+    ///
+    pub fn code_elements(&self) -> impl Iterator<Item = &CodeElement> {
+        self.elements
+            .iter()
+            .flat_map(TextSectionElement::code_elements)
+    }
+}
+
+impl TextSectionElement {
+    ///
+    /// The instructions of the element in the source order.
+    ///
+    pub fn code_elements(&self) -> &[CodeElement] {
+        match self {
+            TextSectionElement::Unlabeled(element) => std::slice::from_ref(element),
+            TextSectionElement::Labeled(function) => function.code_elements(),
+        }
+    }
+}
+
+impl LabeledFunction {
+    ///
+    /// The label the function starts at.
+    ///
+    pub fn label(&self) -> &str {
+        self.label.as_str()
+    }
+
+    ///
+    /// The line of the label.
+    ///
+    pub fn source_line(&self) -> usize {
+        self.source_line
+    }
+
+    ///
+    /// The instructions from the label up to the next one in the source order.
+    ///
+    pub fn code_elements(&self) -> &[CodeElement] {
+        self.content.as_slice()
+    }
+}
+
+impl CodeElement {
+    ///
+    /// The line of the instruction.
+    ///
+    pub fn source_line(&self) -> usize {
+        self.source_line
+    }
+
+    ///
+    /// The parsed instruction, with the labels not linked yet.
+    ///
+    pub fn instruction(&self) -> &Instruction {
+        &self.instruction
+    }
+}
+
+impl DataSection {
+    ///
+    /// The constants before the first label and the labeled constants in the source order.
+    ///
+    pub fn elements(&self) -> &[DataSectionElement] {
+        self.elements.as_slice()
+    }
+
+    ///
+    /// The labeled constants in the source order.
+    ///
+    pub fn constants(&self) -> impl Iterator<Item = &LabeledConstant> {
+        self.elements.iter().filter_map(|element| match element {
+            DataSectionElement::Labeled(constant) => Some(constant),
+            DataSectionElement::Unlabeled(_) => None,
+        })
+    }
+}
+
+impl LabeledConstant {
+    ///
+    /// The label of the constant.
+    ///
+    pub fn label(&self) -> &str {
+        self.label.as_str()
+    }
+
+    ///
+    /// The line of the label.
+    ///
+    pub fn source_line(&self) -> usize {
+        self.source_line
+    }
+
+    ///
+    /// The values of the constant in the source order.
+    ///
+    pub fn values(&self) -> &[ConstantValue] {
+        self.content.as_slice()
+    }
+}
+
+impl ConstantElement {
+    ///
+    /// The line of the value.
+    ///
+    pub fn source_line(&self) -> usize {
+        self.source_line
+    }
+
+    ///
+    /// The value, which has no label.
+    ///
+    pub fn value(&self) -> &ConstantValue {
+        &self.content_type
+    }
+}
+
+impl GlobalsSection {
+    ///
+    /// The values before the first label and the global variables in the source order.
+    ///
+    pub fn elements(&self) -> &[GlobalsSectionElement] {
+        self.elements.as_slice()
+    }
+
+    ///
+    /// The global variables in the source order, which is the order of their stack slots.
+    ///
+    pub fn globals(&self) -> impl Iterator<Item = &LabeledGlobal> {
+        self.elements.iter().filter_map(|element| match element {
+            GlobalsSectionElement::Labeled(global) => Some(global),
+            GlobalsSectionElement::Unlabeled(_) => None,
+        })
+    }
+}
+
+impl LabeledGlobal {
+    ///
+    /// The label of the variable.
+    ///
+    pub fn label(&self) -> &str {
+        self.label.as_str()
+    }
+
+    ///
+    /// The line of the label.
+    ///
+    pub fn source_line(&self) -> usize {
+        self.source_line
+    }
+
+    ///
+    /// The initial values of the words of the variable.
+    ///
+    pub fn values(&self) -> &[ConstantValue] {
+        self.content.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_source_order() {
+        let assembly = Assembly::try_from(
+            r#"
+    .text
+    nop
+f:
+    add r1, r0, r2
+    ret.ok r2
+    .data
+g:
+    .cell 1
+    .cell 2
+    .text
+__entry:
+    near_call r0, @f, @DEFAULT_UNWIND
+    ret.ok r0
+    .rodata
+c:
+    .cell 3
+"#
+            .to_owned(),
+        )
+        .unwrap();
+
+        let lines: Vec<&str> = assembly.assembly_code.lines().collect();
+        let functions: Vec<(&str, &str)> = assembly
+            .functions()
+            .map(|function| (function.label(), lines[function.source_line()]))
+            .collect();
+        assert_eq!(functions, vec![("f", "f:"), ("__entry", "__entry:")]);
+
+        let code: Vec<&str> = assembly
+            .code_elements()
+            .map(|element| lines[element.source_line()].trim())
+            .collect();
+        assert_eq!(
+            code,
+            vec![
+                "nop",
+                "add r1, r0, r2",
+                "ret.ok r2",
+                "near_call r0, @f, @DEFAULT_UNWIND",
+                "ret.ok r0"
+            ]
+        );
+        assert!(matches!(
+            assembly.code_elements().nth(3).unwrap().instruction(),
+            Instruction::NearCall(_)
+        ));
+
+        let globals: Vec<(&str, usize)> = assembly
+            .globals()
+            .map(|global| (global.label(), global.values().len()))
+            .collect();
+        assert_eq!(globals, vec![("g", 2)]);
+        let constants: Vec<&str> = assembly.constants().map(|c| c.label()).collect();
+        assert_eq!(constants, vec!["c"]);
+        assert_eq!(assembly.sections().len(), 4);
+    }
+}
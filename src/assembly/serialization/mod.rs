@@ -0,0 +1,520 @@
+//!
+//! The `serde` representation of the assembly, enabled by the `serde` feature.
+//!
+//! The JSON shapes are documented on `Assembly` and `LinkedAssembly`.
+//!
+
+use crate::assembly::constants::ConstantValue;
+use crate::assembly::instruction::condition::ConditionCase;
+use crate::assembly::instruction::Instruction;
+use crate::assembly::linking::{AlignedRawBytecode, LinkedAssembly};
+use crate::assembly::operand::RegisterOperand;
+use num_bigint::{BigInt, BigUint};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use zkevm_opcode_defs::decoding::VmEncodingMode;
+use zkevm_opcode_defs::ISAVersion;
+
+///
+/// Declares the `serde(with)` module mapping the foreign enum to its canonical names.
+///
+macro_rules! canonical_names {
+    ($module:ident: $type:ident { $($name:literal => $variant:ident,)* }) => {
+        pub(crate) mod $module {
+            use serde::de::Error;
+            use serde::{Deserialize, Deserializer, Serializer};
+            use zkevm_opcode_defs::$type;
+
+            pub fn serialize<S: Serializer>(value: &$type, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(match value {
+                    $($type::$variant => $name,)*
+                })
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<$type, D::Error> {
+                let name = String::deserialize(deserializer)?;
+                match name.as_str() {
+                    $($name => Ok($type::$variant),)*
+                    _ => Err(D::Error::unknown_variant(&name, &[$($name),*])),
+                }
+            }
+        }
+    };
+}
+
+canonical_names!(imm_mem_handler_flags: ImmMemHandlerFlags {
+    "reg" => UseRegOnly,
+    "stack_push_pop" => UseStackWithPushPop,
+    "stack_relative" => UseStackWithOffset,
+    "stack_absolute" => UseAbsoluteOnStack,
+    "imm" => UseImm16Only,
+    "code" => UseCodePage,
+});
+
+canonical_names!(reg_or_imm_flags: RegOrImmFlags {
+    "reg" => UseRegOnly,
+    "imm" => UseImm16Only,
+});
+
+canonical_names!(context_opcode: ContextOpcode {
+    "this" => This,
+    "caller" => Caller,
+    "code_source" => CodeAddress,
+    "meta" => Meta,
+    "ergs_left" => ErgsLeft,
+    "sp" => Sp,
+    "get_context_u128" => GetContextU128,
+    "set_context_u128" => SetContextU128,
+    "set_ergs_per_pubdata" => SetErgsPerPubdataByte,
+    "inc_tx_num" => IncrementTxNumber,
+});
+
+canonical_names!(shift_opcode: ShiftOpcode {
+    "shl" => Shl,
+    "shr" => Shr,
+    "rol" => Rol,
+    "ror" => Ror,
+});
+
+canonical_names!(binop_opcode: BinopOpcode {
+    "xor" => Xor,
+    "and" => And,
+    "or" => Or,
+});
+
+canonical_names!(ptr_opcode: PtrOpcode {
+    "add" => Add,
+    "sub" => Sub,
+    "pack" => Pack,
+    "shrink" => Shrink,
+});
+
+canonical_names!(log_opcode: LogOpcode {
+    "sread" => StorageRead,
+    "swrite" => StorageWrite,
+    "event" => Event,
+    "to_l1" => ToL1Message,
+    "precompile" => PrecompileCall,
+});
+
+canonical_names!(far_call_opcode: FarCallOpcode {
+    "normal" => Normal,
+    "delegate" => Delegate,
+    "mimic" => Mimic,
+});
+
+canonical_names!(ret_opcode: RetOpcode {
+    "ok" => Ok,
+    "revert" => Revert,
+    "panic" => Panic,
+});
+
+canonical_names!(uma_opcode: UMAOpcode {
+    "heap_read" => HeapRead,
+    "heap_write" => HeapWrite,
+    "aux_heap_read" => AuxHeapRead,
+    "aux_heap_write" => AuxHeapWrite,
+    "fat_ptr_read" => FatPointerRead,
+});
+
+pub(crate) mod isa_version {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use zkevm_opcode_defs::ISAVersion;
+
+    pub fn serialize<S: Serializer>(value: &ISAVersion, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(value.0)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ISAVersion, D::Error> {
+        u8::deserialize(deserializer).map(ISAVersion)
+    }
+}
+
+pub(crate) mod hash {
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &[u8; 32], serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize_hex(value, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 32], D::Error> {
+        super::deserialize_word(deserializer)
+    }
+}
+
+pub(crate) mod optional_hash {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Hash(#[serde(with = "super::hash")] [u8; 32]);
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<[u8; 32]>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.map(Hash).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<[u8; 32]>, D::Error> {
+        Ok(Option::<Hash>::deserialize(deserializer)?.map(|hash| hash.0))
+    }
+}
+
+fn serialize_hex<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+}
+
+fn deserialize_hex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let string = String::deserialize(deserializer)?;
+    let digits = string.strip_prefix("0x").ok_or_else(|| {
+        D::Error::custom(format!(
+            "expected a 0x-prefixed hex string, found `{}`",
+            string
+        ))
+    })?;
+    hex::decode(digits).map_err(D::Error::custom)
+}
+
+fn deserialize_word<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 32], D::Error> {
+    let bytes = deserialize_hex(deserializer)?;
+    <[u8; 32]>::try_from(bytes.as_slice())
+        .map_err(|_| D::Error::invalid_length(bytes.len(), &"32 bytes"))
+}
+
+impl Serialize for RegisterOperand {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for RegisterOperand {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        match name.strip_prefix('r').map(str::parse::<u8>) {
+            Some(Ok(0)) => Ok(RegisterOperand::Null),
+            Some(Ok(index)) if (index as usize) <= zkevm_opcode_defs::REGISTERS_COUNT => {
+                Ok(RegisterOperand::Register(index))
+            }
+            _ => Err(D::Error::custom(format!(
+                "expected a register, found `{}`",
+                name
+            ))),
+        }
+    }
+}
+
+impl Serialize for ConditionCase {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.canonical_modifier().unwrap_or("always"))
+    }
+}
+
+impl<'de> Deserialize<'de> for ConditionCase {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        match name.as_str() {
+            "always" => Ok(ConditionCase::default()),
+            name => ConditionCase::from_modifier(name).map_err(D::Error::custom),
+        }
+    }
+}
+
+///
+/// The mirror of `ConstantValue` with the numbers written as strings.
+///
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ConstantValueRepr {
+    Cell(#[serde(with = "hash")] [u8; 32]),
+    Signed {
+        value: String,
+        bits: usize,
+    },
+    Unsigned {
+        value: String,
+        bits: usize,
+    },
+    ByteArray(
+        #[serde(
+            serialize_with = "serialize_byte_array",
+            deserialize_with = "deserialize_hex"
+        )]
+        Vec<u8>,
+    ),
+}
+
+fn serialize_byte_array<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serialize_hex(bytes, serializer)
+}
+
+impl Serialize for ConstantValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ConstantValue::Cell(word) => ConstantValueRepr::Cell(*word),
+            ConstantValue::Signed(value, bits) => ConstantValueRepr::Signed {
+                value: value.to_string(),
+                bits: *bits,
+            },
+            ConstantValue::Unsigned(value, bits) => ConstantValueRepr::Unsigned {
+                value: value.to_string(),
+                bits: *bits,
+            },
+            ConstantValue::ByteArray(bytes) => ConstantValueRepr::ByteArray(bytes.clone()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ConstantValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match ConstantValueRepr::deserialize(deserializer)? {
+            ConstantValueRepr::Cell(word) => ConstantValue::Cell(word),
+            ConstantValueRepr::Signed { value, bits } => {
+                ConstantValue::Signed(value.parse::<BigInt>().map_err(D::Error::custom)?, bits)
+            }
+            ConstantValueRepr::Unsigned { value, bits } => {
+                ConstantValue::Unsigned(value.parse::<BigUint>().map_err(D::Error::custom)?, bits)
+            }
+            ConstantValueRepr::ByteArray(bytes) => ConstantValue::ByteArray(bytes),
+        })
+    }
+}
+
+///
+/// The mirror of `AlignedRawBytecode`.
+///
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum AlignedRawBytecodeRepr {
+    Instructions(Vec<Instruction>),
+    Data(ConstantValue),
+}
+
+///
+/// The bytecode word written as a hex string.
+///
+#[derive(Serialize, Deserialize, PartialEq)]
+struct Word(#[serde(with = "hash")] [u8; 32]);
+
+///
+/// The mirror of `LinkedAssembly` without the encoded instructions, which depend on the mode.
+///
+#[derive(Serialize, Deserialize)]
+struct LinkedAssemblyRepr {
+    #[serde(with = "isa_version")]
+    isa_version: ISAVersion,
+    raw_bytecode: Vec<AlignedRawBytecodeRepr>,
+    bytecode: Vec<Word>,
+    pc_line_mapping: HashMap<usize, usize>,
+    function_labels: HashMap<String, usize>,
+    function_sizes: HashMap<String, usize>,
+    deduplicated_constants: usize,
+    globals_size: usize,
+}
+
+impl<const N: usize, E: VmEncodingMode<N>> Serialize for LinkedAssembly<N, E> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        LinkedAssemblyRepr {
+            isa_version: self.isa_version,
+            raw_bytecode: self
+                .raw_bytecode
+                .iter()
+                .map(|element| match element {
+                    AlignedRawBytecode::Instructions(chunk) => {
+                        AlignedRawBytecodeRepr::Instructions(chunk.to_vec())
+                    }
+                    AlignedRawBytecode::Data(value) => AlignedRawBytecodeRepr::Data(value.clone()),
+                })
+                .collect(),
+            bytecode: self.bytecode.iter().copied().map(Word).collect(),
+            pc_line_mapping: self.pc_line_mapping.clone(),
+            function_labels: self.function_labels.clone(),
+            function_sizes: self.function_sizes.clone(),
+            deduplicated_constants: self.deduplicated_constants,
+            globals_size: self.globals_size,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, const N: usize, E: VmEncodingMode<N>> Deserialize<'de> for LinkedAssembly<N, E> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = LinkedAssemblyRepr::deserialize(deserializer)?;
+        let opcodes_per_word = 32 / N;
+        let mut raw_bytecode = Vec::with_capacity(repr.raw_bytecode.len());
+        for element in repr.raw_bytecode {
+            raw_bytecode.push(match element {
+                AlignedRawBytecodeRepr::Instructions(chunk) if chunk.len() != opcodes_per_word => {
+                    return Err(D::Error::invalid_length(
+                        chunk.len(),
+                        &format!("{} instructions per word", opcodes_per_word).as_str(),
+                    ));
+                }
+                AlignedRawBytecodeRepr::Instructions(chunk) => {
+                    AlignedRawBytecode::Instructions(chunk.into_iter().collect())
+                }
+                AlignedRawBytecodeRepr::Data(value) => AlignedRawBytecode::Data(value),
+            });
+        }
+
+        let linked = Self::new(
+            raw_bytecode,
+            repr.pc_line_mapping,
+            repr.function_labels,
+            repr.function_sizes,
+            repr.deduplicated_constants,
+            repr.globals_size,
+            repr.isa_version,
+        )
+        .map_err(D::Error::custom)?;
+        if !linked.bytecode.iter().copied().map(Word).eq(repr.bytecode) {
+            return Err(D::Error::custom(
+                "the bytecode does not match the encoded instructions",
+            ));
+        }
+        Ok(linked)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::assembly::instruction::Instruction;
+    use crate::assembly::linking::LinkedAssembly;
+    use crate::assembly::parse::code_element::parse_code_element;
+    use crate::Assembly;
+    use std::convert::TryFrom;
+    use zkevm_opcode_defs::decoding::EncodingModeProduction;
+
+    #[test]
+    fn test_instruction_json() {
+        let instruction = parse_code_element("shl.s.eq stack-=[r2 + 1], r3, r4").unwrap();
+        let json = serde_json::to_value(&instruction).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "shift": {
+                    "condition": "eq",
+                    "set_flags_option": false,
+                    "source_1": {
+                        "full": {"type": "stack_push_pop", "immediate": 1, "register": "r2"}
+                    },
+                    "source_2": "r3",
+                    "destination": {
+                        "full": {"type": "reg", "immediate": 0, "register": "r4"}
+                    },
+                    "swap_operands": true,
+                    "variant": "shl",
+                }
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<Instruction>(json).unwrap(),
+            instruction
+        );
+
+        for code in [
+            "uma.fat_ptr_read.inc r1, r2, r3, r4",
+            "far_call.mimic.static r1, r2, @DEFAULT_FAR_REVERT",
+            "log.to_l1.first r1, r2, r0",
+            "ret.panic.to_label r1, @DEFAULT_UNWIND",
+            "context.set_ergs_per_pubdata r1",
+            "add.gtlt @CPI0_0[1], r0, stack[@val]",
+        ] {
+            let instruction = parse_code_element(code).unwrap();
+            let json = serde_json::to_string(&instruction).unwrap();
+            assert_eq!(
+                serde_json::from_str::<Instruction>(&json).unwrap(),
+                instruction
+            );
+        }
+        assert!(serde_json::from_str::<Instruction>(r#"{"jump": {"condition": "eq"}}"#).is_err());
+    }
+
+    #[test]
+    fn test_assembly_round_trip() {
+        let assembly = Assembly::try_from(
+            r#"
+    .data
+val:
+    .cell 5
+    .text
+__entry:
+    add @CPI0_0[0], r0, r1
+    add stack[@val], r1, r2
+    ret.ok r2
+    .rodata.cst32
+CPI0_0:
+    .cell -1
+"#
+            .to_owned(),
+        )
+        .unwrap();
+
+        let mut compiled = assembly.clone();
+        compiled.compile_to_bytecode().unwrap();
+        let json = serde_json::to_string(&compiled).unwrap();
+        for output in [
+            "pc_line_mapping",
+            "function_labels",
+            "deduplicated_constants",
+        ] {
+            assert!(!json.contains(output));
+        }
+        let restored: Assembly = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.assembly_code, assembly.assembly_code);
+        assert_eq!(restored.metadata_hash, assembly.metadata_hash);
+        assert_eq!(restored.config, assembly.config);
+
+        let linked = assembly.link::<8, EncodingModeProduction>().unwrap();
+        let restored = restored.link::<8, EncodingModeProduction>().unwrap();
+        assert_eq!(restored.instructions(), linked.instructions());
+        assert_eq!(restored.bytecode(), linked.bytecode());
+    }
+
+    #[test]
+    fn test_linked_assembly_round_trip() {
+        let assembly = Assembly::try_from(
+            r#"
+    .text
+__entry:
+    add @CPI0_0[0], r0, r1
+    near_call r0, @f, @DEFAULT_UNWIND
+    ret.ok r1
+f:
+    add 1, r1, r1
+    ret.ok r0
+    .rodata.cst32
+CPI0_0:
+    .cell -1
+"#
+            .to_owned(),
+        )
+        .unwrap();
+        let linked = assembly.link::<8, EncodingModeProduction>().unwrap();
+
+        let json = serde_json::to_value(&linked).unwrap();
+        assert_eq!(json["isa_version"], serde_json::json!(linked.isa_version.0));
+        assert_eq!(
+            json["bytecode"][0],
+            serde_json::json!(format!("0x{}", hex::encode(linked.bytecode()[0])))
+        );
+        let restored: LinkedAssembly<8, EncodingModeProduction> =
+            serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(restored.bytecode(), linked.bytecode());
+        assert_eq!(restored.instructions(), linked.instructions());
+        assert_eq!(restored.opcodes().len(), linked.opcodes().len());
+        assert_eq!(restored.pc_line_mapping(), linked.pc_line_mapping());
+        assert_eq!(restored.function_labels(), linked.function_labels());
+        assert_eq!(restored.function_sizes(), linked.function_sizes());
+        assert_eq!(restored.globals_size(), linked.globals_size());
+
+        let mut tampered = json;
+        tampered["bytecode"][0] = serde_json::json!(format!("0x{}", hex::encode([0xffu8; 32])));
+        assert!(
+            serde_json::from_value::<LinkedAssembly<8, EncodingModeProduction>>(tampered).is_err()
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//!
+//! The environment of the simulated contract.
+//!
+
+use num_bigint::BigUint;
+use std::collections::HashMap;
+use zkevm_opcode_defs::FarCallOpcode;
+
+///
+/// The call to another contract made by `far_call`.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct FarCallRequest {
+    /// The call variant.
+    pub variant: FarCallOpcode,
+    /// The called address.
+    pub address: BigUint,
+    /// The raw ABI word.
+    pub abi: BigUint,
+    /// The forwarded calldata.
+    pub calldata: Vec<u8>,
+    /// Whether the call is static.
+    pub is_static: bool,
+    /// Whether the call is made to another shard.
+    pub is_call_shard: bool,
+}
+
+///
+/// The result of the far call.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FarCallResult {
+    /// The call returned the data.
+    Ok(Vec<u8>),
+    /// The call reverted with the data.
+    Revert(Vec<u8>),
+    /// The call panicked.
+    Panic,
+}
+
+///
+/// The environment the simulated contract talks to.
+///
+pub trait Host {
+    /// The address of the executed contract.
+    fn this_address(&self) -> BigUint;
+
+    /// The address of the caller.
+    fn caller(&self) -> BigUint;
+
+    /// The address the executed code belongs to.
+    fn code_address(&self) -> BigUint;
+
+    fn storage_read(&mut self, key: &BigUint) -> BigUint;
+
+    fn storage_write(&mut self, key: BigUint, value: BigUint);
+
+    fn event(&mut self, key: BigUint, value: BigUint, is_initial: bool);
+
+    fn to_l1_message(&mut self, key: BigUint, value: BigUint, is_initial: bool);
+
+    /// Returns the value written to the destination register.
+    fn precompile_call(&mut self, abi: BigUint, ergs: BigUint) -> BigUint;
+
+    fn far_call(&mut self, request: FarCallRequest) -> FarCallResult;
+}
+
+///
+/// The host which keeps everything in memory, so the effects can be inspected in tests.
+///
+#[derive(Debug, Clone, Default)]
+pub struct MockHost {
+    pub this_address: BigUint,
+    pub caller: BigUint,
+    pub code_address: BigUint,
+    pub storage: HashMap<BigUint, BigUint>,
+    /// The key, the value and the initial flag of every event.
+    pub events: Vec<(BigUint, BigUint, bool)>,
+    /// The key, the value and the initial flag of every L1 message.
+    pub l1_messages: Vec<(BigUint, BigUint, bool)>,
+    pub far_calls: Vec<FarCallRequest>,
+    /// The results by the called address. The other addresses return no data.
+    pub far_call_results: HashMap<BigUint, FarCallResult>,
+}
+
+impl Host for MockHost {
+    fn this_address(&self) -> BigUint {
+        self.this_address.clone()
+    }
+
+    fn caller(&self) -> BigUint {
+        self.caller.clone()
+    }
+
+    fn code_address(&self) -> BigUint {
+        self.code_address.clone()
+    }
+
+    fn storage_read(&mut self, key: &BigUint) -> BigUint {
+        self.storage.get(key).cloned().unwrap_or_default()
+    }
+
+    fn storage_write(&mut self, key: BigUint, value: BigUint) {
+        self.storage.insert(key, value);
+    }
+
+    fn event(&mut self, key: BigUint, value: BigUint, is_initial: bool) {
+        self.events.push((key, value, is_initial));
+    }
+
+    fn to_l1_message(&mut self, key: BigUint, value: BigUint, is_initial: bool) {
+        self.l1_messages.push((key, value, is_initial));
+    }
+
+    fn precompile_call(&mut self, _abi: BigUint, _ergs: BigUint) -> BigUint {
+        BigUint::default()
+    }
+
+    fn far_call(&mut self, request: FarCallRequest) -> FarCallResult {
+        let result = self
+            .far_call_results
+            .get(&request.address)
+            .cloned()
+            .unwrap_or(FarCallResult::Ok(vec![]));
+        self.far_calls.push(request);
+
+        result
+    }
+}
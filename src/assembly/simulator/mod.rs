@@ -0,0 +1,1071 @@
+//!
+//! The reference simulator of the assembled programs.
+//!
+//! It executes the linked instructions closely enough to check the registers, the memory
+//! and the return values of the hand-written assembly in tests. Ergs are not accounted.
+//! The program is either the linked assembly, the encoded opcodes or the linked instructions.
+//!
+
+pub mod host;
+
+use self::host::{FarCallRequest, FarCallResult, Host};
+use super::instruction::metadata::{InstructionMetadata, OperandRef};
+use super::instruction::Instruction;
+use super::linking::LinkedAssembly;
+use super::operand::{
+    ConstantOperand, FullOperand, GenericOperand, GlobalVariable, RegisterOperand,
+};
+use crate::error::SimulationError;
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+use std::collections::HashMap;
+use zkevm_opcode_defs::decoding::{AllowedPcOrImm, VmEncodingMode};
+use zkevm_opcode_defs::*;
+
+lazy_static::lazy_static! {
+    static ref WORD_MODULUS: BigUint = BigUint::one() << 256u32;
+    static ref LOW_U128_MASK: BigUint = (BigUint::one() << 128u32) - 1u32;
+}
+
+/// The number of the registers including `r0`.
+pub const SIMULATED_REGISTERS_COUNT: usize = 16;
+
+/// The memory page of the calldata.
+pub const CALLDATA_PAGE: u32 = 1;
+/// The memory page of the heap.
+pub const HEAP_PAGE: u32 = 2;
+/// The memory page of the auxiliary heap.
+pub const AUX_HEAP_PAGE: u32 = 3;
+
+/// The ergs reported by `context.ergs_left`.
+const SIMULATED_ERGS_LEFT: u32 = u32::MAX;
+
+/// The position of the forwarding mode byte in the far call and return ABI.
+const FORWARDING_MODE_SHIFT: u32 = 232;
+
+///
+/// The flags set by the instructions with the `!` modifier.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Flags {
+    /// The less than or overflow flag.
+    pub lt_of: bool,
+    /// The equality flag.
+    pub eq: bool,
+    /// The greater than flag.
+    pub gt: bool,
+}
+
+impl Flags {
+    fn arithmetic(overflow: bool, result: &BigUint) -> Self {
+        let eq = result.is_zero();
+        Self {
+            lt_of: overflow,
+            eq,
+            gt: !overflow && !eq,
+        }
+    }
+
+    fn bitwise(result: &BigUint) -> Self {
+        Self {
+            lt_of: false,
+            eq: result.is_zero(),
+            gt: false,
+        }
+    }
+
+    pub fn satisfy(&self, condition: Condition) -> bool {
+        match condition {
+            Condition::Always => true,
+            Condition::Gt => self.gt,
+            Condition::Lt => self.lt_of,
+            Condition::Eq => self.eq,
+            Condition::Ge => self.gt || self.eq,
+            Condition::Le => self.lt_of || self.eq,
+            Condition::Ne => !self.eq,
+            Condition::GtOrLt => self.gt || self.lt_of,
+        }
+    }
+}
+
+///
+/// The frame pushed by `near_call`.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// The PC of the called function.
+    pub entry_pc: usize,
+    /// The PC to continue from on `ret.ok`.
+    pub return_pc: usize,
+    /// The PC to continue from on `ret.revert` and `ret.panic`.
+    pub exception_handler: usize,
+    /// The stack pointer to restore on return.
+    pub sp: u64,
+}
+
+///
+/// The 128-bit pointer into the memory page, stored in the lowest bits of a word.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FatPointer {
+    pub offset: u32,
+    pub memory_page: u32,
+    pub start: u32,
+    pub length: u32,
+}
+
+impl FatPointer {
+    pub fn from_word(word: &BigUint) -> Self {
+        let low = (word & &*LOW_U128_MASK)
+            .to_u128()
+            .expect("masked to 128 bits");
+        Self {
+            offset: low as u32,
+            memory_page: (low >> 32) as u32,
+            start: (low >> 64) as u32,
+            length: (low >> 96) as u32,
+        }
+    }
+
+    pub fn to_word(&self) -> BigUint {
+        let low = (self.offset as u128)
+            | (self.memory_page as u128) << 32
+            | (self.start as u128) << 64
+            | (self.length as u128) << 96;
+        BigUint::from(low)
+    }
+}
+
+///
+/// The way the program has finished.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutcomeKind {
+    Ok,
+    Revert,
+    Panic,
+}
+
+///
+/// The result of the finished program.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionOutcome {
+    pub kind: OutcomeKind,
+    /// The data forwarded by the outermost `ret`.
+    pub return_data: Vec<u8>,
+}
+
+///
+/// The state after the executed step.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionStatus {
+    Running,
+    Finished(ExecutionOutcome),
+}
+
+/// The exception which unwinds to the handler of the current frame.
+struct Panic;
+
+///
+/// The reference simulator.
+///
+#[derive(Debug, Clone)]
+pub struct Simulator<H: Host> {
+    instructions: Vec<Instruction>,
+    code_page: Vec<[u8; 32]>,
+    address_mask: u64,
+    registers: [BigUint; SIMULATED_REGISTERS_COUNT],
+    flags: Flags,
+    pc: usize,
+    sp: u64,
+    frames: Vec<Frame>,
+    stack: HashMap<u64, BigUint>,
+    pages: HashMap<u32, Vec<u8>>,
+    next_page: u32,
+    context_u128: u128,
+    ergs_per_pubdata_byte: u32,
+    tx_number: u64,
+    steps: usize,
+    outcome: Option<ExecutionOutcome>,
+    host: H,
+}
+
+impl<H: Host> Simulator<H> {
+    pub fn new<const N: usize, E: VmEncodingMode<N>>(
+        assembly: &LinkedAssembly<N, E>,
+        host: H,
+    ) -> Self {
+        Self::with_code::<N, E>(
+            assembly.instructions().to_vec(),
+            assembly.bytecode().to_vec(),
+            host,
+        )
+    }
+
+    ///
+    /// Runs the encoded opcodes, which are also the code page, packed `32 / N` into a word.
+    /// There is no data section, so the code page only holds the opcodes themselves.
+    ///
+    pub fn from_opcodes<const N: usize, E: VmEncodingMode<N>>(
+        opcodes: &[DecodedOpcode<N, E>],
+        host: H,
+    ) -> Result<Self, SimulationError> {
+        let instructions = opcodes
+            .iter()
+            .enumerate()
+            .map(|(pc, opcode)| {
+                Instruction::decode(opcode)
+                    .map_err(|error| SimulationError::UndecodableOpcode(pc, error))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let code_page = opcodes
+            .chunks(32 / N)
+            .map(|chunk| {
+                let mut word = [0u8; 32];
+                for (index, opcode) in chunk.iter().enumerate() {
+                    word[N * index..N * (index + 1)].copy_from_slice(&opcode.serialize_as_bytes());
+                }
+                word
+            })
+            .collect();
+
+        Ok(Self::with_code::<N, E>(instructions, code_page, host))
+    }
+
+    ///
+    /// Runs the linked instructions for the encoding mode `E`. There is no code page, so
+    /// the reads from it return zero, and the operands still referring to labels are rejected.
+    ///
+    pub fn from_instructions<const N: usize, E: VmEncodingMode<N>>(
+        instructions: Vec<Instruction>,
+        host: H,
+    ) -> Result<Self, SimulationError> {
+        let label = instructions
+            .iter()
+            .flat_map(|instruction| instruction.operands())
+            .find_map(|operand| match operand.operand {
+                OperandRef::Full(FullOperand::Constant(ConstantOperand { label, .. }))
+                | OperandRef::Full(FullOperand::GlobalVariable(GlobalVariable { label, .. })) => {
+                    Some(label.clone())
+                }
+                _ => None,
+            });
+        if let Some(label) = label {
+            return Err(SimulationError::UnknownLabel(label));
+        }
+
+        Ok(Self::with_code::<N, E>(instructions, vec![], host))
+    }
+
+    fn with_code<const N: usize, E: VmEncodingMode<N>>(
+        instructions: Vec<Instruction>,
+        code_page: Vec<[u8; 32]>,
+        host: H,
+    ) -> Self {
+        Self {
+            instructions,
+            code_page,
+            address_mask: E::PcOrImm::max().as_u64(),
+            registers: Default::default(),
+            flags: Flags::default(),
+            pc: 0,
+            sp: 0,
+            frames: vec![],
+            stack: HashMap::new(),
+            pages: HashMap::new(),
+            next_page: AUX_HEAP_PAGE + 1,
+            context_u128: 0,
+            ergs_per_pubdata_byte: 0,
+            tx_number: 0,
+            steps: 0,
+            outcome: None,
+            host,
+        }
+    }
+
+    ///
+    /// Puts the calldata into its page and the pointer to it into `r1`, as the VM does on a call.
+    ///
+    pub fn with_calldata(mut self, calldata: Vec<u8>) -> Self {
+        let pointer = FatPointer {
+            offset: 0,
+            memory_page: CALLDATA_PAGE,
+            start: 0,
+            length: calldata.len() as u32,
+        };
+        self.pages.insert(CALLDATA_PAGE, calldata);
+        self.registers[1] = pointer.to_word();
+        self
+    }
+
+    ///
+    /// Executes the instruction at the current PC.
+    ///
+    pub fn step(&mut self) -> Result<ExecutionStatus, SimulationError> {
+        if let Some(outcome) = self.outcome.as_ref() {
+            return Ok(ExecutionStatus::Finished(outcome.clone()));
+        }
+
+        self.steps += 1;
+        let instruction = match self.instructions.get(self.pc) {
+            Some(instruction) => instruction.clone(),
+            None => {
+                // the words past the code are never valid instructions
+                self.unwind();
+                return Ok(self.status());
+            }
+        };
+
+        if !self.flags.satisfy(instruction.condition()) {
+            self.pc += 1;
+            return Ok(ExecutionStatus::Running);
+        }
+
+        if self.execute(instruction).is_err() {
+            self.unwind();
+        }
+
+        Ok(self.status())
+    }
+
+    ///
+    /// Executes the program until it returns from the outermost frame.
+    ///
+    pub fn run(&mut self, max_steps: usize) -> Result<ExecutionOutcome, SimulationError> {
+        for _ in 0..max_steps {
+            if let ExecutionStatus::Finished(outcome) = self.step()? {
+                return Ok(outcome);
+            }
+        }
+
+        Err(SimulationError::StepLimitExceeded(max_steps))
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    pub fn sp(&self) -> u64 {
+        self.sp
+    }
+
+    pub fn flags(&self) -> Flags {
+        self.flags
+    }
+
+    /// The near call frames, the innermost one is the last.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    pub fn registers(&self) -> &[BigUint] {
+        &self.registers
+    }
+
+    pub fn register(&self, index: usize) -> BigUint {
+        self.registers.get(index).cloned().unwrap_or_default()
+    }
+
+    pub fn stack_word(&self, address: u64) -> BigUint {
+        self.stack.get(&address).cloned().unwrap_or_default()
+    }
+
+    /// The contents of the memory page. The bytes past the end are zero.
+    pub fn page(&self, page: u32) -> &[u8] {
+        self.pages.get(&page).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn heap(&self) -> &[u8] {
+        self.page(HEAP_PAGE)
+    }
+
+    pub fn aux_heap(&self) -> &[u8] {
+        self.page(AUX_HEAP_PAGE)
+    }
+
+    pub fn context_u128(&self) -> u128 {
+        self.context_u128
+    }
+
+    pub fn ergs_per_pubdata_byte(&self) -> u32 {
+        self.ergs_per_pubdata_byte
+    }
+
+    pub fn tx_number(&self) -> u64 {
+        self.tx_number
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn outcome(&self) -> Option<&ExecutionOutcome> {
+        self.outcome.as_ref()
+    }
+
+    pub fn host(&self) -> &H {
+        &self.host
+    }
+
+    pub fn host_mut(&mut self) -> &mut H {
+        &mut self.host
+    }
+
+    fn status(&self) -> ExecutionStatus {
+        match self.outcome.as_ref() {
+            Some(outcome) => ExecutionStatus::Finished(outcome.clone()),
+            None => ExecutionStatus::Running,
+        }
+    }
+
+    ///
+    /// Continues from the exception handler of the current frame, or finishes the program.
+    ///
+    fn unwind(&mut self) {
+        match self.frames.pop() {
+            Some(frame) => {
+                self.pc = frame.exception_handler;
+                self.sp = frame.sp;
+                self.flags = Flags {
+                    lt_of: true,
+                    ..Flags::default()
+                };
+            }
+            None => {
+                self.outcome = Some(ExecutionOutcome {
+                    kind: OutcomeKind::Panic,
+                    return_data: vec![],
+                });
+            }
+        }
+    }
+
+    fn execute(&mut self, instruction: Instruction) -> Result<(), Panic> {
+        let mut next_pc = self.pc + 1;
+
+        match instruction {
+            Instruction::Invalid(_) => return Err(Panic),
+            Instruction::Nop(instr) => {
+                if let ImmMemHandlerFlags::UseStackWithPushPop = instr.source_1.r#type {
+                    let offset = self.offset(&instr.source_1);
+                    self.sp = self.sp.wrapping_sub(offset) & self.address_mask;
+                }
+                if let ImmMemHandlerFlags::UseStackWithPushPop = instr.dest_1.r#type {
+                    let offset = self.offset(&instr.dest_1);
+                    self.sp = self.sp.wrapping_add(offset) & self.address_mask;
+                }
+            }
+            Instruction::Add(instr) => {
+                let sum = self.read_source(&instr.source_1) + self.read_register(&instr.source_2);
+                let overflow = sum >= *WORD_MODULUS;
+                let result = sum % &*WORD_MODULUS;
+                self.set_flags(
+                    instr.set_flags_option.0,
+                    Flags::arithmetic(overflow, &result),
+                );
+                self.write_destination(&instr.destination, result);
+            }
+            Instruction::Sub(instr) => {
+                let (a, b) =
+                    self.read_sources(&instr.source_1, &instr.source_2, instr.swap_operands);
+                let overflow = a < b;
+                let result = (a + &*WORD_MODULUS - b) % &*WORD_MODULUS;
+                self.set_flags(
+                    instr.set_flags_option.0,
+                    Flags::arithmetic(overflow, &result),
+                );
+                self.write_destination(&instr.destination, result);
+            }
+            Instruction::Mul(instr) => {
+                let product =
+                    self.read_source(&instr.source_1) * self.read_register(&instr.source_2);
+                let low = &product % &*WORD_MODULUS;
+                let high = product >> 256u32;
+                let flags = Flags::arithmetic(!high.is_zero(), &low);
+                self.set_flags(instr.set_flags_option.0, flags);
+                self.write_destination(&instr.destination_1, low);
+                self.write_register(&instr.destination_2, high);
+            }
+            Instruction::Div(instr) => {
+                let (a, b) =
+                    self.read_sources(&instr.source_1, &instr.source_2, instr.swap_operands);
+                let (quotient, remainder, flags) = if b.is_zero() {
+                    let flags = Flags {
+                        lt_of: true,
+                        ..Flags::default()
+                    };
+                    (BigUint::zero(), BigUint::zero(), flags)
+                } else {
+                    let (quotient, remainder) = (&a / &b, &a % &b);
+                    let flags = Flags {
+                        lt_of: false,
+                        eq: quotient.is_zero(),
+                        gt: remainder.is_zero(),
+                    };
+                    (quotient, remainder, flags)
+                };
+                self.set_flags(instr.set_flags_option.0, flags);
+                self.write_destination(&instr.quotient_destination, quotient);
+                self.write_register(&instr.remainder_destination, remainder);
+            }
+            Instruction::Shift(instr) => {
+                let (value, shift) =
+                    self.read_sources(&instr.source_1, &instr.source_2, instr.swap_operands);
+                let shift = (shift % 256u32).to_u32().expect("reduced modulo 256");
+                let result = match instr.variant {
+                    ShiftOpcode::Shl => (&value << shift) % &*WORD_MODULUS,
+                    ShiftOpcode::Shr => &value >> shift,
+                    ShiftOpcode::Rol => {
+                        ((&value << shift) | (&value >> (256 - shift))) % &*WORD_MODULUS
+                    }
+                    ShiftOpcode::Ror => {
+                        ((&value >> shift) | (&value << (256 - shift))) % &*WORD_MODULUS
+                    }
+                };
+                self.set_flags(instr.set_flags_option.0, Flags::bitwise(&result));
+                self.write_destination(&instr.destination, result);
+            }
+            Instruction::Bitwise(instr) => {
+                let a = self.read_source(&instr.source_1);
+                let b = self.read_register(&instr.source_2);
+                let result = match instr.op_type {
+                    BinopOpcode::And => a & b,
+                    BinopOpcode::Or => a | b,
+                    BinopOpcode::Xor => a ^ b,
+                };
+                self.set_flags(instr.set_flags_option.0, Flags::bitwise(&result));
+                self.write_destination(&instr.destination, result);
+            }
+            Instruction::Jump(instr) => {
+                next_pc = self.read_pc(&instr.destination_true);
+            }
+            Instruction::Context(instr) => {
+                let result = match instr.field {
+                    ContextOpcode::This => Some(self.host.this_address()),
+                    ContextOpcode::Caller => Some(self.host.caller()),
+                    ContextOpcode::CodeAddress => Some(self.host.code_address()),
+                    ContextOpcode::Meta => Some(BigUint::zero()),
+                    ContextOpcode::ErgsLeft => Some(BigUint::from(SIMULATED_ERGS_LEFT)),
+                    ContextOpcode::Sp => Some(BigUint::from(self.sp)),
+                    ContextOpcode::GetContextU128 => Some(BigUint::from(self.context_u128)),
+                    ContextOpcode::SetContextU128 => {
+                        let value = self.read_register(&instr.source_location);
+                        self.context_u128 = (value & &*LOW_U128_MASK)
+                            .to_u128()
+                            .expect("masked to 128 bits");
+                        None
+                    }
+                    ContextOpcode::SetErgsPerPubdataByte => {
+                        let value = self.read_register(&instr.source_location);
+                        self.ergs_per_pubdata_byte = low_u64(&value) as u32;
+                        None
+                    }
+                    ContextOpcode::IncrementTxNumber => {
+                        self.tx_number += 1;
+                        None
+                    }
+                };
+                if let Some(result) = result {
+                    self.write_register(&instr.destination_location, result);
+                }
+            }
+            Instruction::Ptr(instr) => {
+                let (pointer, value) =
+                    self.read_sources(&instr.source_1, &instr.source_2, instr.swap_operands);
+                let mut fat_pointer = FatPointer::from_word(&pointer);
+                let high = &pointer - (&pointer & &*LOW_U128_MASK);
+                let result = match instr.op_type {
+                    PtrOpcode::Add | PtrOpcode::Sub | PtrOpcode::Shrink => {
+                        let value = value.to_u32().ok_or(Panic)?;
+                        let field = match instr.op_type {
+                            PtrOpcode::Shrink => &mut fat_pointer.length,
+                            _ => &mut fat_pointer.offset,
+                        };
+                        *field = match instr.op_type {
+                            PtrOpcode::Add => field.checked_add(value),
+                            _ => field.checked_sub(value),
+                        }
+                        .ok_or(Panic)?;
+                        high + fat_pointer.to_word()
+                    }
+                    PtrOpcode::Pack => {
+                        if !(&value & &*LOW_U128_MASK).is_zero() {
+                            return Err(Panic);
+                        }
+                        (pointer & &*LOW_U128_MASK) + value
+                    }
+                };
+                self.write_destination(&instr.destination, result);
+            }
+            Instruction::Log(instr) => {
+                let key = self.read_register(&instr.key);
+                let value = self.read_register(&instr.value_source);
+                match instr.log_type {
+                    LogOpcode::StorageRead => {
+                        let result = self.host.storage_read(&key);
+                        self.write_register(&instr.value_destination, result);
+                    }
+                    LogOpcode::StorageWrite => self.host.storage_write(key, value),
+                    LogOpcode::ToL1Message => self.host.to_l1_message(key, value, instr.is_initial),
+                    LogOpcode::Event => self.host.event(key, value, instr.is_initial),
+                    LogOpcode::PrecompileCall => {
+                        let result = self.host.precompile_call(key, value);
+                        self.write_register(&instr.value_destination, result);
+                    }
+                }
+            }
+            Instruction::NearCall(instr) => {
+                let entry_pc = self.read_pc(&instr.destination);
+                let exception_handler = self.read_pc(&instr.exception_handler);
+                self.frames.push(Frame {
+                    entry_pc,
+                    return_pc: next_pc,
+                    exception_handler,
+                    sp: self.sp,
+                });
+                self.flags = Flags::default();
+                next_pc = entry_pc;
+            }
+            Instruction::FarCall(instr) => {
+                let abi = self.read_register(&instr.source_for_meta_args);
+                let address = self.read_register(&instr.source_for_address_to_call);
+                let exception_handler = self.read_pc(&instr.exception_handler);
+                let calldata = self.forwarded_data(&abi)?;
+                let request = FarCallRequest {
+                    variant: instr.variant,
+                    address,
+                    abi,
+                    calldata,
+                    is_static: instr.is_static,
+                    is_call_shard: instr.is_call_shard,
+                };
+                self.flags = Flags::default();
+                match self.host.far_call(request) {
+                    FarCallResult::Ok(data) => {
+                        self.registers[1] = self.new_page(data);
+                    }
+                    FarCallResult::Revert(data) => {
+                        self.registers[1] = self.new_page(data);
+                        next_pc = exception_handler;
+                    }
+                    FarCallResult::Panic => {
+                        self.registers[1] = BigUint::zero();
+                        self.flags.lt_of = true;
+                        next_pc = exception_handler;
+                    }
+                }
+            }
+            Instruction::Ret(instr) => match self.frames.pop() {
+                Some(frame) => {
+                    self.sp = frame.sp;
+                    self.flags = Flags {
+                        lt_of: matches!(instr.variant, RetOpcode::Panic),
+                        ..Flags::default()
+                    };
+                    next_pc = match (instr.label_for_return.as_ref(), instr.variant) {
+                        (Some(label), _) if instr.is_to_label => self.read_pc(label),
+                        (_, RetOpcode::Ok) => frame.return_pc,
+                        (_, RetOpcode::Revert | RetOpcode::Panic) => frame.exception_handler,
+                    };
+                }
+                None => {
+                    let kind = match instr.variant {
+                        RetOpcode::Ok => OutcomeKind::Ok,
+                        RetOpcode::Revert => OutcomeKind::Revert,
+                        RetOpcode::Panic => OutcomeKind::Panic,
+                    };
+                    let return_data = match kind {
+                        OutcomeKind::Panic => vec![],
+                        _ => {
+                            let abi = self.read_register(&instr.source_for_meta_args);
+                            self.forwarded_data(&abi)?
+                        }
+                    };
+                    self.outcome = Some(ExecutionOutcome { kind, return_data });
+                    return Ok(());
+                }
+            },
+            Instruction::UMA(instr) => {
+                let address = match instr.src_0.r#type {
+                    RegOrImmFlags::UseRegOnly => self.read_register(&instr.src_0.register),
+                    RegOrImmFlags::UseImm16Only => BigUint::from(instr.src_0.immediate),
+                };
+                match instr.uma_type {
+                    UMAOpcode::HeapRead | UMAOpcode::AuxHeapRead => {
+                        let page = match instr.uma_type {
+                            UMAOpcode::HeapRead => HEAP_PAGE,
+                            _ => AUX_HEAP_PAGE,
+                        };
+                        let offset = heap_offset(&address)?;
+                        let value = BigUint::from_bytes_be(&self.read_bytes(page, offset, 32));
+                        self.write_register(&instr.dst_0, value);
+                        if instr.increment_offset {
+                            self.write_register(&instr.dst_1, BigUint::from(offset + 32));
+                        }
+                    }
+                    UMAOpcode::HeapWrite | UMAOpcode::AuxHeapWrite => {
+                        let page = match instr.uma_type {
+                            UMAOpcode::HeapWrite => HEAP_PAGE,
+                            _ => AUX_HEAP_PAGE,
+                        };
+                        let offset = heap_offset(&address)?;
+                        let value = self.read_register(&instr.src_1);
+                        self.write_bytes(page, offset, &word_to_bytes(&value));
+                        if instr.increment_offset {
+                            self.write_register(&instr.dst_0, BigUint::from(offset + 32));
+                        }
+                    }
+                    UMAOpcode::FatPointerRead => {
+                        let mut pointer = FatPointer::from_word(&address);
+                        let mut bytes = [0u8; 32];
+                        if pointer.offset < pointer.length {
+                            let available = (pointer.length - pointer.offset).min(32) as usize;
+                            let start = pointer.start.checked_add(pointer.offset).ok_or(Panic)?;
+                            let data = self.read_bytes(pointer.memory_page, start, available);
+                            bytes[..available].copy_from_slice(&data);
+                        }
+                        self.write_register(&instr.dst_0, BigUint::from_bytes_be(&bytes));
+                        if instr.increment_offset {
+                            pointer.offset = pointer.offset.checked_add(32).ok_or(Panic)?;
+                            let high = &address - (&address & &*LOW_U128_MASK);
+                            self.write_register(&instr.dst_1, high + pointer.to_word());
+                        }
+                    }
+                }
+            }
+        }
+
+        self.pc = next_pc;
+
+        Ok(())
+    }
+
+    fn set_flags(&mut self, set_flags: bool, flags: Flags) {
+        if set_flags {
+            self.flags = flags;
+        }
+    }
+
+    fn read_register(&self, register: &RegisterOperand) -> BigUint {
+        match register {
+            RegisterOperand::Null => BigUint::zero(),
+            RegisterOperand::Register(index) => self.register(*index as usize),
+        }
+    }
+
+    fn write_register(&mut self, register: &RegisterOperand, value: BigUint) {
+        if let RegisterOperand::Register(index) = register {
+            if let Some(register) = self.registers.get_mut(*index as usize) {
+                *register = value;
+            }
+        }
+    }
+
+    /// The `reg + imm` part of the memory location.
+    fn offset(&self, operand: &GenericOperand) -> u64 {
+        low_u64(&self.read_register(&operand.register)).wrapping_add(operand.immediate)
+            & self.address_mask
+    }
+
+    fn read_source(&mut self, operand: &FullOperand) -> BigUint {
+        let operand = match operand {
+            FullOperand::Register(register) => return self.read_register(register),
+            FullOperand::Full(operand) => operand,
+            FullOperand::Constant(_) | FullOperand::GlobalVariable(_) => {
+                unreachable!("labels are resolved by the linker")
+            }
+        };
+
+        let offset = self.offset(operand);
+        match operand.r#type {
+            ImmMemHandlerFlags::UseRegOnly => self.read_register(&operand.register),
+            ImmMemHandlerFlags::UseImm16Only => BigUint::from(operand.immediate),
+            ImmMemHandlerFlags::UseStackWithPushPop => {
+                self.sp = self.sp.wrapping_sub(offset) & self.address_mask;
+                self.stack_word(self.sp)
+            }
+            ImmMemHandlerFlags::UseStackWithOffset => {
+                self.stack_word(self.sp.wrapping_sub(offset) & self.address_mask)
+            }
+            ImmMemHandlerFlags::UseAbsoluteOnStack => self.stack_word(offset),
+            ImmMemHandlerFlags::UseCodePage => self
+                .code_page
+                .get(offset as usize)
+                .map(|word| BigUint::from_bytes_be(word))
+                .unwrap_or_default(),
+        }
+    }
+
+    fn read_sources(
+        &mut self,
+        source_1: &FullOperand,
+        source_2: &RegisterOperand,
+        swap_operands: bool,
+    ) -> (BigUint, BigUint) {
+        let a = self.read_source(source_1);
+        let b = self.read_register(source_2);
+        if swap_operands {
+            (b, a)
+        } else {
+            (a, b)
+        }
+    }
+
+    fn read_pc(&mut self, operand: &FullOperand) -> usize {
+        (low_u64(&self.read_source(operand)) & self.address_mask) as usize
+    }
+
+    fn write_destination(&mut self, operand: &FullOperand, value: BigUint) {
+        let operand = match operand {
+            FullOperand::Register(register) => return self.write_register(register, value),
+            FullOperand::Full(operand) => operand,
+            FullOperand::Constant(_) | FullOperand::GlobalVariable(_) => {
+                unreachable!("labels are resolved by the linker")
+            }
+        };
+
+        let offset = self.offset(operand);
+        let address = match operand.r#type {
+            ImmMemHandlerFlags::UseRegOnly => return self.write_register(&operand.register, value),
+            ImmMemHandlerFlags::UseStackWithPushPop => {
+                let address = self.sp;
+                self.sp = self.sp.wrapping_add(offset) & self.address_mask;
+                address
+            }
+            ImmMemHandlerFlags::UseStackWithOffset => {
+                self.sp.wrapping_sub(offset) & self.address_mask
+            }
+            ImmMemHandlerFlags::UseAbsoluteOnStack => offset,
+            ImmMemHandlerFlags::UseImm16Only | ImmMemHandlerFlags::UseCodePage => {
+                unreachable!("rejected for destinations by the parser")
+            }
+        };
+        self.stack.insert(address, value);
+    }
+
+    fn read_bytes(&self, page: u32, start: u32, length: usize) -> Vec<u8> {
+        let memory = self.page(page);
+        (0..length)
+            .map(|index| {
+                memory
+                    .get(start as usize + index)
+                    .copied()
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    fn write_bytes(&mut self, page: u32, start: u32, bytes: &[u8]) {
+        let memory = self.pages.entry(page).or_default();
+        let end = start as usize + bytes.len();
+        if memory.len() < end {
+            memory.resize(end, 0);
+        }
+        memory[start as usize..end].copy_from_slice(bytes);
+    }
+
+    ///
+    /// Places the data into a new page and returns the pointer to it.
+    ///
+    fn new_page(&mut self, data: Vec<u8>) -> BigUint {
+        let page = self.next_page;
+        self.next_page += 1;
+        let pointer = FatPointer {
+            offset: 0,
+            memory_page: page,
+            start: 0,
+            length: data.len() as u32,
+        };
+        self.pages.insert(page, data);
+        pointer.to_word()
+    }
+
+    ///
+    /// Reads the data forwarded by `far_call` or `ret` according to the forwarding mode of the ABI.
+    ///
+    fn forwarded_data(&self, abi: &BigUint) -> Result<Vec<u8>, Panic> {
+        let pointer = FatPointer::from_word(abi);
+        let mode = low_u64(&(abi >> FORWARDING_MODE_SHIFT)) as u8;
+        let (page, start) = match mode {
+            0 => (HEAP_PAGE, pointer.start),
+            1 => (
+                pointer.memory_page,
+                pointer.start.checked_add(pointer.offset).ok_or(Panic)?,
+            ),
+            2 => (AUX_HEAP_PAGE, pointer.start),
+            _ => return Err(Panic),
+        };
+        let length = match mode {
+            1 => pointer.length.checked_sub(pointer.offset).ok_or(Panic)?,
+            _ => pointer.length,
+        };
+
+        Ok(self.read_bytes(page, start, length as usize))
+    }
+}
+
+fn low_u64(word: &BigUint) -> u64 {
+    word.iter_u64_digits().next().unwrap_or_default()
+}
+
+/// The heap offsets are 32-bit, and the whole word must fit.
+fn heap_offset(address: &BigUint) -> Result<u32, Panic> {
+    address
+        .to_u32()
+        .filter(|offset| offset.checked_add(32).is_some())
+        .ok_or(Panic)
+}
+
+fn word_to_bytes(word: &BigUint) -> [u8; 32] {
+    let bytes = word.to_bytes_be();
+    let mut result = [0u8; 32];
+    result[32 - bytes.len()..].copy_from_slice(&bytes);
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::host::MockHost;
+    use super::*;
+    use crate::assembly::Assembly;
+    use std::convert::TryFrom;
+    use zkevm_opcode_defs::decoding::EncodingModeProduction;
+
+    fn run(asm_text: &str, host: MockHost) -> Simulator<MockHost> {
+        let assembly = Assembly::try_from(asm_text.to_owned()).unwrap();
+        let linked = assembly.link::<8, EncodingModeProduction>().unwrap();
+        let mut simulator = Simulator::new(&linked, host);
+        simulator.run(1000).unwrap();
+        simulator
+    }
+
+    #[test]
+    fn test_arithmetic_stack_and_constants() {
+        let simulator = run(
+            r#"
+    .text
+    .globl  __entry
+    __entry:
+        add 5, r0, r1
+        add @CPI0_0[0], r1, r2
+        sub.s! 1, r2, r3
+        add r3, r0, stack+=[2]
+        add stack-[2], r0, r4
+        mul r4, r4, r5, r6
+        div.s 3, r5, r7, r8
+        shl.s 1, r7, r9
+        jump.ne @.BB0_1
+        add 1, r0, r10
+    .BB0_1:
+        ret.ok r0
+    .rodata
+    CPI0_0:
+        .cell 10
+    "#,
+            MockHost::default(),
+        );
+
+        assert_eq!(simulator.outcome().unwrap().kind, OutcomeKind::Ok);
+        assert_eq!(simulator.register(2), BigUint::from(15u32));
+        assert_eq!(simulator.register(3), BigUint::from(14u32));
+        assert_eq!(simulator.register(4), BigUint::from(14u32));
+        assert_eq!(simulator.register(5), BigUint::from(196u32));
+        assert_eq!(simulator.register(7), BigUint::from(65u32));
+        assert_eq!(simulator.register(8), BigUint::from(1u32));
+        assert_eq!(simulator.register(9), BigUint::from(130u32));
+        assert_eq!(simulator.register(10), BigUint::zero());
+        assert_eq!(simulator.sp(), 2);
+        assert_eq!(simulator.stack_word(0), BigUint::from(14u32));
+    }
+
+    #[test]
+    fn test_near_call_heap_and_storage() {
+        let simulator = run(
+            r#"
+    .text
+    .globl  __entry
+    __entry:
+        add 32, r0, r2
+        near_call r0, @store, @handler
+        near_call r0, @fail, @handler
+        ret.ok r0
+    store:
+        add 42, r0, r1
+        st.1 r2, r1
+        ld.1 r2, r3
+        sstore r2, r1
+        add r0, r0, stack+=[1]
+        ret.ok r0
+    fail:
+        ret.revert r0
+    handler:
+        add 7, r0, r4
+        add 32, r0, r1
+        shl.s 96, r1, r1
+        shl.s 64, r2, r2
+        add r2, r1, r1
+        ret.revert r1
+    "#,
+            MockHost::default(),
+        );
+
+        let outcome = simulator.outcome().unwrap();
+        assert_eq!(outcome.kind, OutcomeKind::Revert);
+        let mut expected = vec![0u8; 31];
+        expected.push(42);
+        assert_eq!(outcome.return_data, expected);
+        assert_eq!(simulator.register(3), BigUint::from(42u32));
+        assert_eq!(simulator.register(4), BigUint::from(7u32));
+        assert_eq!(simulator.sp(), 0);
+        assert_eq!(
+            simulator.host().storage.get(&BigUint::from(32u32)),
+            Some(&BigUint::from(42u32))
+        );
+    }
+
+    #[test]
+    fn test_opcodes_and_instructions() {
+        let assembly = Assembly::try_from(
+            r#"
+    .text
+    __entry:
+        add 5, r0, r1
+        near_call r0, @double, @DEFAULT_UNWIND
+        ret.ok r0
+    double:
+        add r1, r1, r1
+        ret.ok r0
+    "#
+            .to_owned(),
+        )
+        .unwrap();
+        let linked = assembly.link::<8, EncodingModeProduction>().unwrap();
+
+        let mut simulator = Simulator::from_opcodes(linked.opcodes(), MockHost::default()).unwrap();
+        assert_eq!(simulator.run(100).unwrap().kind, OutcomeKind::Ok);
+        assert_eq!(simulator.register(1), BigUint::from(10u32));
+
+        let mut simulator = Simulator::from_instructions::<8, EncodingModeProduction>(
+            linked.instructions().to_vec(),
+            MockHost::default(),
+        )
+        .unwrap();
+        assert_eq!(simulator.run(100).unwrap().kind, OutcomeKind::Ok);
+        assert_eq!(simulator.register(1), BigUint::from(10u32));
+
+        let unlinked = assembly
+            .code_elements()
+            .map(|element| element.instruction().clone())
+            .collect();
+        assert!(matches!(
+            Simulator::from_instructions::<8, EncodingModeProduction>(unlinked, MockHost::default()),
+            Err(SimulationError::UnknownLabel(label)) if label == "double"
+        ));
+    }
+}
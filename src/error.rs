@@ -2,6 +2,7 @@
 //! The common error.
 //!
 
+use crate::assembly::config::LandingPadPolicy;
 use crate::assembly::operand::{FullOperand, NonMemoryOperand};
 use std::collections::HashMap;
 use thiserror::Error;
@@ -19,6 +20,8 @@ pub enum Error {
     FileReading(std::io::Error),
     #[error(transparent)]
     AssemblyParseError(#[from] AssemblyParseError),
+    #[error(transparent)]
+    InstructionReadError(#[from] InstructionReadError),
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -37,6 +40,14 @@ pub enum AssemblyParseError {
     DuplicateLabel(String),
     #[error("there is no label `{0}` in data section of functions")]
     LabelNotFound(String),
+    #[error("label `{0}` is called, but not declared with `.type {0},@function`")]
+    NotAFunction(String),
+    #[error("the entry `{0}` does not end in an unconditional return, jump or panic")]
+    EntryNotTerminated(String),
+    #[error("the code before the entry `{0}` falls through into it")]
+    EntryFallenInto(String),
+    #[error("the `.landing_pads` directive selects the {0:?} policy, but the configuration selects {1:?}")]
+    LandingPadPolicyConflict(LandingPadPolicy, LandingPadPolicy),
     #[error("Label {1} was tried to be used for either PC or constant at offset {0} that is more than `{2}` addressable space")]
     CodeIsTooLong(usize, String, u64),
 }
@@ -126,6 +137,41 @@ pub enum InstructionReadError {
     UnexpectedSubtraction,
     #[error("integer overflow when computing the immediate")]
     IntegerOverflow,
+    /// The register beyond the register file.
+    #[error("register `{0}` is out of range, the registers are `r0` to `r{1}`")]
+    RegisterOutOfRange(String, usize),
+    /// The instruction without side effects, whose only destination is `r0`.
+    #[error("the result of `{0}` is written to `r0` and discarded")]
+    DiscardedResult(String),
+    /// The malformed `.reg` or `.unreg` directive.
+    #[error("invalid register alias directive `{0}`")]
+    InvalidRegisterAlias(String),
+    #[error("register alias `{0}` is already defined, `.unreg` it first")]
+    DuplicateRegisterAlias(String),
+    #[error("unknown register alias `{0}`")]
+    UnknownRegisterAlias(String),
+    #[error("invalid landing pad directive `{0}`")]
+    InvalidLandingPadDirective(String),
+    #[error("invalid symbol directive `{0}`")]
+    InvalidSymbolDirective(String),
+    /// The malformed numeric or character literal.
+    #[error("invalid {radix} literal `{literal}`")]
+    InvalidLiteral {
+        /// The literal as written.
+        literal: String,
+        /// The radix the literal was read in.
+        radix: &'static str,
+    },
+    /// The literal that does not fit into its location.
+    #[error("{radix} literal `{literal}` does not fit into {bits} bits")]
+    LiteralTooLarge {
+        /// The literal as written.
+        literal: String,
+        /// The radix the literal was read in.
+        radix: &'static str,
+        /// The bit width of the location.
+        bits: u64,
+    },
     #[error("unknown symbol or label `{0}`")]
     UnknownLabel(String),
     #[error("unknown mnemonic `{0}`")]
@@ -140,6 +186,10 @@ pub enum InstructionReadError {
     TooManyOpcodes(u64, u64),
     #[error("code is too long, can address {0} words at maximum, encountered {1}")]
     CodeIsTooLong(u64, u64),
+    #[error("cannot decode the opcode {0}")]
+    UndecodableOpcode(String),
+    #[error("`{0}` is not available in ISA v{1}")]
+    NotAvailableInIsaVersion(&'static str, u8),
     // #[error("opcode has specific requirements for source and destination for it's variant `{0}`")]
     // UnknownSourceOrDestination(String),
 }
@@ -163,3 +213,15 @@ pub enum BinaryParseError {
     #[error("Supported context fields indices: 0-5")]
     UnknownContextField,
 }
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SimulationError {
+    #[error("the program did not finish in {0} steps")]
+    StepLimitExceeded(usize),
+    #[error("label `{0}` is not found in the code")]
+    UnknownLabel(String),
+    #[error("no instruction is mapped to the line {0}")]
+    NoCodeAtLine(usize),
+    #[error("cannot decode the opcode at PC {0}: {1}")]
+    UndecodableOpcode(usize, InstructionReadError),
+}
@@ -19,21 +19,64 @@ pub use self::assembly::instruction::bitwise::Bitwise as BitwiseInstruction;
 pub use self::assembly::instruction::context::Context as ContextInstruction;
 pub use self::assembly::instruction::div::Div as DivInstruction;
 pub use self::assembly::instruction::far_call::FarCall as ExternalCallInstruction;
+pub use self::assembly::instruction::invalid::Invalid as InvalidInstruction;
 pub use self::assembly::instruction::jump::Jump as JumpInstruction;
+pub use self::assembly::instruction::log::Log as LogInstruction;
 pub use self::assembly::instruction::mul::Mul as MulInstruction;
 pub use self::assembly::instruction::near_call::NearCall as LocalCallInstruction;
 pub use self::assembly::instruction::nop::Nop as NopInstruction;
+pub use self::assembly::instruction::ptr::Ptr as PtrInstruction;
 pub use self::assembly::instruction::ret::Ret as ReturnInstruction;
 pub use self::assembly::instruction::shift::Shift as ShiftInstruction;
 pub use self::assembly::instruction::sub::Sub as SubInstruction;
+pub use self::assembly::instruction::uma::UMA as UMAInstruction;
 
+pub use self::assembly::analysis::cfg::Function as CfgFunction;
+pub use self::assembly::analysis::cfg::{BasicBlock, ControlFlowGraph};
+pub use self::assembly::analysis::cost::{BlockCost, CostReport, FunctionCost, InstructionCost};
+pub use self::assembly::analysis::liveness::{
+    BlockLiveness, FunctionLiveness, LivenessReport, UndefinedUse,
+};
+pub use self::assembly::analysis::stack::{FunctionStack, StackIssue, StackReport};
+pub use self::assembly::batch::{BatchAssembler, BatchOutput, BatchSource};
+pub use self::assembly::builder::AssemblyBuilder;
+pub use self::assembly::config::{
+    AssemblerConfig, LandingPadPolicy, MetadataPolicy, NullDestinationPolicy,
+};
+pub use self::assembly::constants::ConstantValue;
+pub use self::assembly::debugger::{BacktraceFrame, Debugger, StopReason};
+pub use self::assembly::instruction::isa::IsaFeature;
+pub use self::assembly::instruction::metadata::{
+    ControlFlow, InstructionMetadata, MemoryEffect, OperandInfo, OperandRef, OperandRole,
+};
+pub use self::assembly::instruction::registers::RegisterSet;
 pub use self::assembly::instruction::Instruction;
+pub use self::assembly::language::{
+    Completion, CompletionKind, SourceDiagnostic, SourceFile, SourceRange,
+};
+pub use self::assembly::linking::LinkedAssembly;
+pub use self::assembly::mnemonic::Mnemonic;
 pub use self::assembly::operand::FullOperand;
+pub use self::assembly::operand::GenericOperand;
+pub use self::assembly::operand::NonMemoryOperand;
 pub use self::assembly::operand::RegisterOperand;
+pub use self::assembly::section::{
+    CodeElement, ConstantElement, DataSection, DataSectionElement, GlobalsSection,
+    GlobalsSectionElement, LabeledConstant, LabeledFunction, LabeledGlobal, ParsedSection,
+    TextSection, TextSectionElement,
+};
+pub use self::assembly::simulator::host::{FarCallRequest, FarCallResult, Host, MockHost};
+pub use self::assembly::simulator::{
+    ExecutionOutcome, ExecutionStatus, FatPointer, Flags, Frame, OutcomeKind, Simulator,
+};
 pub use self::assembly::Assembly;
-pub use self::error::{AssemblyParseError, BinaryParseError, InstructionReadError};
+pub use self::error::{
+    AssemblyParseError, BinaryParseError, Error, InstructionReadError, SimulationError,
+};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 #[repr(u64)]
 pub enum RunningVmEncodingMode {
     Production = 0,
@@ -58,10 +101,12 @@ use std::sync::atomic::AtomicU64;
 
 pub static ENCODING_MODE: AtomicU64 = AtomicU64::new(RunningVmEncodingMode::Production as u64);
 
+#[deprecated(note = "pass the `AssemblerConfig` explicitly instead")]
 pub fn set_encoding_mode(value: RunningVmEncodingMode) {
     ENCODING_MODE.store(value.as_u64(), std::sync::atomic::Ordering::SeqCst);
 }
 
+#[deprecated(note = "pass the `AssemblerConfig` explicitly instead")]
 pub fn get_encoding_mode() -> RunningVmEncodingMode {
     RunningVmEncodingMode::from_u64(ENCODING_MODE.load(std::sync::atomic::Ordering::Relaxed))
 }
@@ -75,10 +120,12 @@ const _: () = if DEFAULT_ISA_VERSION.0 != zkevm_opcode_defs::DEFAULT_ISA_VERSION
 
 pub static ISA_VERSION_U64: AtomicU64 = AtomicU64::new(DEFAULT_ISA_VERSION.0 as u64);
 
+#[deprecated(note = "pass the `AssemblerConfig` explicitly instead")]
 pub fn set_isa_version(value: ISAVersion) {
     ISA_VERSION_U64.store(value.0 as u64, std::sync::atomic::Ordering::SeqCst);
 }
 
+#[deprecated(note = "pass the `AssemblerConfig` explicitly instead")]
 pub fn get_isa_version() -> ISAVersion {
     ISAVersion(ISA_VERSION_U64.load(std::sync::atomic::Ordering::Relaxed) as u8)
 }
@@ -0,0 +1,16 @@
+//!
+//! zkEVM assembly language server binary.
+//!
+
+pub mod server;
+
+///
+/// The application entry point, which talks LSP over stdio.
+///
+fn main() {
+    env_logger::init();
+
+    let (connection, io_threads) = lsp_server::Connection::stdio();
+    server::run(&connection).expect("Language server failure");
+    io_threads.join().expect("Language server stdio failure");
+}
@@ -0,0 +1,449 @@
+//!
+//! The language server over the `lsp-server` connection.
+//!
+//! The LSP positions count the UTF-16 code units of the line, so they are converted from and
+//! to the character columns of the source analysis.
+//!
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, RequestId, Response};
+use lsp_types::notification::Notification as _;
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, PublishDiagnostics,
+};
+use lsp_types::request::Request as _;
+use lsp_types::request::{Completion, GotoDefinition, HoverRequest};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, CompletionResponse,
+    Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents,
+    HoverParams, HoverProviderCapability, Location, MarkupContent, MarkupKind, OneOf, Position,
+    PublishDiagnosticsParams, Range, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, Url,
+};
+use std::collections::HashMap;
+use std::error::Error;
+use std::panic::AssertUnwindSafe;
+use zkevm_assembly::{
+    AssemblerConfig, CompletionKind, RunningVmEncodingMode, SourceFile, SourceRange,
+    DEFAULT_ISA_VERSION,
+};
+
+///
+/// The open documents and the configuration they are checked with.
+///
+struct Server {
+    config: AssemblerConfig,
+    documents: HashMap<Url, SourceFile>,
+}
+
+///
+/// Serves the initialized connection until the shutdown.
+///
+pub fn run(connection: &Connection) -> Result<(), Box<dyn Error + Sync + Send>> {
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        definition_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![".".to_owned(), "@".to_owned()]),
+            ..CompletionOptions::default()
+        }),
+        ..ServerCapabilities::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    let mut server = Server {
+        config: AssemblerConfig::new(RunningVmEncodingMode::Production, DEFAULT_ISA_VERSION),
+        documents: HashMap::new(),
+    };
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                connection
+                    .sender
+                    .send(Message::Response(server.request(request)))?;
+            }
+            Message::Notification(notification) => {
+                if let Some(notification) = server.notification(notification)? {
+                    connection
+                        .sender
+                        .send(Message::Notification(notification))?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+impl Server {
+    fn request(&self, request: Request) -> Response {
+        let id = request.id.clone();
+        let result = match request.method.as_str() {
+            GotoDefinition::METHOD => self.respond(request, Self::definition),
+            HoverRequest::METHOD => self.respond(request, Self::hover),
+            Completion::METHOD => self.respond(request, Self::completion),
+            method => {
+                return Response::new_err(
+                    id,
+                    ErrorCode::MethodNotFound as i32,
+                    format!("unknown method `{}`", method),
+                )
+            }
+        };
+
+        match result {
+            Ok(response) => response,
+            Err(error) => Response::new_err(id, ErrorCode::InternalError as i32, error),
+        }
+    }
+
+    ///
+    /// Answers the request with the handler, turning its panics into errors, since the parser
+    /// asserts on some malformed input.
+    ///
+    fn respond<P, R>(
+        &self,
+        request: Request,
+        handler: fn(&Self, P) -> Option<R>,
+    ) -> Result<Response, String>
+    where
+        P: serde::de::DeserializeOwned,
+        R: serde::Serialize,
+    {
+        let params: P =
+            serde_json::from_value(request.params).map_err(|error| error.to_string())?;
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| handler(self, params)))
+            .map_err(|_| "the request handler panicked".to_owned())?;
+        Ok(Response::new_ok(request.id, result))
+    }
+
+    fn definition(&self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let position = params.text_document_position_params;
+        let uri = position.text_document.uri;
+        let document = self.documents.get(&uri)?;
+        let range = document.definition_at(
+            position.position.line as usize,
+            column(document, position.position),
+        )?;
+        Some(GotoDefinitionResponse::Scalar(Location::new(
+            uri,
+            lsp_range(document, range),
+        )))
+    }
+
+    fn hover(&self, params: HoverParams) -> Option<Hover> {
+        let position = params.text_document_position_params;
+        let expansion = self
+            .documents
+            .get(&position.text_document.uri)?
+            .expansion(position.position.line as usize)?;
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: format!("```\n{}\n```", expansion),
+            }),
+            range: None,
+        })
+    }
+
+    fn completion(&self, params: CompletionParams) -> Option<CompletionResponse> {
+        let position = params.text_document_position;
+        let document = self.documents.get(&position.text_document.uri)?;
+        let items = document
+            .completions(
+                position.position.line as usize,
+                column(document, position.position),
+            )
+            .into_iter()
+            .map(|completion| CompletionItem {
+                label: completion.label,
+                kind: Some(match completion.kind {
+                    CompletionKind::Mnemonic => CompletionItemKind::KEYWORD,
+                    CompletionKind::Modifier => CompletionItemKind::ENUM_MEMBER,
+                    CompletionKind::Register => CompletionItemKind::VARIABLE,
+                    CompletionKind::Label => CompletionItemKind::REFERENCE,
+                }),
+                ..CompletionItem::default()
+            })
+            .collect();
+        Some(CompletionResponse::Array(items))
+    }
+
+    ///
+    /// Updates the documents, returning the diagnostics to publish.
+    ///
+    fn notification(
+        &mut self,
+        notification: Notification,
+    ) -> Result<Option<Notification>, serde_json::Error> {
+        let (uri, version) = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let document = params.text_document;
+                self.documents
+                    .insert(document.uri.clone(), SourceFile::new(document.text));
+                (document.uri, Some(document.version))
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let document = params.text_document;
+                if let Some(change) = params.content_changes.into_iter().last() {
+                    self.documents
+                        .insert(document.uri.clone(), SourceFile::new(change.text));
+                }
+                (document.uri, Some(document.version))
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                self.documents.remove(&params.text_document.uri);
+                (params.text_document.uri, None)
+            }
+            _ => return Ok(None),
+        };
+
+        let diagnostics = self.diagnostics(&uri);
+        let params = PublishDiagnosticsParams::new(uri, diagnostics, version);
+        Ok(Some(Notification::new(
+            PublishDiagnostics::METHOD.to_owned(),
+            params,
+        )))
+    }
+
+    fn diagnostics(&self, uri: &Url) -> Vec<Diagnostic> {
+        let document = match self.documents.get(uri) {
+            Some(document) => document,
+            None => return vec![],
+        };
+
+        match std::panic::catch_unwind(AssertUnwindSafe(|| document.diagnostics(self.config))) {
+            Ok(diagnostics) => diagnostics
+                .into_iter()
+                .map(|diagnostic| Diagnostic {
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    ..Diagnostic::new_simple(
+                        lsp_range(document, diagnostic.range),
+                        diagnostic.message,
+                    )
+                })
+                .collect(),
+            Err(_) => vec![Diagnostic::new_simple(
+                Range::default(),
+                "the assembler panicked on this document".to_owned(),
+            )],
+        }
+    }
+}
+
+///
+/// The character column of the LSP position.
+///
+fn column(document: &SourceFile, position: Position) -> usize {
+    let line = document
+        .text()
+        .lines()
+        .nth(position.line as usize)
+        .unwrap_or_default();
+    let mut units = 0;
+    line.chars()
+        .take_while(|character| {
+            units += character.len_utf16();
+            units <= position.character as usize
+        })
+        .count()
+}
+
+///
+/// The LSP range of the character columns.
+///
+fn lsp_range(document: &SourceFile, range: SourceRange) -> Range {
+    let line = document.text().lines().nth(range.line).unwrap_or_default();
+    let units = |column: usize| -> u32 {
+        line.chars()
+            .take(column)
+            .map(char::len_utf16)
+            .sum::<usize>() as u32
+    };
+    Range::new(
+        Position::new(range.line as u32, units(range.start)),
+        Position::new(range.line as u32, units(range.end)),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lsp_types::notification::{Exit, Initialized};
+    use lsp_types::request::{Initialize, Shutdown};
+    use lsp_types::{
+        InitializeParams, InitializedParams, TextDocumentIdentifier, TextDocumentItem,
+        TextDocumentPositionParams,
+    };
+    use std::thread::JoinHandle;
+
+    ///
+    /// The client end of the server running over the in-memory connection.
+    ///
+    struct Client {
+        connection: Connection,
+        server: JoinHandle<()>,
+        next_id: i32,
+    }
+
+    impl Client {
+        fn start() -> Self {
+            let (server, connection) = Connection::memory();
+            let server = std::thread::spawn(move || run(&server).unwrap());
+            let mut client = Self {
+                connection,
+                server,
+                next_id: 0,
+            };
+            client.request::<Initialize>(InitializeParams::default());
+            client.notify::<Initialized>(InitializedParams {});
+            client
+        }
+
+        fn request<R: lsp_types::request::Request>(&mut self, params: R::Params) -> R::Result {
+            self.next_id += 1;
+            let id = RequestId::from(self.next_id);
+            let request = Request::new(id.clone(), R::METHOD.to_owned(), params);
+            self.connection.sender.send(request.into()).unwrap();
+            match self.connection.receiver.recv().unwrap() {
+                Message::Response(response) => {
+                    assert_eq!(response.id, id);
+                    serde_json::from_value(response.result.unwrap()).unwrap()
+                }
+                message => panic!("expected the response, found {:?}", message),
+            }
+        }
+
+        fn notify<N: lsp_types::notification::Notification>(&self, params: N::Params) {
+            let notification = Notification::new(N::METHOD.to_owned(), params);
+            self.connection.sender.send(notification.into()).unwrap();
+        }
+
+        fn diagnostics(&self) -> PublishDiagnosticsParams {
+            match self.connection.receiver.recv().unwrap() {
+                Message::Notification(notification) => {
+                    assert_eq!(notification.method, PublishDiagnostics::METHOD);
+                    serde_json::from_value(notification.params).unwrap()
+                }
+                message => panic!("expected the diagnostics, found {:?}", message),
+            }
+        }
+
+        fn stop(mut self) {
+            self.request::<Shutdown>(());
+            self.notify::<Exit>(());
+            self.server.join().unwrap();
+        }
+    }
+
+    fn position(uri: &Url, line: u32, character: u32) -> TextDocumentPositionParams {
+        TextDocumentPositionParams::new(
+            TextDocumentIdentifier::new(uri.clone()),
+            Position::new(line, character),
+        )
+    }
+
+    #[test]
+    fn test_session() {
+        let uri = Url::parse("file:///contract.sasm").unwrap();
+        let mut client = Client::start();
+
+        client.notify::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(
+                uri.clone(),
+                "sasm".to_owned(),
+                1,
+                "\t.text\n__entry:\n\tnear_call r0, @f, @DEFAULT_UNWIND\n\tret\nf:\n\tjump @g\n"
+                    .to_owned(),
+            ),
+        });
+        let diagnostics = client.diagnostics();
+        assert_eq!(diagnostics.version, Some(1));
+        assert_eq!(diagnostics.diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics.diagnostics[0].range,
+            Range::new(Position::new(5, 7), Position::new(5, 8))
+        );
+
+        let definition = client.request::<GotoDefinition>(GotoDefinitionParams {
+            text_document_position_params: position(&uri, 2, 17),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        });
+        assert_eq!(
+            definition,
+            Some(GotoDefinitionResponse::Scalar(Location::new(
+                uri.clone(),
+                Range::new(Position::new(4, 0), Position::new(4, 1))
+            )))
+        );
+
+        let hover = client.request::<HoverRequest>(HoverParams {
+            text_document_position_params: position(&uri, 3, 2),
+            work_done_progress_params: Default::default(),
+        });
+        assert_eq!(
+            hover.unwrap().contents,
+            HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: "```\nret.ok r1\n```".to_owned(),
+            })
+        );
+
+        client.notify::<DidChangeTextDocument>(DidChangeTextDocumentParams {
+            text_document: lsp_types::VersionedTextDocumentIdentifier::new(uri.clone(), 2),
+            content_changes: vec![lsp_types::TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: "\t.text\n__entry:\n\tret\n\t.rodata\nc:\n\t.cell 1\n\tadd @".to_owned(),
+            }],
+        });
+        assert_eq!(client.diagnostics().diagnostics.len(), 1);
+
+        let completion = client.request::<Completion>(CompletionParams {
+            text_document_position: position(&uri, 6, 6),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: None,
+        });
+        let labels: Vec<String> = match completion {
+            Some(CompletionResponse::Array(items)) => {
+                items.into_iter().map(|item| item.label).collect()
+            }
+            response => panic!("unexpected completion {:?}", response),
+        };
+        assert_eq!(labels, vec!["__entry".to_owned(), "c".to_owned()]);
+
+        client.stop();
+    }
+
+    #[test]
+    fn test_utf16_positions() {
+        let document = SourceFile::new("\t.text\n; 🙂\n__entry:\n\tret\n; é\nf:\n".to_owned());
+        // the emoji takes two UTF-16 code units, but it is a single character
+        assert_eq!(column(&document, Position::new(1, 2)), 2);
+        assert_eq!(column(&document, Position::new(1, 4)), 3);
+        assert_eq!(column(&document, Position::new(4, 3)), 3);
+
+        let range = SourceRange {
+            line: 1,
+            start: 2,
+            end: 3,
+        };
+        assert_eq!(
+            lsp_range(&document, range),
+            Range::new(Position::new(1, 2), Position::new(1, 4))
+        );
+    }
+}
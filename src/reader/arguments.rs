@@ -18,6 +18,10 @@ pub struct Arguments {
     /// Output file, stdout if not present
     #[structopt(parse(from_os_str))]
     pub output: Option<PathBuf>,
+
+    /// Output the ergs cost report instead of the bytecode
+    #[structopt(long = "cost-report")]
+    pub cost_report: bool,
 }
 
 impl Arguments {
@@ -17,18 +17,25 @@ fn main() {
 
     let args = Arguments::new();
 
-    let mut assembly =
-        zkevm_assembly::Assembly::try_from(args.input).expect("Assembly file reading");
+    let assembly = zkevm_assembly::Assembly::try_from(args.input).expect("Assembly file reading");
 
-    let serialized = assembly
-        .compile_to_bytecode_for_mode::<8, EncodingModeProduction>()
-        .expect("Must compile the bytecode");
-
-    let mut pretty_bytecode = String::with_capacity((64 + 2) * serialized.len() + 100);
-    for el in serialized.into_iter() {
-        std::fmt::write(&mut pretty_bytecode, format_args!("{}", hex::encode(el)))
-            .expect("Error occurred while trying to write in String");
-    }
+    let output = if args.cost_report {
+        assembly
+            .cost_report::<8, EncodingModeProduction>()
+            .expect("Must link the assembly")
+            .to_string()
+    } else {
+        let serialized = assembly
+            .compile_to_bytecode_for_mode::<8, EncodingModeProduction>()
+            .expect("Must compile the bytecode");
+
+        let mut pretty_bytecode = String::with_capacity((64 + 2) * serialized.len() + 100);
+        for el in serialized.into_iter() {
+            std::fmt::write(&mut pretty_bytecode, format_args!("{}", hex::encode(el)))
+                .expect("Error occurred while trying to write in String");
+        }
+        pretty_bytecode
+    };
 
     if let Some(path) = args.output {
         let mut file = std::fs::OpenOptions::new()
@@ -36,11 +43,11 @@ fn main() {
             .write(true)
             .open(path)
             .expect("can not open an output file");
-        file.write_all(pretty_bytecode.as_bytes())
+        file.write_all(output.as_bytes())
             .expect("can not write to file");
     } else {
         std::io::stdout()
-            .write_all(pretty_bytecode.as_bytes())
+            .write_all(output.as_bytes())
             .expect("can not write to stdout");
     }
 }
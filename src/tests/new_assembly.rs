@@ -26,8 +26,338 @@ fn test_memory_syntax() {
         .cell 1
         .cell 4
     "#;
-    let mut asm = Assembly::try_from(asm_text.to_owned()).unwrap();
+    let asm = Assembly::try_from(asm_text.to_owned()).unwrap();
     let _bc = asm.compile_to_bytecode_for_mode::<8, EncodingModeProduction>().unwrap();
-    let instructions = asm.opcodes::<8, EncodingModeProduction>();
-    dbg!(&instructions);
-}
\ No newline at end of file
+    let linked = asm.link::<8, EncodingModeProduction>().unwrap();
+    dbg!(linked.opcodes());
+}
+
+#[test]
+fn test_constant_pool_deduplication() {
+    let asm_text = r#"
+    .text
+    .globl  __entry
+    __entry:
+        add @CPI0_0[0], r0, r1
+        add @CPI0_1[0], r0, r2
+        add @CPI0_3[0], r0, r3
+        add @CPI0_2[1], r0, r4
+        ret.ok r0
+    .rodata
+    CPI0_0:
+        .cell 777
+    CPI0_1:
+        .cell 777
+    CPI0_2:
+        .cell 1
+        .cell 2
+    CPI0_3:
+        .cell 2
+    "#;
+    let mut asm = Assembly::try_from(asm_text.to_owned()).unwrap();
+    let bytecode = asm.compile_to_bytecode().unwrap();
+    assert_eq!(asm.deduplicated_constants, 2);
+    // 2 code words, 3 distinct constants, the padding word and the metadata hash
+    assert_eq!(bytecode.len(), 7);
+
+    let linked = asm.link::<8, EncodingModeProduction>().unwrap();
+    let offsets: Vec<_> = linked.instructions()[..4]
+        .iter()
+        .map(|instruction| match instruction {
+            crate::Instruction::Add(add) => add.source_1.clone(),
+            _ => unreachable!(),
+        })
+        .collect();
+    assert_eq!(offsets[0], offsets[1]);
+    assert_eq!(offsets[2], offsets[3]);
+
+    // the indexed label keeps its word, so `@CPI0_1[1]` still reads the first word of `CPI0_2`
+    let indexed = asm_text.replace("@CPI0_1[0]", "@CPI0_1[1]");
+    let asm = Assembly::try_from(indexed).unwrap();
+    let linked = asm.link::<8, EncodingModeProduction>().unwrap();
+    assert_eq!(linked.deduplicated_constants(), 1);
+}
+
+#[test]
+fn test_link_for_different_modes() {
+    use zkevm_opcode_defs::decoding::EncodingModeTesting;
+
+    let asm_text = r#"
+    .text
+    .globl  __entry
+    __entry:
+        add 1, r0, r1
+        add 2, r1, r2
+        ret.ok r0
+    "#;
+    let mut asm = Assembly::try_from(asm_text.to_owned()).unwrap();
+    let production = asm.link::<8, EncodingModeProduction>().unwrap();
+    let testing = asm.link::<16, EncodingModeTesting>().unwrap();
+    assert_eq!(production.opcodes().len(), production.instructions().len());
+    assert_eq!(testing.opcodes().len(), testing.instructions().len());
+
+    // compiling for another mode than the configured one must not leak into the kept outputs
+    let bytecode = asm.compile_to_bytecode_for_mode::<16, EncodingModeTesting>().unwrap();
+    assert_eq!(bytecode.as_slice(), testing.bytecode());
+    assert_ne!(production.bytecode(), testing.bytecode());
+    assert!(asm.bytecode.is_empty());
+    assert!(asm.function_labels.is_empty());
+
+    let bytecode = asm.compile_to_bytecode().unwrap();
+    assert_eq!(bytecode.as_slice(), production.bytecode());
+    assert_eq!(&asm.function_labels, production.function_labels());
+    assert_eq!(&asm.pc_line_mapping, production.pc_line_mapping());
+}
+
+#[test]
+fn test_load_immediate_for_different_modes() {
+    use crate::{FullOperand, GenericOperand};
+    use zkevm_opcode_defs::decoding::EncodingModeTesting;
+    use zkevm_opcode_defs::ImmMemHandlerFlags;
+
+    let asm_text = r#"
+    .text
+    .globl  __entry
+    __entry:
+        li 42, r1
+        li 0x1_2345, r2
+        load_imm 0x1_0000_0000_0000_0000, r3
+        li 0x1_0000_0000_0000_0000, r4
+        add @CPI0_0[0], r0, r5
+        ret.ok r0
+    .rodata
+    CPI0_0:
+        .cell 0x10000000000000000
+    "#;
+    let sources = |instructions: &[crate::Instruction]| -> Vec<GenericOperand> {
+        instructions[..5]
+            .iter()
+            .map(|instruction| match instruction {
+                crate::Instruction::Add(add) => match add.source_1.clone() {
+                    FullOperand::Full(operand) => operand,
+                    operand => panic!("unlinked operand {:?}", operand),
+                },
+                _ => unreachable!(),
+            })
+            .collect()
+    };
+
+    let asm = Assembly::try_from(asm_text.to_owned()).unwrap();
+    // the unlinked `li` is printed without its internal label
+    let printed: Vec<_> = asm
+        .code_elements()
+        .map(|element| element.instruction().to_string())
+        .collect();
+    assert_eq!(printed[0], "li 0x2a, r1");
+    assert_eq!(printed[2], "li 0x10000000000000000, r3");
+
+    let production = asm.link::<8, EncodingModeProduction>().unwrap();
+    let operands = sources(production.instructions());
+    assert_eq!(operands[0], GenericOperand::immediate(42));
+    assert_eq!(operands[1].r#type, ImmMemHandlerFlags::UseCodePage);
+    assert_eq!(operands[2].r#type, ImmMemHandlerFlags::UseCodePage);
+    assert_eq!(operands[2], operands[3]);
+    assert_eq!(operands[2], operands[4]);
+    assert_eq!(production.deduplicated_constants(), 1);
+
+    let testing = asm.link::<16, EncodingModeTesting>().unwrap();
+    let operands = sources(testing.instructions());
+    assert_eq!(operands[0], GenericOperand::immediate(42));
+    assert_eq!(operands[1], GenericOperand::immediate(0x1_2345));
+    assert_eq!(operands[2].r#type, ImmMemHandlerFlags::UseCodePage);
+    assert_eq!(operands[2], operands[4]);
+    assert_eq!(testing.deduplicated_constants(), 1);
+}
+
+#[test]
+fn test_explicit_config() {
+    use crate::assembly::config::*;
+    use crate::RunningVmEncodingMode;
+
+    let asm_text = r#"
+    .text
+    .globl  __entry
+    __entry:
+        add 1, r0, r1
+        ret.ok r0
+    "#;
+    let config = AssemblerConfig::new(RunningVmEncodingMode::Testing, crate::DEFAULT_ISA_VERSION);
+    let mut asm = Assembly::from_string_with_config(asm_text.to_owned(), config).unwrap();
+    // 2 instructions and 3 landing pads take 3 words in the testing mode, then the metadata hash
+    let bytecode = asm.compile_to_bytecode().unwrap();
+    assert_eq!(bytecode.len(), 5);
+    assert_eq!(asm.function_labels.len(), 4);
+
+    let config = config
+        .with_metadata_policy(MetadataPolicy::Omit)
+        .with_landing_pad_policy(LandingPadPolicy::Omit);
+    let mut asm = Assembly::from_string_with_config(asm_text.to_owned(), config).unwrap();
+    // the aligned code still gets a word of padding, and the length must be odd
+    let bytecode = asm.compile_to_bytecode().unwrap();
+    assert_eq!(bytecode.len(), 3);
+    assert_eq!(asm.function_labels.len(), 1);
+}
+
+#[test]
+fn test_landing_pad_directives() {
+    use crate::assembly::config::*;
+    use crate::{FullOperand, RunningVmEncodingMode};
+
+    let asm_text = r#"
+    .text
+    .landing_pads referenced
+    .globl  __entry
+    __entry:
+        .unwind @handler
+        call @f
+    .BB0_1:
+        call @f
+        ret.ok r0
+    f:
+        call @g
+        ret.ok r0
+    g:
+        ret.ok r0
+    handler:
+        ret.panic r0
+    "#;
+    let config = AssemblerConfig::new(RunningVmEncodingMode::Testing, crate::DEFAULT_ISA_VERSION);
+    let mut asm = Assembly::from_string_with_config(asm_text.to_owned(), config).unwrap();
+    assert_eq!(asm.config.landing_pad_policy, LandingPadPolicy::Referenced);
+    let handlers: Vec<FullOperand> = asm
+        .code_elements()
+        .filter_map(|element| match element.instruction() {
+            crate::Instruction::NearCall(call) => Some(call.exception_handler.clone()),
+            _ => None,
+        })
+        .collect();
+    let handler = FullOperand::label("handler");
+    assert_eq!(
+        handlers,
+        vec![handler.clone(), handler, FullOperand::label("DEFAULT_UNWIND")]
+    );
+
+    // only the unwind pad is referenced, by the call in `f`
+    asm.compile_to_bytecode().unwrap();
+    assert!(asm.function_labels.contains_key("DEFAULT_UNWIND"));
+    assert!(!asm.function_labels.contains_key("DEFAULT_FAR_RETURN"));
+    assert!(!asm.function_labels.contains_key("DEFAULT_FAR_REVERT"));
+
+    let invalid = asm_text.replace("@handler", "handler");
+    assert!(Assembly::from_string_with_config(invalid, config).is_err());
+
+    // only the default policy of the configuration is overridden
+    let omitting = config.with_landing_pad_policy(LandingPadPolicy::Omit);
+    assert_eq!(
+        Assembly::from_string_with_config(asm_text.to_owned(), omitting).unwrap_err(),
+        crate::error::AssemblyParseError::LandingPadPolicyConflict(
+            LandingPadPolicy::Referenced,
+            LandingPadPolicy::Omit
+        )
+    );
+    let referencing = config.with_landing_pad_policy(LandingPadPolicy::Referenced);
+    assert!(Assembly::from_string_with_config(asm_text.to_owned(), referencing).is_ok());
+}
+
+#[test]
+fn test_entry_directive() {
+    use crate::error::{AssemblyParseError, InstructionReadError};
+
+    let asm_text = r#"
+    .data
+    val:
+        .cell 5
+    .text
+    .type f,@function
+    f:
+        add 1, r0, r1
+    .BB0_1:
+        ret.ok r1
+    .size f, .Lfunc_end0-f
+    .type __entry,@function
+    .entry __entry
+    __entry:
+        near_call r0, @f, @DEFAULT_UNWIND
+    .BB1_1:
+        ret.ok r0
+    .size __entry, .Lfunc_end1-__entry
+    "#;
+    let asm = Assembly::try_from(asm_text.to_owned()).unwrap();
+    assert_eq!(asm.entry(), Some("__entry"));
+
+    // the globals initializer is followed by the entry and its local blocks
+    let linked = asm.link::<8, EncodingModeProduction>().unwrap();
+    assert_eq!(linked.globals_size(), 1);
+    assert_eq!(linked.function_labels()["__entry"], 2);
+    assert_eq!(linked.function_labels()[".BB1_1"], 3);
+    assert_eq!(linked.function_labels()["f"], 4);
+    assert_eq!(linked.function_sizes()["__entry"], 2);
+    assert_eq!(linked.function_sizes()["f"], 2);
+    assert!(!linked.function_sizes().contains_key(".BB0_1"));
+
+    let missing = asm_text.replace(".entry __entry", ".entry main");
+    assert_eq!(
+        Assembly::try_from(missing).unwrap_err(),
+        AssemblyParseError::LabelNotFound("main".to_owned())
+    );
+    let undeclared = asm_text.replace(".type f,@function", "");
+    assert_eq!(
+        Assembly::try_from(undeclared).unwrap_err(),
+        AssemblyParseError::NotAFunction("f".to_owned())
+    );
+
+    // the moved entry must neither be fallen into nor fall through itself
+    let fallen_into = asm_text.replace("ret.ok r1", "add 2, r0, r1");
+    assert_eq!(
+        Assembly::try_from(fallen_into).unwrap().link::<8, EncodingModeProduction>().unwrap_err(),
+        InstructionReadError::AssemblyParseError(AssemblyParseError::EntryFallenInto(
+            "__entry".to_owned()
+        ))
+    );
+    let not_terminated = asm_text.replace("ret.ok r0", "add 2, r0, r1");
+    assert_eq!(
+        Assembly::try_from(not_terminated).unwrap().link::<8, EncodingModeProduction>().unwrap_err(),
+        InstructionReadError::AssemblyParseError(AssemblyParseError::EntryNotTerminated(
+            "__entry".to_owned()
+        ))
+    );
+
+    // the symbol directives are accepted in the data sections as well
+    let data_symbols = asm_text.replace("val:", ".type val,@object\n    .size val, 32\n    val:");
+    let asm = Assembly::try_from(data_symbols).unwrap();
+    assert_eq!(asm.entry(), Some("__entry"));
+    asm.link::<8, EncodingModeProduction>().unwrap();
+}
+
+#[test]
+fn test_isa_version_validation() {
+    use crate::assembly::config::AssemblerConfig;
+    use crate::error::{AssemblyParseError, InstructionReadError, SectionReadError};
+    use crate::{ISAVersion, RunningVmEncodingMode};
+
+    let asm_text = r#"
+    .text
+    .globl  __entry
+    __entry:
+        uma.heap_read r1, r0, r2, r0
+        uma.heap_read 32, r0, r2, r0
+        ret.ok r0
+    "#;
+    let config = AssemblerConfig::new(RunningVmEncodingMode::Production, ISAVersion(0));
+    let error = Assembly::from_string_with_config(asm_text.to_owned(), config).unwrap_err();
+    match error {
+        AssemblyParseError::TextSectionInvalid(SectionReadError::LineReadError(errors)) => {
+            assert_eq!(errors.len(), 1);
+            let (line, error) = errors.values().next().unwrap();
+            assert_eq!(line, "uma.heap_read 32, r0, r2, r0");
+            assert_eq!(
+                error,
+                &InstructionReadError::NotAvailableInIsaVersion("uma with an immediate source", 0)
+            );
+        }
+        error => panic!("unexpected error {:?}", error),
+    }
+
+    let config = AssemblerConfig::new(RunningVmEncodingMode::Production, ISAVersion(1));
+    assert!(Assembly::from_string_with_config(asm_text.to_owned(), config).is_ok());
+}